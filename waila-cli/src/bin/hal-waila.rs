@@ -0,0 +1,44 @@
+//! `hal waila <string>`, hal runs `hal-<subcommand>` binaries it finds on the `PATH`.
+//! Prints JSON like the rest of hal's commands.
+
+use std::process;
+use std::str::FromStr;
+
+use bitcoin_waila::PaymentParams;
+use serde_json::{Map, Value};
+
+const USAGE: &str = "usage: hal waila <string>
+
+Decodes a bitcoin-related string with waila and prints what it is as JSON.";
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let string = match args.as_slice() {
+        [flag] if flag == "-h" || flag == "--help" => {
+            println!("{USAGE}");
+            return;
+        }
+        [string] => string,
+        _ => {
+            eprintln!("{USAGE}");
+            process::exit(2);
+        }
+    };
+
+    match PaymentParams::from_str(string.trim()) {
+        Ok(params) => {
+            let object: Map<String, Value> = params
+                .describe()
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value))
+                .collect();
+            let json = serde_json::to_string_pretty(&Value::Object(object))
+                .expect("a json value always serializes");
+            println!("{json}");
+        }
+        Err(e) => {
+            eprintln!("error: {e}");
+            process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use std::io::{self, BufRead};
+use std::process;
+use std::str::FromStr;
+
+use bitcoin_waila::PaymentParams;
+use serde_json::{Map, Value};
+
+const USAGE: &str = "usage: waila [--json] <string>
+
+Decodes a bitcoin-related string and prints what it is.
+Reads the string from stdin when it isn't given.
+
+  --json    print the fields as a JSON object";
+
+fn print_fields(fields: Vec<(&'static str, Value)>, json: bool) {
+    if json {
+        let object: Map<String, Value> = fields
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect();
+        println!("{}", Value::Object(object));
+        return;
+    }
+
+    let width = fields.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    for (name, value) in fields {
+        match value {
+            Value::String(s) => println!("{name:width$}  {s}"),
+            value => println!("{name:width$}  {value}"),
+        }
+    }
+}
+
+fn main() {
+    let mut json = false;
+    let mut string = None;
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--json" => json = true,
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return;
+            }
+            _ if string.is_none() => string = Some(arg),
+            _ => {
+                eprintln!("{USAGE}");
+                process::exit(2);
+            }
+        }
+    }
+
+    let string = match string {
+        Some(string) => string,
+        None => match io::stdin().lock().lines().next() {
+            Some(Ok(line)) => line,
+            _ => {
+                eprintln!("{USAGE}");
+                process::exit(2);
+            }
+        },
+    };
+
+    match PaymentParams::from_str(string.trim()) {
+        Ok(params) => print_fields(params.describe(), json),
+        Err(e) => {
+            if json {
+                println!("{}", serde_json::json!({ "error": e.to_string() }));
+            } else {
+                eprintln!("error: {e}");
+            }
+            process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,201 @@
+/* Generated with cbindgen, run `just ffi-header` to regenerate */
+
+#ifndef WAILA_H
+#define WAILA_H
+
+#include <stdbool.h>
+#include <stdint.h>
+
+/**
+ * The result of parsing a string, opaque to C
+ */
+typedef struct WailaPaymentParams WailaPaymentParams;
+
+/**
+ * Parses a nul terminated UTF-8 string, returning null if it couldn't be parsed.
+ *
+ * On failure `error` is set to why, if it isn't null, and must be freed with
+ * `waila_string_free`.
+ *
+ * # Safety
+ *
+ * `string` must be a valid nul terminated string and `error` either null or
+ * valid for writes.
+ */
+WailaPaymentParams *waila_parse(const char *string, char **error);
+
+/**
+ * Frees params returned by `waila_parse`, null is ignored.
+ *
+ * # Safety
+ *
+ * `params` must be null or returned by `waila_parse` and not already freed.
+ */
+void waila_payment_params_free(WailaPaymentParams *params);
+
+/**
+ * Frees a string returned by waila, null is ignored.
+ *
+ * # Safety
+ *
+ * `string` must be null or returned by waila and not already freed.
+ */
+void waila_string_free(char *string);
+
+/**
+ * What kind of string was parsed, e.g. `on_chain` or `bolt11`
+ *
+ * # Safety
+ *
+ * `params` must be null or returned by `waila_parse` and not freed.
+ */
+char *waila_payment_params_kind(const WailaPaymentParams *params);
+
+/**
+ * The canonical encoding of what was parsed
+ *
+ * # Safety
+ *
+ * `params` must be null or returned by `waila_parse` and not freed.
+ */
+char *waila_payment_params_canonical_string(const WailaPaymentParams *params);
+
+/**
+ * `bitcoin`, `testnet`, `signet` or `regtest`
+ *
+ * # Safety
+ *
+ * `params` must be null or returned by `waila_parse` and not freed.
+ */
+char *waila_payment_params_network(const WailaPaymentParams *params);
+
+/**
+ * # Safety
+ *
+ * `params` must be null or returned by `waila_parse` and not freed.
+ */
+char *waila_payment_params_label(const WailaPaymentParams *params);
+
+/**
+ * # Safety
+ *
+ * `params` must be null or returned by `waila_parse` and not freed.
+ */
+char *waila_payment_params_message(const WailaPaymentParams *params);
+
+/**
+ * # Safety
+ *
+ * `params` must be null or returned by `waila_parse` and not freed.
+ */
+char *waila_payment_params_memo(const WailaPaymentParams *params);
+
+/**
+ * # Safety
+ *
+ * `params` must be null or returned by `waila_parse` and not freed.
+ */
+char *waila_payment_params_address(const WailaPaymentParams *params);
+
+/**
+ * # Safety
+ *
+ * `params` must be null or returned by `waila_parse` and not freed.
+ */
+char *waila_payment_params_invoice(const WailaPaymentParams *params);
+
+/**
+ * # Safety
+ *
+ * `params` must be null or returned by `waila_parse` and not freed.
+ */
+char *waila_payment_params_offer(const WailaPaymentParams *params);
+
+/**
+ * # Safety
+ *
+ * `params` must be null or returned by `waila_parse` and not freed.
+ */
+char *waila_payment_params_node_pubkey(const WailaPaymentParams *params);
+
+/**
+ * # Safety
+ *
+ * `params` must be null or returned by `waila_parse` and not freed.
+ */
+char *waila_payment_params_lnurl(const WailaPaymentParams *params);
+
+/**
+ * # Safety
+ *
+ * `params` must be null or returned by `waila_parse` and not freed.
+ */
+char *waila_payment_params_lightning_address(const WailaPaymentParams *params);
+
+/**
+ * The bech32 `npub` of a nostr pubkey
+ *
+ * # Safety
+ *
+ * `params` must be null or returned by `waila_parse` and not freed.
+ */
+char *waila_payment_params_nostr_pubkey(const WailaPaymentParams *params);
+
+/**
+ * # Safety
+ *
+ * `params` must be null or returned by `waila_parse` and not freed.
+ */
+char *waila_payment_params_payjoin_endpoint(const WailaPaymentParams *params);
+
+/**
+ * # Safety
+ *
+ * `params` must be null or returned by `waila_parse` and not freed.
+ */
+char *waila_payment_params_txid(const WailaPaymentParams *params);
+
+/**
+ * Returns false if there is no amount.
+ *
+ * # Safety
+ *
+ * `params` must be null or returned by `waila_parse` and not freed,
+ * `amount` must be valid for writes.
+ */
+bool waila_payment_params_amount_sats(const WailaPaymentParams *params, uint64_t *amount);
+
+/**
+ * Returns false if there is no amount.
+ *
+ * # Safety
+ *
+ * `params` must be null or returned by `waila_parse` and not freed,
+ * `amount` must be valid for writes.
+ */
+bool waila_payment_params_amount_msats(const WailaPaymentParams *params, uint64_t *amount);
+
+/**
+ * Whether the string was a secret, e.g. a private key, that shouldn't be shown
+ *
+ * # Safety
+ *
+ * `params` must be null or returned by `waila_parse` and not freed.
+ */
+bool waila_payment_params_is_sensitive(const WailaPaymentParams *params);
+
+/**
+ * # Safety
+ *
+ * `params` must be null or returned by `waila_parse` and not freed.
+ */
+bool waila_payment_params_requires_tor(const WailaPaymentParams *params);
+
+/**
+ * # Safety
+ *
+ * `params` must be null or returned by `waila_parse` and not freed.
+ */
+bool waila_payment_params_is_expired(const WailaPaymentParams *params);
+
+#endif /* WAILA_H */
@@ -0,0 +1,314 @@
+//! A C API for waila, the header is at `include/waila.h`.
+//!
+//! Every string returned is owned by the caller and must be freed with
+//! [`waila_string_free`], parsed params with [`waila_payment_params_free`].
+//! Getters return null when the params don't have the value.
+
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+use std::ptr;
+use std::str::FromStr;
+
+use nostr::ToBech32;
+
+/// The result of parsing a string, opaque to C
+pub struct WailaPaymentParams {
+    params: bitcoin_waila::PaymentParams<'static>,
+}
+
+/// Strings with an interior nul can't be given to C, they become null
+fn into_c_string(string: Option<String>) -> *mut c_char {
+    string
+        .and_then(|s| CString::new(s).ok())
+        .map_or(ptr::null_mut(), CString::into_raw)
+}
+
+/// Parses a nul terminated UTF-8 string, returning null if it couldn't be parsed.
+///
+/// On failure `error` is set to why, if it isn't null, and must be freed with
+/// [`waila_string_free`].
+///
+/// # Safety
+///
+/// `string` must be a valid nul terminated string and `error` either null or
+/// valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn waila_parse(
+    string: *const c_char,
+    error: *mut *mut c_char,
+) -> *mut WailaPaymentParams {
+    if !error.is_null() {
+        *error = ptr::null_mut();
+    }
+    if string.is_null() {
+        return ptr::null_mut();
+    }
+
+    let result = CStr::from_ptr(string)
+        .to_str()
+        .map_err(|e| e.to_string())
+        .and_then(|s| bitcoin_waila::PaymentParams::from_str(s).map_err(|e| e.to_string()));
+    match result {
+        Ok(params) => Box::into_raw(Box::new(WailaPaymentParams { params })),
+        Err(e) => {
+            if !error.is_null() {
+                *error = into_c_string(Some(e));
+            }
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Frees params returned by [`waila_parse`], null is ignored.
+///
+/// # Safety
+///
+/// `params` must be null or returned by [`waila_parse`] and not already freed.
+#[no_mangle]
+pub unsafe extern "C" fn waila_payment_params_free(params: *mut WailaPaymentParams) {
+    if !params.is_null() {
+        drop(Box::from_raw(params));
+    }
+}
+
+/// Frees a string returned by waila, null is ignored.
+///
+/// # Safety
+///
+/// `string` must be null or returned by waila and not already freed.
+#[no_mangle]
+pub unsafe extern "C" fn waila_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
+
+macro_rules! string_getter {
+    ($(#[$doc:meta])* $name:ident, |$params:ident| $body:expr) => {
+        $(#[$doc])*
+        ///
+        /// # Safety
+        ///
+        /// `params` must be null or returned by [`waila_parse`] and not freed.
+        #[no_mangle]
+        pub unsafe extern "C" fn $name(params: *const WailaPaymentParams) -> *mut c_char {
+            let $params = match params.as_ref() {
+                Some(params) => &params.params,
+                None => return ptr::null_mut(),
+            };
+            into_c_string($body)
+        }
+    };
+}
+
+macro_rules! bool_getter {
+    ($(#[$doc:meta])* $name:ident, |$params:ident| $body:expr) => {
+        $(#[$doc])*
+        ///
+        /// # Safety
+        ///
+        /// `params` must be null or returned by [`waila_parse`] and not freed.
+        #[no_mangle]
+        pub unsafe extern "C" fn $name(params: *const WailaPaymentParams) -> bool {
+            match params.as_ref() {
+                Some(params) => {
+                    let $params = &params.params;
+                    $body
+                }
+                None => false,
+            }
+        }
+    };
+}
+
+macro_rules! amount_getter {
+    ($(#[$doc:meta])* $name:ident, |$params:ident| $body:expr) => {
+        $(#[$doc])*
+        /// Returns false if there is no amount.
+        ///
+        /// # Safety
+        ///
+        /// `params` must be null or returned by [`waila_parse`] and not freed,
+        /// `amount` must be valid for writes.
+        #[no_mangle]
+        pub unsafe extern "C" fn $name(params: *const WailaPaymentParams, amount: *mut u64) -> bool {
+            let $params = match params.as_ref() {
+                Some(params) => &params.params,
+                None => return false,
+            };
+            match $body {
+                Some(value) if !amount.is_null() => {
+                    *amount = value;
+                    true
+                }
+                _ => false,
+            }
+        }
+    };
+}
+
+string_getter! {
+    /// What kind of string was parsed, e.g. `on_chain` or `bolt11`
+    waila_payment_params_kind, |params| Some(params.kind().as_str().to_string())
+}
+
+string_getter! {
+    /// The canonical encoding of what was parsed, null for secrets, see
+    /// `waila_payment_params_is_sensitive`
+    waila_payment_params_canonical_string, |params| {
+        (!params.is_sensitive()).then(|| params.to_string())
+    }
+}
+
+string_getter! {
+    /// `bitcoin`, `testnet`, `signet` or `regtest`
+    waila_payment_params_network, |params| params.network().map(|n| n.to_string())
+}
+
+string_getter! {
+    waila_payment_params_label, |params| params.label()
+}
+
+string_getter! {
+    waila_payment_params_message, |params| params.message()
+}
+
+string_getter! {
+    waila_payment_params_memo, |params| params.memo()
+}
+
+string_getter! {
+    waila_payment_params_address, |params| params.address().map(|addr| addr.to_string())
+}
+
+string_getter! {
+    waila_payment_params_invoice, |params| params.invoice().map(|invoice| invoice.to_string())
+}
+
+string_getter! {
+    waila_payment_params_offer, |params| params.offer().map(|offer| offer.to_string())
+}
+
+string_getter! {
+    waila_payment_params_node_pubkey, |params| params.node_pubkey().map(|pubkey| pubkey.to_string())
+}
+
+string_getter! {
+    waila_payment_params_lnurl, |params| params.lnurl().map(|lnurl| lnurl.to_string())
+}
+
+string_getter! {
+    waila_payment_params_lightning_address, |params| params.lightning_address().map(|addr| addr.to_string())
+}
+
+string_getter! {
+    /// The bech32 `npub` of a nostr pubkey
+    waila_payment_params_nostr_pubkey, |params| params.nostr_pubkey().and_then(|key| key.to_bech32().ok())
+}
+
+string_getter! {
+    waila_payment_params_payjoin_endpoint, |params| params.payjoin_endpoint().map(|url| url.to_string())
+}
+
+string_getter! {
+    waila_payment_params_txid, |params| params.txid().map(|txid| txid.to_string())
+}
+
+amount_getter! {
+    waila_payment_params_amount_sats, |params| params.amount().map(|amount| amount.to_sat())
+}
+
+amount_getter! {
+    waila_payment_params_amount_msats, |params| params.amount_msats()
+}
+
+bool_getter! {
+    /// Whether the string was a secret, e.g. a private key, that shouldn't be shown
+    waila_payment_params_is_sensitive, |params| params.is_sensitive()
+}
+
+bool_getter! {
+    waila_payment_params_requires_tor, |params| params.requires_tor()
+}
+
+bool_getter! {
+    waila_payment_params_is_expired, |params| params.is_expired()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    unsafe fn take_string(string: *mut c_char) -> Option<String> {
+        if string.is_null() {
+            return None;
+        }
+        let owned = CStr::from_ptr(string).to_str().unwrap().to_string();
+        waila_string_free(string);
+        Some(owned)
+    }
+
+    #[test]
+    fn test_parse() {
+        let string =
+            CString::new("bitcoin:bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4?amount=0.0001")
+                .unwrap();
+        unsafe {
+            let mut error = ptr::null_mut();
+            let params = waila_parse(string.as_ptr(), &mut error);
+            assert!(!params.is_null());
+            assert!(error.is_null());
+
+            assert_eq!(
+                take_string(waila_payment_params_kind(params)).as_deref(),
+                Some("bip21")
+            );
+            assert_eq!(
+                take_string(waila_payment_params_address(params)).as_deref(),
+                Some("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4")
+            );
+            assert_eq!(take_string(waila_payment_params_memo(params)), None);
+
+            let mut amount = 0;
+            assert!(waila_payment_params_amount_sats(params, &mut amount));
+            assert_eq!(amount, 10_000);
+            assert!(!waila_payment_params_is_sensitive(params));
+
+            waila_payment_params_free(params);
+        }
+    }
+
+    #[test]
+    fn test_sensitive_canonical_string() {
+        let string =
+            CString::new("nsec1vl029mgpspedva04g90vltkh6fvh240zqtv9k0t9af8935ke9laqsnlfe5")
+                .unwrap();
+        unsafe {
+            let params = waila_parse(string.as_ptr(), ptr::null_mut());
+            assert!(waila_payment_params_is_sensitive(params));
+            assert_eq!(
+                take_string(waila_payment_params_canonical_string(params)),
+                None
+            );
+
+            waila_payment_params_free(params);
+        }
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let string = CString::new("not a thing").unwrap();
+        unsafe {
+            let mut error = ptr::null_mut();
+            let params = waila_parse(string.as_ptr(), &mut error);
+            assert!(params.is_null());
+            assert!(take_string(error).is_some());
+
+            // null is handled everywhere
+            assert!(waila_parse(ptr::null(), ptr::null_mut()).is_null());
+            assert!(waila_payment_params_kind(ptr::null()).is_null());
+            waila_payment_params_free(ptr::null_mut());
+            waila_string_free(ptr::null_mut());
+        }
+    }
+}
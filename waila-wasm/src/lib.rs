@@ -1,8 +1,70 @@
+use bitcoin::bech32::{self, ToBase32};
 use bitcoin::Network;
+use lightning::util::ser::Writeable;
 use nostr::prelude::ToBech32;
+use std::fmt::Write;
 use std::str::FromStr;
 use wasm_bindgen::prelude::*;
 
+/// The TypeScript type of [`PaymentParams::kind`], wasm-bindgen can't derive it
+#[wasm_bindgen(typescript_custom_section)]
+const PAYMENT_PARAMS_KIND: &str = r#"
+export type PaymentParamsKind =
+    | "on_chain"
+    | "bip21"
+    | "bolt11"
+    | "bolt12"
+    | "bolt12_refund"
+    | "node_pubkey"
+    | "lnurl"
+    | "lightning_address"
+    | "nostr"
+    | "fedimint_invite"
+    | "nostr_wallet_auth"
+    | "cashu_token"
+    | "fedimint_oob_notes"
+    | "short_channel_id"
+    | "silent_payment"
+    | "bip353"
+    | "liquid"
+    | "bolt12_invoice"
+    | "lnd_connect"
+    | "lnd_hub"
+    | "descriptor"
+    | "xpub"
+    | "psbt"
+    | "txid"
+    | "private_key"
+    | "mnemonic"
+    | "payment_code"
+    | "paynym"
+    | "ark"
+    | "cashu_mint"
+    | "nostr_profile"
+    | "nostr_note"
+    | "nostr_event"
+    | "nostr_secret_key"
+    | "nip05"
+    | "nostr_wallet_connect"
+    | "signed_message"
+    | "lnd_channel_backup"
+    | "cln_rune"
+    | "slashtag"
+    | "rgb";
+"#;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "PaymentParamsKind")]
+    pub type PaymentParamsKind;
+}
+
+/// Parses a string, the same as `new PaymentParams(string)`
+#[wasm_bindgen]
+pub fn parse(string: String) -> Result<PaymentParams, JsValue> {
+    PaymentParams::from_string(string)
+}
+
 #[derive(Debug, Clone)]
 #[wasm_bindgen]
 pub struct PaymentParams {
@@ -14,20 +76,132 @@ pub struct PaymentParams {
 impl PaymentParams {
     #[wasm_bindgen(constructor)]
     pub fn from_string(string: String) -> Result<PaymentParams, JsValue> {
-        let params = bitcoin_waila::PaymentParams::from_str(&string).map_err(|_| JsValue::NULL)?;
+        let params = bitcoin_waila::PaymentParams::from_str(&string)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        Ok(PaymentParams { string, params })
+    }
+
+    /// Parses an NDEF message read from an NFC tag, e.g. a Bolt Card
+    pub fn from_ndef(bytes: Vec<u8>) -> Result<PaymentParams, JsValue> {
+        let params = bitcoin_waila::PaymentParams::from_ndef(&bytes)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        let string = params.to_string();
         Ok(PaymentParams { string, params })
     }
 
+    /// Parses the raw bytes of a scanned QR code, e.g. a CompactSeedQR
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<PaymentParams, JsValue> {
+        let params = bitcoin_waila::PaymentParams::from_bytes(&bytes)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        let string = match String::from_utf8(bytes) {
+            Ok(string) => string,
+            Err(e) => e.as_bytes().iter().fold(String::new(), |mut s, b| {
+                let _ = write!(s, "{b:02x}");
+                s
+            }),
+        };
+        Ok(PaymentParams { string, params })
+    }
+
+    /// Parses a string that must be for the given network, e.g. `signet`
+    pub fn from_string_for_network(
+        string: String,
+        network: String,
+    ) -> Result<PaymentParams, JsValue> {
+        let network = Network::from_str(&network).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        let params = bitcoin_waila::PaymentParams::parse_for_network(&string, network)
+            .map_err(|e| JsValue::from_str(&e.to_string()))?;
+        Ok(PaymentParams { string, params })
+    }
+
+    /// All the ways a string can be interpreted, in order of likelihood
+    pub fn parse_multiple(string: String) -> Vec<PaymentParams> {
+        bitcoin_waila::PaymentParams::parse_multiple(&string)
+            .into_iter()
+            .map(|params| PaymentParams {
+                string: string.clone(),
+                params,
+            })
+            .collect()
+    }
+
+    /// All the ways a string can be interpreted with a confidence score, highest first
+    pub fn parse_ranked(string: String) -> Vec<RankedPaymentParams> {
+        bitcoin_waila::PaymentParams::parse_ranked(&string)
+            .into_iter()
+            .map(|(params, confidence)| RankedPaymentParams {
+                params: PaymentParams {
+                    string: string.clone(),
+                    params,
+                },
+                confidence,
+            })
+            .collect()
+    }
+
+    /// A corrected version of a string that failed to parse, e.g. an address
+    /// with a typo or characters misread by OCR
+    pub fn suggestion(string: String) -> Option<String> {
+        bitcoin_waila::PaymentParams::from_str(&string)
+            .err()
+            .and_then(|e| e.suggestion(&string))
+    }
+
+    /// Finds everything that can be parsed inside free-form text
+    pub fn find_all(text: String) -> Vec<PaymentParams> {
+        bitcoin_waila::PaymentParams::find_all(&text)
+            .into_iter()
+            .map(|(range, params)| PaymentParams {
+                string: text[range].to_string(),
+                params,
+            })
+            .collect()
+    }
+
     #[wasm_bindgen(getter)]
     pub fn string(&self) -> String {
         self.string.clone()
     }
 
+    /// The canonical encoding of what was parsed, e.g. a `lightning:` prefixed
+    /// invoice without the prefix. Undefined for secrets, see `is_sensitive`
+    #[wasm_bindgen(getter)]
+    pub fn canonical_string(&self) -> Option<String> {
+        (!self.params.is_sensitive()).then(|| self.params.to_string())
+    }
+
+    /// The string to put in a QR code, uppercased where that makes the code smaller
+    #[wasm_bindgen(getter)]
+    pub fn qr_payload(&self) -> String {
+        self.params.to_qr_payload()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn label(&self) -> Option<String> {
+        self.params.label()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn message(&self) -> Option<String> {
+        self.params.message()
+    }
+
     #[wasm_bindgen(getter)]
     pub fn memo(&self) -> Option<String> {
         self.params.memo()
     }
 
+    #[wasm_bindgen(getter)]
+    pub fn safe_memo(&self) -> Option<String> {
+        self.params.safe_memo()
+    }
+
+    /// What kind of string was parsed, e.g. `on_chain` or `bolt11`
+    #[wasm_bindgen(getter)]
+    pub fn kind(&self) -> PaymentParamsKind {
+        JsValue::from_str(self.params.kind().as_str()).unchecked_into()
+    }
+
     #[wasm_bindgen(getter)]
     pub fn network(&self) -> Option<String> {
         self.params.network().map(|n| n.to_string())
@@ -49,6 +223,16 @@ impl PaymentParams {
         self.params.amount_msats()
     }
 
+    #[wasm_bindgen(getter)]
+    pub fn currency_amount(&self) -> Option<u64> {
+        self.params.currency_amount().map(|(amount, _)| amount)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn currency_unit(&self) -> Option<String> {
+        self.params.currency_amount().map(|(_, unit)| unit)
+    }
+
     #[wasm_bindgen(getter)]
     pub fn address(&self) -> Option<String> {
         self.params.address().map(|addr| addr.to_string())
@@ -59,16 +243,94 @@ impl PaymentParams {
         self.params.invoice().map(|invoice| invoice.to_string())
     }
 
+    #[wasm_bindgen(getter)]
+    pub fn invoices(&self) -> Vec<JsValue> {
+        self.params
+            .invoices()
+            .into_iter()
+            .map(|invoice| JsValue::from_str(&invoice.to_string()))
+            .collect()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn description_hash(&self) -> Option<String> {
+        self.params.description_hash().map(|h| h.to_string())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn min_final_cltv_expiry_delta(&self) -> Option<u64> {
+        self.params.min_final_cltv_expiry_delta()
+    }
+
+    /// Unix timestamp in seconds of when the BOLT12 offer or refund expires
+    #[wasm_bindgen(getter)]
+    pub fn absolute_expiry(&self) -> Option<u64> {
+        self.params
+            .absolute_expiry()
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|d| d.as_secs())
+    }
+
+    /// Unix timestamp in seconds of when the invoice was created
+    #[wasm_bindgen(getter)]
+    pub fn created_at(&self) -> Option<u64> {
+        self.params
+            .created_at()
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|d| d.as_secs())
+    }
+
+    /// Unix timestamp in seconds of when the payment request expires
+    #[wasm_bindgen(getter)]
+    pub fn expires_at(&self) -> Option<u64> {
+        self.params
+            .expires_at()
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|d| d.as_secs())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn is_expired(&self) -> bool {
+        self.params.is_expired()
+    }
+
     #[wasm_bindgen(getter)]
     pub fn offer(&self) -> Option<String> {
         self.params.offer().map(|offer| offer.to_string())
     }
 
+    #[wasm_bindgen(getter)]
+    pub fn offer_issuer(&self) -> Option<String> {
+        self.params.offer_issuer()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn blinded_path_introduction_nodes(&self) -> Vec<JsValue> {
+        self.params
+            .blinded_path_introduction_nodes()
+            .into_iter()
+            .map(|node| JsValue::from_str(&node.to_string()))
+            .collect()
+    }
+
     #[wasm_bindgen(getter)]
     pub fn refund(&self) -> Option<String> {
         self.params.refund().map(|refund| refund.to_string())
     }
 
+    #[wasm_bindgen(getter)]
+    pub fn bolt12_invoice(&self) -> Option<String> {
+        self.params.bolt12_invoice().map(|invoice| {
+            bech32::encode_without_checksum("lni", invoice.encode().to_base32())
+                .expect("hrp is valid")
+        })
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn payment_hash(&self) -> Option<String> {
+        self.params.payment_hash().map(|hash| hash.to_string())
+    }
+
     #[wasm_bindgen(getter)]
     pub fn node_pubkey(&self) -> Option<String> {
         self.params.node_pubkey().map(|pubkey| pubkey.to_string())
@@ -84,11 +346,53 @@ impl PaymentParams {
         self.params.lightning_address().map(|addr| addr.to_string())
     }
 
+    #[wasm_bindgen(getter)]
+    pub fn lightning_address_tag(&self) -> Option<String> {
+        self.params.lightning_address_tag()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn requires_tor(&self) -> bool {
+        self.params.requires_tor()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn bip353_name(&self) -> Option<String> {
+        self.params.bip353_name().map(|name| name.to_string())
+    }
+
     #[wasm_bindgen(getter)]
     pub fn is_lnurl_auth(&self) -> bool {
         self.params.is_lnurl_auth()
     }
 
+    #[wasm_bindgen(getter)]
+    pub fn is_lnurl_withdraw(&self) -> bool {
+        self.params.is_lnurl_withdraw()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn lnurl_kind(&self) -> Option<String> {
+        self.params.lnurl_kind().map(|kind| kind.to_string())
+    }
+
+    /// The domain an LNURL-auth login is for, the linking key is derived from it
+    #[wasm_bindgen(getter)]
+    pub fn lnurl_auth_domain(&self) -> Option<String> {
+        self.params
+            .lnurl_auth_request()
+            .map(|request| request.domain().to_string())
+    }
+
+    /// `register`, `login`, `link` or `auth` when the LNURL-auth service specified it
+    #[wasm_bindgen(getter)]
+    pub fn lnurl_auth_action(&self) -> Option<String> {
+        self.params
+            .lnurl_auth_request()
+            .and_then(|request| request.action())
+            .map(|action| action.to_string())
+    }
+
     #[wasm_bindgen(getter)]
     pub fn nostr_pubkey(&self) -> Option<String> {
         self.params
@@ -96,6 +400,119 @@ impl PaymentParams {
             .and_then(|key| key.to_bech32().ok())
     }
 
+    #[wasm_bindgen(getter)]
+    pub fn nostr_relays(&self) -> Vec<JsValue> {
+        self.params
+            .nostr_relays()
+            .into_iter()
+            .map(|url| JsValue::from_str(url.as_str()))
+            .collect()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn nostr_event_id(&self) -> Option<String> {
+        self.params
+            .nostr_event_id()
+            .and_then(|id| id.to_bech32().ok())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn nostr_event_author(&self) -> Option<String> {
+        self.params
+            .nostr_event_author()
+            .and_then(|key| key.to_bech32().ok())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn nip05(&self) -> Option<String> {
+        self.params.nip05().map(|id| id.to_string())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn nostr_wallet_connect_pubkey(&self) -> Option<String> {
+        self.params
+            .nostr_wallet_connect()
+            .map(|uri| uri.public_key().to_hex())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn nostr_wallet_connect_relays(&self) -> Vec<JsValue> {
+        self.params
+            .nostr_wallet_connect()
+            .map(|uri| {
+                uri.relays()
+                    .iter()
+                    .map(|url| JsValue::from_str(url.as_str()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn nostr_wallet_connect_secret(&self) -> Option<String> {
+        self.params
+            .nostr_wallet_connect()
+            .map(|uri| uri.secret().to_secret_hex())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn nostr_wallet_connect_lud16(&self) -> Option<String> {
+        self.params
+            .nostr_wallet_connect()
+            .and_then(|uri| uri.lud16().map(|l| l.to_string()))
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn signed_message(&self) -> Option<String> {
+        self.params
+            .signed_message()
+            .and_then(|msg| msg.message().map(|m| m.to_string()))
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn signed_message_address(&self) -> Option<String> {
+        self.params
+            .signed_message()
+            .and_then(|msg| msg.address().map(|a| a.to_string()))
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn signed_message_valid(&self) -> Option<bool> {
+        self.params.signed_message().and_then(|msg| msg.verify())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn lnd_channel_backup_chan_points(&self) -> Vec<JsValue> {
+        self.params
+            .lnd_channel_backup()
+            .map(|backup| {
+                backup
+                    .chan_points()
+                    .iter()
+                    .map(|point| JsValue::from_str(&point.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn cln_rune_restrictions(&self) -> Vec<JsValue> {
+        self.params
+            .cln_rune()
+            .map(|rune| {
+                rune.restrictions()
+                    .iter()
+                    .map(|r| JsValue::from_str(&r.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn slashtag_pubkey(&self) -> Option<String> {
+        self.params.slashtag().map(|s| s.encoded_public_key())
+    }
+
     #[wasm_bindgen(getter)]
     pub fn fedimint_invite_code(&self) -> Option<String> {
         self.params
@@ -103,14 +520,116 @@ impl PaymentParams {
             .map(|code| code.to_string())
     }
 
+    #[wasm_bindgen(getter)]
+    pub fn fedimint_federation_id(&self) -> Option<String> {
+        self.params.fedimint_federation_id()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn fedimint_api_urls(&self) -> Vec<JsValue> {
+        self.params
+            .fedimint_api_urls()
+            .into_iter()
+            .map(|url| JsValue::from_str(url.as_str()))
+            .collect()
+    }
+
+    /// `[peer id, api url]` pairs of the guardians in an invite code
+    #[wasm_bindgen(getter)]
+    pub fn fedimint_guardians(&self) -> Vec<JsValue> {
+        self.params
+            .fedimint_guardians()
+            .into_iter()
+            .map(|(peer, url)| pair(JsValue::from(peer), JsValue::from_str(url.as_str())))
+            .collect()
+    }
+
+    /// `[denomination msats, count]` pairs of the notes, smallest first
+    #[wasm_bindgen(getter)]
+    pub fn fedimint_note_denominations(&self) -> Vec<JsValue> {
+        self.params
+            .fedimint_note_denominations()
+            .into_iter()
+            .map(|(msats, count)| pair(JsValue::from(msats), JsValue::from(count)))
+            .collect()
+    }
+
     #[wasm_bindgen(getter)]
     pub fn nostr_wallet_auth(&self) -> Option<String> {
         self.params.nostr_wallet_auth().map(|u| u.to_string())
     }
 
+    #[wasm_bindgen(getter)]
+    pub fn nostr_wallet_auth_required_commands(&self) -> Vec<JsValue> {
+        self.params
+            .nostr_wallet_auth()
+            .map(|u| {
+                u.required_commands()
+                    .iter()
+                    .map(|m| JsValue::from_str(&m.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn nostr_wallet_auth_optional_commands(&self) -> Vec<JsValue> {
+        self.params
+            .nostr_wallet_auth()
+            .map(|u| {
+                u.optional_commands()
+                    .iter()
+                    .map(|m| JsValue::from_str(&m.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn nostr_wallet_auth_budget_msats(&self) -> Option<u64> {
+        self.params
+            .nostr_wallet_auth()
+            .and_then(|u| u.budget().map(|b| b.amount_msats()))
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn nostr_wallet_auth_budget_period(&self) -> Option<String> {
+        self.params
+            .nostr_wallet_auth()
+            .and_then(|u| u.budget().map(|b| b.period().to_string()))
+    }
+
     #[wasm_bindgen(getter)]
     pub fn cashu_token(&self) -> Option<String> {
-        self.params.cashu_token().and_then(|t| t.serialize().ok())
+        self.params.cashu_token().map(|t| t.to_string())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn cashu_mint_url(&self) -> Option<String> {
+        self.params.cashu_mint_url().map(|url| url.to_string())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn cashu_mints(&self) -> Vec<JsValue> {
+        self.params
+            .cashu_mints()
+            .into_iter()
+            .map(|url| JsValue::from_str(url.as_str()))
+            .collect()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn cashu_unit(&self) -> Option<String> {
+        self.params.cashu_unit()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn cashu_keyset_ids(&self) -> Vec<JsValue> {
+        self.params
+            .cashu_keyset_ids()
+            .into_iter()
+            .map(|id| JsValue::from_str(&id))
+            .collect()
     }
 
     #[wasm_bindgen(getter)]
@@ -118,6 +637,215 @@ impl PaymentParams {
         self.params.fedimint_oob_notes().map(|t| t.to_string())
     }
 
+    #[wasm_bindgen(getter)]
+    pub fn fedimint_notes_federation_id_prefix(&self) -> Option<String> {
+        self.params.fedimint_notes_federation_id_prefix()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn fedimint_note_count(&self) -> Option<usize> {
+        self.params
+            .fedimint_oob_notes()
+            .map(|notes| notes.note_count())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn silent_payment_address(&self) -> Option<String> {
+        self.params.silent_payment_address().map(|a| a.to_string())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn silent_payment_scan_key(&self) -> Option<String> {
+        self.params
+            .silent_payment_address()
+            .map(|a| a.scan_key().to_string())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn silent_payment_spend_key(&self) -> Option<String> {
+        self.params
+            .silent_payment_address()
+            .map(|a| a.spend_key().to_string())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn liquid_address(&self) -> Option<String> {
+        self.params.liquid_address().map(|a| a.to_string())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn liquid_blinding_pubkey(&self) -> Option<String> {
+        self.params.liquid_blinding_pubkey().map(|k| k.to_string())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn liquid_unconfidential_address(&self) -> Option<String> {
+        self.params
+            .liquid_unconfidential_address()
+            .map(|a| a.to_string())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn liquid_asset_id(&self) -> Option<String> {
+        self.params
+            .liquid_uri()
+            .and_then(|uri| uri.asset_id)
+            .map(|id| id.to_string())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn liquid_amount_sats(&self) -> Option<u64> {
+        self.params
+            .liquid_uri()
+            .and_then(|uri| uri.amount)
+            .map(|amount| amount.to_sat())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn lnd_connect_host(&self) -> Option<String> {
+        self.params.lnd_connect().map(|uri| uri.host)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn lnd_connect_port(&self) -> Option<u16> {
+        self.params.lnd_connect().map(|uri| uri.port)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn lnd_connect_cert(&self) -> Option<Vec<u8>> {
+        self.params.lnd_connect().and_then(|uri| uri.cert)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn lnd_connect_macaroon(&self) -> Option<Vec<u8>> {
+        self.params.lnd_connect().map(|uri| uri.macaroon)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn lnd_hub_login(&self) -> Option<String> {
+        self.params.lnd_hub().map(|uri| uri.login().to_string())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn lnd_hub_password(&self) -> Option<String> {
+        self.params.lnd_hub().map(|uri| uri.password().to_string())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn lnd_hub_endpoint(&self) -> Option<String> {
+        self.params
+            .lnd_hub()
+            .and_then(|uri| uri.endpoint().map(|u| u.to_string()))
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn descriptor(&self) -> Option<String> {
+        self.params.descriptor().map(|desc| desc.to_string())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn descriptor_type(&self) -> Option<String> {
+        self.params.descriptor_type().map(|t| format!("{t:?}"))
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn xpub(&self) -> Option<String> {
+        self.params.xpub().map(|xpub| xpub.key.to_string())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn xpub_script_type(&self) -> Option<String> {
+        self.params
+            .xpub()
+            .and_then(|xpub| xpub.script_type)
+            .map(|t| format!("{t:?}"))
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn psbt_output_amount(&self) -> Option<u64> {
+        self.params.psbt_output_amount().map(|amt| amt.to_sat())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn psbt_networks(&self) -> Vec<JsValue> {
+        self.params
+            .psbt_networks()
+            .into_iter()
+            .map(|n| JsValue::from_str(&n.to_string()))
+            .collect()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn txid(&self) -> Option<String> {
+        self.params.txid().map(|txid| txid.to_string())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn is_sensitive(&self) -> bool {
+        self.params.is_sensitive()
+    }
+
+    /// The npub of a pasted nsec, the secret itself is never exposed
+    #[wasm_bindgen(getter)]
+    pub fn nostr_secret_key_pubkey(&self) -> Option<String> {
+        self.params
+            .nostr_secret_key()
+            .and_then(|key| key.public_key().to_bech32().ok())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn mnemonic_word_count(&self) -> Option<usize> {
+        self.params.mnemonic().map(|m| m.word_count())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn payment_code(&self) -> Option<String> {
+        self.params.payment_code().map(|code| code.to_string())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn paynym(&self) -> Option<String> {
+        self.params.paynym().map(|nym| nym.to_string())
+    }
+
+    #[wasm_bindgen]
+    pub fn payment_code_notification_address(&self, network: String) -> Option<String> {
+        let network = Network::from_str(&network).ok()?;
+        self.params
+            .payment_code()
+            .map(|code| code.notification_address(network).to_string())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn ark_address(&self) -> Option<String> {
+        self.params.ark_address().map(|a| a.to_string())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn short_channel_id(&self) -> Option<String> {
+        self.params.short_channel_id().map(|scid| scid.to_string())
+    }
+
+    /// Every `lightning` parameter of a BIP21 URI, not just the first
+    #[wasm_bindgen(getter)]
+    pub fn lightning_params(&self) -> Vec<JsValue> {
+        self.params
+            .lightning_params()
+            .iter()
+            .map(|param| JsValue::from_str(&param.to_string()))
+            .collect()
+    }
+
+    /// `[key, value]` pairs of the BIP21 parameters waila doesn't understand
+    #[wasm_bindgen(getter)]
+    pub fn extra_params(&self) -> Vec<JsValue> {
+        self.params
+            .extra_params()
+            .into_iter()
+            .map(|(key, value)| pair(JsValue::from(key), JsValue::from(value)))
+            .collect()
+    }
+
     #[wasm_bindgen(getter)]
     pub fn payjoin_endpoint(&self) -> Option<String> {
         self.params.payjoin_endpoint().map(|n| n.to_string())
@@ -132,4 +860,91 @@ impl PaymentParams {
     pub fn payjoin_supported(&self) -> bool {
         self.params.payjoin_endpoint().is_some()
     }
+
+    #[wasm_bindgen(getter)]
+    pub fn payment_request_url(&self) -> Option<String> {
+        self.params.payment_request_url().map(|u| u.to_string())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn proof_of_payment_callback(&self) -> Option<String> {
+        self.params.proof_of_payment_callback()
+    }
+}
+
+fn pair(first: JsValue, second: JsValue) -> JsValue {
+    js_sys::Array::of2(&first, &second).into()
+}
+
+/// A candidate of [`PaymentParams::parse_ranked`] and how confident we are in it
+#[derive(Debug, Clone)]
+#[wasm_bindgen]
+pub struct RankedPaymentParams {
+    params: PaymentParams,
+    confidence: f32,
+}
+
+#[wasm_bindgen]
+impl RankedPaymentParams {
+    #[wasm_bindgen(getter)]
+    pub fn params(&self) -> PaymentParams {
+        self.params.clone()
+    }
+
+    /// Between 0 and 1
+    #[wasm_bindgen(getter)]
+    pub fn confidence(&self) -> f32 {
+        self.confidence
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+#[wasm_bindgen]
+pub struct ScanSession {
+    frames: Vec<String>,
+    session: bitcoin_waila::ScanSession,
+}
+
+#[wasm_bindgen]
+impl ScanSession {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> ScanSession {
+        ScanSession::default()
+    }
+
+    /// Feeds a scanned QR frame, returns false if it was ignored
+    pub fn receive(&mut self, frame: String) -> bool {
+        let accepted = self.session.receive(&frame);
+        if accepted {
+            self.frames.push(frame);
+        }
+        accepted
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn kind(&self) -> Option<String> {
+        self.session.kind().map(|k| format!("{k:?}").to_lowercase())
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn progress(&self) -> f64 {
+        self.session.progress()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn is_complete(&self) -> bool {
+        self.session.is_complete()
+    }
+
+    pub fn result(&self) -> Option<PaymentParams> {
+        self.session.result().map(|params| PaymentParams {
+            string: self.frames.join("\n"),
+            params,
+        })
+    }
+
+    pub fn reset(&mut self) {
+        self.frames.clear();
+        self.session.reset();
+    }
 }
@@ -0,0 +1,252 @@
+use core::fmt;
+use core::str::FromStr;
+
+use bitcoin::bech32::{self, FromBase32, ToBase32, Variant};
+use bitcoin::secp256k1::XOnlyPublicKey;
+use bitcoin::{Amount, Denomination, Network};
+use nostr::prelude::url::form_urlencoded;
+
+use crate::prelude::*;
+
+/// Ark URI Scheme
+pub const ARK_URI_SCHEME: &str = "ark";
+
+const KEY_LENGTH: usize = 32;
+
+/// An Ark address, the server's key and the taproot key of the VTXO to receive to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ArkAddress {
+    version: u8,
+    network: Network,
+    server_pubkey: XOnlyPublicKey,
+    vtxo_taproot_key: XOnlyPublicKey,
+}
+
+impl ArkAddress {
+    pub fn new(
+        network: Network,
+        server_pubkey: XOnlyPublicKey,
+        vtxo_taproot_key: XOnlyPublicKey,
+    ) -> Self {
+        Self {
+            version: 0,
+            network,
+            server_pubkey,
+            vtxo_taproot_key,
+        }
+    }
+
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    /// The network the address is for, all test networks share the `tark` prefix
+    /// so these are reported as testnet
+    pub fn network(&self) -> Network {
+        self.network
+    }
+
+    /// The key of the Ark server the VTXO is held with
+    pub fn server_pubkey(&self) -> XOnlyPublicKey {
+        self.server_pubkey
+    }
+
+    /// The taproot output key of the VTXO
+    pub fn vtxo_taproot_key(&self) -> XOnlyPublicKey {
+        self.vtxo_taproot_key
+    }
+
+    pub fn is_valid_for_network(&self, network: Network) -> bool {
+        match self.network {
+            Network::Bitcoin => network == Network::Bitcoin,
+            _ => network != Network::Bitcoin,
+        }
+    }
+
+    fn hrp(&self) -> &'static str {
+        match self.network {
+            Network::Bitcoin => "ark",
+            _ => "tark",
+        }
+    }
+}
+
+impl fmt::Display for ArkAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut data = vec![self.version];
+        data.extend_from_slice(&self.server_pubkey.serialize());
+        data.extend_from_slice(&self.vtxo_taproot_key.serialize());
+
+        bech32::encode_to_fmt(f, self.hrp(), data.to_base32(), Variant::Bech32m)
+            .map_err(|_| fmt::Error)?
+    }
+}
+
+impl FromStr for ArkAddress {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (hrp, data, variant) = bech32::decode(s).map_err(|_| ())?;
+        let network = match hrp.as_str() {
+            "ark" => Network::Bitcoin,
+            "tark" => Network::Testnet,
+            _ => return Err(()),
+        };
+        if variant != Variant::Bech32m {
+            return Err(());
+        }
+
+        // unlike segwit addresses the version is part of the byte payload
+        let bytes = Vec::<u8>::from_base32(&data).map_err(|_| ())?;
+        let (version, keys) = bytes.split_first().ok_or(())?;
+        if *version != 0 || keys.len() != KEY_LENGTH * 2 {
+            return Err(());
+        }
+
+        let server_pubkey = XOnlyPublicKey::from_slice(&keys[..KEY_LENGTH]).map_err(|_| ())?;
+        let vtxo_taproot_key = XOnlyPublicKey::from_slice(&keys[KEY_LENGTH..]).map_err(|_| ())?;
+
+        Ok(Self {
+            version: *version,
+            network,
+            server_pubkey,
+            vtxo_taproot_key,
+        })
+    }
+}
+
+/// An `ark:` URI or a bare Ark address, URIs follow BIP21 for the amount
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArkUri {
+    pub address: ArkAddress,
+    pub amount: Option<Amount>,
+}
+
+impl From<ArkAddress> for ArkUri {
+    fn from(address: ArkAddress) -> Self {
+        Self {
+            address,
+            amount: None,
+        }
+    }
+}
+
+impl fmt::Display for ArkUri {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{ARK_URI_SCHEME}:{}", self.address)?;
+        if let Some(amount) = self.amount {
+            write!(f, "?amount={}", amount.to_string_in(Denomination::Bitcoin))?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for ArkUri {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (scheme, rest) = s.split_once(':').ok_or(())?;
+        if !scheme.eq_ignore_ascii_case(ARK_URI_SCHEME) {
+            return Err(());
+        }
+
+        let (address, query) = rest.split_once('?').unwrap_or((rest, ""));
+        let mut uri = ArkUri::from(ArkAddress::from_str(address)?);
+
+        for (key, value) in form_urlencoded::parse(query.as_bytes()) {
+            match key.as_ref() {
+                "amount" if uri.amount.is_none() => {
+                    let amount =
+                        Amount::from_str_in(&value, Denomination::Bitcoin).map_err(|_| ())?;
+                    uri.amount = Some(amount);
+                }
+                "amount" => return Err(()),
+                key if key.starts_with("req-") => return Err(()),
+                _ => {}
+            }
+        }
+
+        Ok(uri)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::str::FromStr;
+
+    use bitcoin::secp256k1::XOnlyPublicKey;
+    use bitcoin::{Amount, Network};
+
+    use super::*;
+
+    const ADDRESS: &str = "ark1qpumuen7l8wthtz45p3ftn58pvrs9xlumvkuu2xet8egzkcklqte33sy072yrmtad5cy2srwjhq8ekzuw78yhr808jn6htqfh9w8p8h9huxqgy";
+
+    #[test]
+    fn test_parse_ark_address() {
+        let address = ArkAddress::from_str(ADDRESS).unwrap();
+        assert_eq!(address.version(), 0);
+        assert_eq!(address.network(), Network::Bitcoin);
+        assert_eq!(
+            address.server_pubkey(),
+            XOnlyPublicKey::from_str(
+                "79be667ef9dcbbac55a06295ce870b07029bfcdb2dce28d959f2815b16f81798"
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            address.vtxo_taproot_key(),
+            XOnlyPublicKey::from_str(
+                "c6047f9441ed7d6d3045406e95c07cd85c778e4b8cef3ca7abac09b95c709ee5"
+            )
+            .unwrap()
+        );
+        assert_eq!(address.to_string(), ADDRESS);
+        assert!(address.is_valid_for_network(Network::Bitcoin));
+        assert!(!address.is_valid_for_network(Network::Signet));
+
+        let upper = ArkAddress::from_str(&ADDRESS.to_uppercase()).unwrap();
+        assert_eq!(upper, address);
+    }
+
+    #[test]
+    fn test_testnet_ark_address() {
+        let address = ArkAddress::from_str("tark1qpumuen7l8wthtz45p3ftn58pvrs9xlumvkuu2xet8egzkcklqte33sy072yrmtad5cy2srwjhq8ekzuw78yhr808jn6htqfh9w8p8h93wdh3v").unwrap();
+        assert_eq!(address.network(), Network::Testnet);
+        assert!(address.is_valid_for_network(Network::Signet));
+        assert!(address.is_valid_for_network(Network::Regtest));
+        assert!(!address.is_valid_for_network(Network::Bitcoin));
+
+        let roundtrip = ArkAddress::new(
+            Network::Testnet,
+            address.server_pubkey(),
+            address.vtxo_taproot_key(),
+        );
+        assert_eq!(roundtrip, address);
+    }
+
+    #[test]
+    fn test_invalid_ark_address() {
+        // bad checksum
+        assert!(ArkAddress::from_str(&ADDRESS.replace("huxqgy", "huxqgz")).is_err());
+        // unknown version
+        assert!(ArkAddress::from_str("ark1q9umuen7l8wthtz45p3ftn58pvrs9xlumvkuu2xet8egzkcklqte33sy072yrmtad5cy2srwjhq8ekzuw78yhr808jn6htqfh9w8p8h9gyyv99").is_err());
+        // wrong hrp
+        assert!(ArkAddress::from_str("bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq").is_err());
+        assert!(ArkAddress::from_str("").is_err());
+    }
+
+    #[test]
+    fn test_parse_ark_uri() {
+        let uri = ArkUri::from_str(&format!("ark:{ADDRESS}?amount=0.0001")).unwrap();
+        assert_eq!(uri.address.to_string(), ADDRESS);
+        assert_eq!(uri.amount, Some(Amount::from_sat(10_000)));
+        assert_eq!(uri.to_string(), format!("ark:{ADDRESS}?amount=0.0001"));
+
+        let uri = ArkUri::from_str(&format!("ARK:{ADDRESS}?label=test")).unwrap();
+        assert_eq!(uri.amount, None);
+
+        assert!(ArkUri::from_str(&format!("ark:{ADDRESS}?amount=abc")).is_err());
+        assert!(ArkUri::from_str(&format!("ark:{ADDRESS}?req-something=1")).is_err());
+        assert!(ArkUri::from_str(&format!("bitcoin:{ADDRESS}")).is_err());
+    }
+}
@@ -0,0 +1,254 @@
+use bitcoin::hashes::hex::FromHex;
+use bitcoin::psbt::PartiallySignedTransaction;
+
+use crate::prelude::*;
+use crate::PaymentParams;
+
+const HEADER_LEN: usize = 8;
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// Zlib parts are inflated up to this many bytes, so a compressed payload
+/// from a malicious QR can't make us allocate gigabytes
+const MAX_DATA_LEN: usize = 4_000_000;
+
+/// How the data of each part is encoded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Encoding {
+    Hex,
+    Base32,
+    /// Raw deflate, then base32
+    Zlib,
+}
+
+/// The parameters every part of a BBQr has to agree on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct BbqrHeader {
+    encoding: Encoding,
+    file_type: char,
+    total: usize,
+}
+
+fn decode_base32(str: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(str.len() * 5 / 8);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for c in str.bytes() {
+        let value = BASE32_ALPHABET.iter().position(|a| *a == c)? as u32;
+        buffer = (buffer << 5) | value;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    Some(bytes)
+}
+
+fn parse_base36(str: &str) -> Option<usize> {
+    usize::from_str_radix(str, 36).ok()
+}
+
+/// Parses a `B$` part into its header, index and decoded data
+fn parse_part(str: &str) -> Option<(BbqrHeader, usize, Vec<u8>)> {
+    let str = str.trim();
+    if !str.starts_with("B$") || str.len() < HEADER_LEN || !str.is_ascii() {
+        return None;
+    }
+
+    let mut chars = str[2..4].chars();
+    let encoding = match chars.next()? {
+        'H' => Encoding::Hex,
+        '2' => Encoding::Base32,
+        'Z' => Encoding::Zlib,
+        _ => return None,
+    };
+    let file_type = chars.next()?;
+    let total = parse_base36(&str[4..6])?;
+    let index = parse_base36(&str[6..8])?;
+    if total == 0 || index >= total {
+        return None;
+    }
+
+    let data = &str[HEADER_LEN..];
+    let data = match encoding {
+        Encoding::Hex => Vec::<u8>::from_hex(data).ok()?,
+        Encoding::Base32 | Encoding::Zlib => decode_base32(data)?,
+    };
+
+    let header = BbqrHeader {
+        encoding,
+        file_type,
+        total,
+    };
+    Some((header, index, data))
+}
+
+/// Whether the string looks like a part of a BBQr
+pub(crate) fn is_bbqr(str: &str) -> bool {
+    str.trim_start().starts_with("B$")
+}
+
+/// Maps the reassembled data to a [`PaymentParams`] based on the file type
+fn decode_payload(file_type: char, data: &[u8]) -> Option<PaymentParams<'static>> {
+    match file_type {
+        'P' => PartiallySignedTransaction::deserialize(data)
+            .ok()
+            .map(|psbt| PaymentParams::Psbt(Box::new(psbt))),
+        'U' | 'B' => PaymentParams::from_bytes(data).ok(),
+        // transactions, JSON and CBOR aren't anything we can identify
+        _ => None,
+    }
+}
+
+/// Reassembles a BBQr, Coinkite's format for splitting data across QR codes.
+///
+/// Parts can be received in any order and duplicates are ignored. Once every
+/// part has been seen, [`BbqrDecoder::result`] returns the decoded payload.
+#[derive(Debug, Clone, Default)]
+pub struct BbqrDecoder {
+    header: Option<BbqrHeader>,
+    parts: Vec<Option<Vec<u8>>>,
+    data: Option<Vec<u8>>,
+    result: Option<PaymentParams<'static>>,
+}
+
+impl BbqrDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feeds a scanned part, returns false if it wasn't a valid part of this BBQr
+    pub fn receive(&mut self, part: &str) -> bool {
+        if self.is_complete() {
+            return false;
+        }
+
+        let (header, index, data) = match parse_part(part) {
+            Some(part) => part,
+            None => return false,
+        };
+        match self.header {
+            Some(existing) if existing != header => return false,
+            Some(_) => {}
+            None => {
+                self.header = Some(header);
+                self.parts = vec![None; header.total];
+            }
+        }
+
+        self.parts[index] = Some(data);
+        if self.parts.iter().all(Option::is_some) {
+            self.finish();
+        }
+
+        true
+    }
+
+    fn finish(&mut self) {
+        let header = self.header.unwrap();
+        let data: Vec<u8> = self.parts.iter().flatten().flatten().copied().collect();
+        let data = match header.encoding {
+            Encoding::Zlib => {
+                match miniz_oxide::inflate::decompress_to_vec_with_limit(&data, MAX_DATA_LEN) {
+                    Ok(data) => data,
+                    Err(_) => {
+                        // corrupt or too large, nothing more we can do with these parts
+                        *self = Self::default();
+                        return;
+                    }
+                }
+            }
+            _ => data,
+        };
+
+        self.result = decode_payload(header.file_type, &data);
+        self.data = Some(data);
+    }
+
+    /// How many parts are needed to complete the BBQr
+    pub fn expected_parts(&self) -> Option<usize> {
+        self.header.map(|h| h.total)
+    }
+
+    /// The fraction of parts received so far, between 0 and 1
+    pub fn progress(&self) -> f64 {
+        if self.is_complete() {
+            return 1.0;
+        }
+        match self.expected_parts() {
+            Some(expected) => {
+                self.parts.iter().filter(|p| p.is_some()).count() as f64 / expected as f64
+            }
+            None => 0.0,
+        }
+    }
+
+    /// Whether all the parts have been received, even if the payload
+    /// turned out to be a type we don't support
+    pub fn is_complete(&self) -> bool {
+        self.data.is_some()
+    }
+
+    /// The decoded payload, once complete
+    pub fn result(&self) -> Option<PaymentParams<'static>> {
+        self.result.clone()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_decode_base32() {
+        assert_eq!(decode_base32("MZXW6YTBOI").unwrap(), b"foobar");
+        assert_eq!(decode_base32("").unwrap(), b"");
+        assert!(decode_base32("mzxw6").is_none());
+    }
+
+    #[test]
+    fn test_invalid_bbqr_parts() {
+        let mut decoder = BbqrDecoder::new();
+        assert!(!decoder.receive("B$"));
+        // index past the total
+        assert!(!decoder.receive("B$HU0101ABCD"));
+        // unknown encoding
+        assert!(!decoder.receive("B$XU0100ABCD"));
+        // bad hex
+        assert!(!decoder.receive("B$HU0100ABC"));
+        assert_eq!(decoder.progress(), 0.0);
+    }
+
+    fn encode_base32(data: &[u8]) -> String {
+        let mut str = String::new();
+        let mut buffer = 0u32;
+        let mut bits = 0;
+        for byte in data {
+            buffer = (buffer << 8) | u32::from(*byte);
+            bits += 8;
+            while bits >= 5 {
+                bits -= 5;
+                str.push(BASE32_ALPHABET[(buffer >> bits) as usize & 31] as char);
+            }
+        }
+        if bits > 0 {
+            str.push(BASE32_ALPHABET[(buffer << (5 - bits)) as usize & 31] as char);
+        }
+        str
+    }
+
+    #[test]
+    fn test_zlib_bomb() {
+        let compressed = miniz_oxide::deflate::compress_to_vec(&vec![0; MAX_DATA_LEN + 1], 10);
+        let mut decoder = BbqrDecoder::new();
+        assert!(decoder.receive(&format!("B$ZU0100{}", encode_base32(&compressed))));
+        assert!(!decoder.is_complete());
+        assert_eq!(decoder.progress(), 0.0);
+
+        let compressed = miniz_oxide::deflate::compress_to_vec(b"hello", 10);
+        let mut decoder = BbqrDecoder::new();
+        assert!(decoder.receive(&format!("B$ZU0100{}", encode_base32(&compressed))));
+        assert!(decoder.is_complete());
+    }
+}
@@ -1,30 +1,71 @@
+use alloc::borrow::Cow;
+use core::convert::TryFrom;
+use core::fmt;
 use core::str::FromStr;
-use std::borrow::Cow;
-use std::convert::TryFrom;
 
 use ::bip21::de::*;
+use ::bip21::ser::SerializeParams;
 use ::bip21::*;
 use bitcoin::address::NetworkUnchecked;
 use lightning::offers::offer::Offer;
 use lightning::offers::parse::Bolt12ParseError;
 use lightning_invoice::{Bolt11Invoice, ParseOrSemanticError};
-use url::Url;
+use nostr::Url;
+
+use crate::prelude::*;
+use crate::silent_payment::SilentPaymentAddress;
 
 /// This lets us parse `lightning`, bolt12, and payjoin parameters from a BIP21 URI.
 pub type UnifiedUri<'a> = Uri<'a, NetworkUnchecked, WailaExtras>;
 
+/// A value of a `lightning` parameter, some wallets put offers there as well.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone)]
+pub enum LightningParam {
+    Bolt11(Bolt11Invoice),
+    Bolt12(Offer),
+}
+
 #[derive(Debug, Default, Clone)]
 pub struct WailaExtras {
+    /// The first invoice of the `lightning` parameters
     pub lightning: Option<Bolt11Invoice>,
+    /// Every `lightning` parameter in the order they appeared in the URI, some unified
+    /// QRs include more than one, e.g. an invoice and an offer. Offers here are
+    /// treated as if they were given as `lno`.
+    pub lightning_params: Vec<LightningParam>,
+    /// A BOLT12 offer from either the `lno` or the older `b12` parameter
     pub b12: Option<Offer>,
     pub pj: Option<Url>,
     pjos: Option<bool>,
+    /// BIP72 payment request url, the legacy BIP70 payment protocol
+    pub r: Option<Url>,
+    /// BIP352 silent payment address
+    pub sp: Option<SilentPaymentAddress>,
+    /// Proof of payment callback, a URI prefix the wallet appends the transaction to once paid
+    pub pop: Option<String>,
+    /// `req-` parameters we don't understand, the URI must be rejected unless parsing leniently
+    unknown_required: Vec<String>,
+    /// Parameters we don't understand in the order they appeared, values are percent decoded
+    pub extra_params: Vec<(String, String)>,
 }
 
 impl WailaExtras {
     pub fn disable_output_substitution(&self) -> bool {
         self.pjos.unwrap_or(false)
     }
+
+    /// Unknown `req-` parameters are only accepted when parsing leniently.
+    pub(crate) fn validate(&self, lenient: bool) -> Result<(), ExtraParamsParseError> {
+        if lenient {
+            return Ok(());
+        }
+
+        match self.unknown_required.first() {
+            Some(key) => Err(ExtraParamsParseError::UnknownRequiredParam(key.clone())),
+            None => Ok(()),
+        }
+    }
 }
 
 #[derive(Debug, Eq, PartialEq, Clone)]
@@ -32,11 +73,84 @@ pub enum ExtraParamsParseError {
     MultipleParams(String),
     InvoiceParsingError,
     Bolt12ParsingError,
+    SilentPaymentParsingError,
     MissingEndpoint,
     NotUtf8(core::str::Utf8Error),
-    BadEndpoint(url::ParseError),
+    BadEndpoint(nostr::prelude::url::ParseError),
     UnsecureEndpoint,
     BadPjOs,
+    UnknownRequiredParam(String),
+    BadProofOfPayment,
+}
+
+/// The callback must have a scheme, and wallets must not open web or file URIs
+/// without the user noticing so those schemes are rejected.
+fn is_valid_pop_callback(pop: &str) -> bool {
+    let scheme = match pop.split_once(':') {
+        Some((scheme, _)) => scheme,
+        None => return false,
+    };
+
+    let mut chars = scheme.chars();
+    chars.next().map_or(false, |c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+        && !matches!(
+            scheme.to_lowercase().as_str(),
+            "http" | "https" | "file" | "javascript" | "mailto"
+        )
+}
+
+impl fmt::Display for LightningParam {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LightningParam::Bolt11(invoice) => write!(f, "{invoice}"),
+            LightningParam::Bolt12(offer) => write!(f, "{offer}"),
+        }
+    }
+}
+
+/// Lets the bip21 crate display a [`UnifiedUri`], parameters we don't understand
+/// are re-emitted as they were given.
+impl<'a> SerializeParams for &'a WailaExtras {
+    type Key = String;
+    type Value = String;
+    type Iterator = alloc::vec::IntoIter<(String, String)>;
+
+    fn serialize_params(self) -> Self::Iterator {
+        let mut params: Vec<(String, String)> = self
+            .lightning_params
+            .iter()
+            .map(|param| ("lightning".to_string(), param.to_string()))
+            .collect();
+
+        // an offer given as a `lightning` parameter is already included
+        let offer_in_lightning = self
+            .lightning_params
+            .iter()
+            .any(|param| matches!(param, LightningParam::Bolt12(_)));
+        if let (Some(offer), false) = (&self.b12, offer_in_lightning) {
+            params.push(("lno".to_string(), offer.to_string()));
+        }
+
+        if let Some(pj) = &self.pj {
+            params.push(("pj".to_string(), pj.to_string()));
+        }
+        if let Some(pjos) = self.pjos {
+            params.push(("pjos".to_string(), u8::from(pjos).to_string()));
+        }
+        if let Some(r) = &self.r {
+            params.push(("r".to_string(), r.to_string()));
+        }
+        if let Some(sp) = &self.sp {
+            params.push(("sp".to_string(), sp.to_string()));
+        }
+        if let Some(pop) = &self.pop {
+            params.push(("pop".to_string(), pop.clone()));
+        }
+        params.extend(self.extra_params.iter().cloned());
+
+        params.into_iter()
+    }
 }
 
 impl From<ParseOrSemanticError> for ExtraParamsParseError {
@@ -63,7 +177,11 @@ impl<'a> DeserializationState<'a> for WailaExtras {
     type Value = WailaExtras;
 
     fn is_param_known(&self, param: &str) -> bool {
-        matches!(param, "lightning" | "pj" | "pjos")
+        let param = param.strip_prefix("req-").unwrap_or(param);
+        matches!(
+            param,
+            "lightning" | "lno" | "b12" | "pj" | "pjos" | "r" | "sp" | "pop"
+        )
     }
 
     fn deserialize_temp(
@@ -71,6 +189,22 @@ impl<'a> DeserializationState<'a> for WailaExtras {
         key: &str,
         value: Param<'_>,
     ) -> Result<ParamKind, <Self::Value as DeserializationError>::Error> {
+        if !self.is_param_known(key) {
+            if let Ok(value) = Cow::try_from(value) {
+                self.extra_params
+                    .push((key.to_string(), value.into_owned()));
+            }
+            // the bip21 crate rejects unknown `req-` parameters outright, claim them
+            // so that lenient parsing can still accept the URI
+            if key.starts_with("req-") {
+                self.unknown_required.push(key.to_string());
+                return Ok(ParamKind::Known);
+            }
+            return Ok(ParamKind::Unknown);
+        }
+
+        // a required parameter we understand is handled like the optional one
+        let key = key.strip_prefix("req-").unwrap_or(key);
         match key {
             "pj" if self.pj.is_none() => {
                 let endpoint = Cow::try_from(value).map_err(ExtraParamsParseError::NotUtf8)?;
@@ -89,16 +223,54 @@ impl<'a> DeserializationState<'a> for WailaExtras {
                 Ok(ParamKind::Known)
             }
             "pjos" => Err(ExtraParamsParseError::MultipleParams(key.to_string())),
-            "lightning" if self.lightning.is_none() => {
+            "r" if self.r.is_none() => {
+                let url = Cow::try_from(value).map_err(ExtraParamsParseError::NotUtf8)?;
+                let url = Url::parse(&url).map_err(ExtraParamsParseError::BadEndpoint)?;
+                if !matches!(url.scheme(), "http" | "https") {
+                    return Err(ExtraParamsParseError::UnsecureEndpoint);
+                }
+                self.r = Some(url);
+
+                Ok(ParamKind::Known)
+            }
+            "r" => Err(ExtraParamsParseError::MultipleParams(key.to_string())),
+            "sp" if self.sp.is_none() => {
+                let str = Cow::try_from(value)
+                    .map_err(|_| ExtraParamsParseError::SilentPaymentParsingError)?;
+                let address = SilentPaymentAddress::from_str(&str)
+                    .map_err(|_| ExtraParamsParseError::SilentPaymentParsingError)?;
+                self.sp = Some(address);
+
+                Ok(ParamKind::Known)
+            }
+            "sp" => Err(ExtraParamsParseError::MultipleParams(key.to_string())),
+            "pop" if self.pop.is_none() => {
+                let pop = Cow::try_from(value).map_err(ExtraParamsParseError::NotUtf8)?;
+                if !is_valid_pop_callback(&pop) {
+                    return Err(ExtraParamsParseError::BadProofOfPayment);
+                }
+                self.pop = Some(pop.into_owned());
+
+                Ok(ParamKind::Known)
+            }
+            "pop" => Err(ExtraParamsParseError::MultipleParams(key.to_string())),
+            "lightning" => {
                 let str =
                     Cow::try_from(value).map_err(|_| ExtraParamsParseError::InvoiceParsingError)?;
-                let invoice = Bolt11Invoice::from_str(&str)?;
-                self.lightning = Some(invoice);
+                let param = match Bolt11Invoice::from_str(&str) {
+                    Ok(invoice) => LightningParam::Bolt11(invoice),
+                    Err(e) => Offer::from_str(&str)
+                        .map(LightningParam::Bolt12)
+                        .map_err(|_| e)?,
+                };
+                if let (None, LightningParam::Bolt11(invoice)) = (&self.lightning, &param) {
+                    self.lightning = Some(invoice.clone());
+                }
+                self.lightning_params.push(param);
 
                 Ok(ParamKind::Known)
             }
-            "lightning" => Err(ExtraParamsParseError::MultipleParams(key.to_string())),
-            "b12" if self.b12.is_none() => {
+            "lno" | "b12" if self.b12.is_none() => {
                 let str =
                     Cow::try_from(value).map_err(|_| ExtraParamsParseError::InvoiceParsingError)?;
                 let offer = Offer::from_str(&str)?;
@@ -106,12 +278,19 @@ impl<'a> DeserializationState<'a> for WailaExtras {
 
                 Ok(ParamKind::Known)
             }
-            "b12" => Err(ExtraParamsParseError::MultipleParams(key.to_string())),
+            "lno" | "b12" => Err(ExtraParamsParseError::MultipleParams(key.to_string())),
             _ => Ok(ParamKind::Unknown),
         }
     }
 
-    fn finalize(self) -> Result<Self::Value, <Self::Value as DeserializationError>::Error> {
+    fn finalize(mut self) -> Result<Self::Value, <Self::Value as DeserializationError>::Error> {
+        if self.b12.is_none() {
+            self.b12 = self.lightning_params.iter().find_map(|param| match param {
+                LightningParam::Bolt12(offer) => Some(offer.clone()),
+                LightningParam::Bolt11(_) => None,
+            });
+        }
+
         match (self.pj.as_ref(), self.pjos) {
             (None, None) => Ok(self),
             (None, Some(_)) => Err(ExtraParamsParseError::MissingEndpoint),
@@ -131,14 +310,14 @@ impl<'a> DeserializationState<'a> for WailaExtras {
 
 #[cfg(test)]
 mod test {
+    use core::convert::TryFrom;
     use core::str::FromStr;
     use lightning::offers::offer::Offer;
     use lightning::util::ser::Writeable;
-    use std::convert::TryFrom;
 
     use lightning_invoice::Bolt11Invoice;
 
-    use crate::bip21::UnifiedUri;
+    use crate::bip21::{ExtraParamsParseError, LightningParam, UnifiedUri};
 
     #[test]
     fn test_ln_uri() {
@@ -160,6 +339,165 @@ mod test {
         assert_eq!(uri.extras.b12.map(|i| i.encode()), Some(offer.encode()));
     }
 
+    #[test]
+    fn test_lno_uri() {
+        let offer = "lno1qsgqmqvgm96frzdg8m0gc6nzeqffvzsqzrxqy32afmr3jn9ggkwg3egfwch2hy0l6jut6vfd8vpsc3h89l6u3dm4q2d6nuamav3w27xvdmv3lpgklhg7l5teypqz9l53hj7zvuaenh34xqsz2sa967yzqkylfu9xtcd5ymcmfp32h083e805y7jfd236w9afhavqqvl8uyma7x77yun4ehe9pnhu2gekjguexmxpqjcr2j822xr7q34p078gzslf9wpwz5y57alxu99s0z2ql0kfqvwhzycqq45ehh58xnfpuek80hw6spvwrvttjrrq9pphh0dpydh06qqspp5uq4gpyt6n9mwexde44qv7lstzzq60nr40ff38u27un6y53aypmx0p4qruk2tf9mjwqlhxak4znvna5y";
+        let input = format!("bitcoin:BC1QYLH3U67J673H6Y6ALV70M0PL2YZ53TZHVXGG7U?lno={offer}");
+
+        let uri = UnifiedUri::from_str(&input).unwrap();
+        assert_eq!(
+            uri.extras.b12.map(|i| i.encode()),
+            Some(Offer::from_str(offer).unwrap().encode())
+        );
+
+        // only one offer is allowed, whichever key is used
+        let input =
+            format!("bitcoin:BC1QYLH3U67J673H6Y6ALV70M0PL2YZ53TZHVXGG7U?lno={offer}&b12={offer}");
+        assert!(UnifiedUri::from_str(&input).is_err());
+    }
+
+    #[test]
+    fn test_offer_in_lightning_param() {
+        let offer = "lno1qsgqmqvgm96frzdg8m0gc6nzeqffvzsqzrxqy32afmr3jn9ggkwg3egfwch2hy0l6jut6vfd8vpsc3h89l6u3dm4q2d6nuamav3w27xvdmv3lpgklhg7l5teypqz9l53hj7zvuaenh34xqsz2sa967yzqkylfu9xtcd5ymcmfp32h083e805y7jfd236w9afhavqqvl8uyma7x77yun4ehe9pnhu2gekjguexmxpqjcr2j822xr7q34p078gzslf9wpwz5y57alxu99s0z2ql0kfqvwhzycqq45ehh58xnfpuek80hw6spvwrvttjrrq9pphh0dpydh06qqspp5uq4gpyt6n9mwexde44qv7lstzzq60nr40ff38u27un6y53aypmx0p4qruk2tf9mjwqlhxak4znvna5y";
+        let input = format!("bitcoin:BC1QYLH3U67J673H6Y6ALV70M0PL2YZ53TZHVXGG7U?lightning={offer}");
+
+        let uri = UnifiedUri::from_str(&input).unwrap();
+        assert!(uri.extras.lightning.is_none());
+        assert_eq!(
+            uri.extras.b12.as_ref().map(|i| i.encode()),
+            Some(Offer::from_str(offer).unwrap().encode())
+        );
+        assert!(uri.extras.validate(false).is_ok());
+
+        // neither an invoice nor an offer
+        let input =
+            "bitcoin:BC1QYLH3U67J673H6Y6ALV70M0PL2YZ53TZHVXGG7U?lightning=lnbc1notaninvoice";
+        assert!(UnifiedUri::from_str(input).is_err());
+    }
+
+    #[test]
+    fn test_multiple_lightning_params() {
+        let invoice = "LNBC10U1P3PJ257PP5YZTKWJCZ5FTL5LAXKAV23ZMZEKAW37ZK6KMV80PK4XAEV5QHTZ7QDPDWD3XGER9WD5KWM36YPRX7U3QD36KUCMGYP282ETNV3SHJCQZPGXQYZ5VQSP5USYC4LK9CHSFP53KVCNVQ456GANH60D89REYKDNGSMTJ6YW3NHVQ9QYYSSQJCEWM5CJWZ4A6RFJX77C490YCED6PEMK0UPKXHY89CMM7SCT66K8GNEANWYKZGDRWRFJE69H9U5U0W57RRCSYSAS7GADWMZXC8C6T0SPJAZUP6";
+        let offer = "lno1qsgqmqvgm96frzdg8m0gc6nzeqffvzsqzrxqy32afmr3jn9ggkwg3egfwch2hy0l6jut6vfd8vpsc3h89l6u3dm4q2d6nuamav3w27xvdmv3lpgklhg7l5teypqz9l53hj7zvuaenh34xqsz2sa967yzqkylfu9xtcd5ymcmfp32h083e805y7jfd236w9afhavqqvl8uyma7x77yun4ehe9pnhu2gekjguexmxpqjcr2j822xr7q34p078gzslf9wpwz5y57alxu99s0z2ql0kfqvwhzycqq45ehh58xnfpuek80hw6spvwrvttjrrq9pphh0dpydh06qqspp5uq4gpyt6n9mwexde44qv7lstzzq60nr40ff38u27un6y53aypmx0p4qruk2tf9mjwqlhxak4znvna5y";
+        let input = format!(
+            "bitcoin:BC1QYLH3U67J673H6Y6ALV70M0PL2YZ53TZHVXGG7U?lightning={invoice}&lightning={offer}"
+        );
+
+        let uri = UnifiedUri::from_str(&input).unwrap();
+        assert_eq!(
+            uri.extras.lightning,
+            Some(Bolt11Invoice::from_str(invoice).unwrap())
+        );
+        assert_eq!(uri.extras.lightning_params.len(), 2);
+        assert!(matches!(
+            uri.extras.lightning_params[0],
+            LightningParam::Bolt11(_)
+        ));
+        assert!(matches!(
+            uri.extras.lightning_params[1],
+            LightningParam::Bolt12(_)
+        ));
+
+        assert!(uri.extras.validate(false).is_ok());
+    }
+
+    #[test]
+    fn test_payment_request_uri() {
+        let input = "bitcoin:mq7se9wy2egettFxPbmn99cK8v5AFq55Lx?amount=0.11&r=https://merchant.com/pay.php?h%3D2a8628fc2fbe";
+
+        let uri = UnifiedUri::from_str(input).unwrap();
+        assert_eq!(
+            uri.extras.r.map(|u| u.to_string()),
+            Some("https://merchant.com/pay.php?h=2a8628fc2fbe".to_string())
+        );
+
+        let input = "bitcoin:mq7se9wy2egettFxPbmn99cK8v5AFq55Lx?r=https://merchant.com/a&r=https://merchant.com/b";
+        assert!(UnifiedUri::from_str(input).is_err());
+        let input = "bitcoin:mq7se9wy2egettFxPbmn99cK8v5AFq55Lx?r=ftp://merchant.com/a";
+        assert!(UnifiedUri::from_str(input).is_err());
+    }
+
+    #[test]
+    fn test_silent_payment_uri() {
+        let sp = "sp1qqgste7k9hx0qftg6qmwlkqtwuy6cycyavzmzj85c6qdfhjdpdjtdgqjuexzk6murw56suy3e0rd2cgqvycxttddwsvgxe2usfpxumr70xc9pkqwv";
+        let input = format!("bitcoin:BC1QYLH3U67J673H6Y6ALV70M0PL2YZ53TZHVXGG7U?sp={sp}");
+
+        let uri = UnifiedUri::from_str(&input).unwrap();
+        assert_eq!(uri.extras.sp.map(|a| a.to_string()), Some(sp.to_string()));
+
+        let input = "bitcoin:BC1QYLH3U67J673H6Y6ALV70M0PL2YZ53TZHVXGG7U?sp=sp1notanaddress";
+        assert!(UnifiedUri::from_str(input).is_err());
+    }
+
+    #[test]
+    fn test_required_params() {
+        let input =
+            "bitcoin:BC1QYLH3U67J673H6Y6ALV70M0PL2YZ53TZHVXGG7U?req-somethingyoudontunderstand=50";
+        let uri = UnifiedUri::from_str(input).unwrap();
+        assert_eq!(
+            uri.extras.validate(false),
+            Err(ExtraParamsParseError::UnknownRequiredParam(
+                "req-somethingyoudontunderstand".to_string()
+            ))
+        );
+        assert!(uri.extras.validate(true).is_ok());
+
+        // required versions of known params are fine
+        let input =
+            "bitcoin:BC1QYLH3U67J673H6Y6ALV70M0PL2YZ53TZHVXGG7U?req-pj=https://example.com/pj";
+        let uri = UnifiedUri::from_str(input).unwrap();
+        assert!(uri.extras.validate(false).is_ok());
+        assert!(uri.extras.pj.is_some());
+
+        let input = "bitcoin:BC1QYLH3U67J673H6Y6ALV70M0PL2YZ53TZHVXGG7U?somethingelse=50";
+        let uri = UnifiedUri::from_str(input).unwrap();
+        assert!(uri.extras.validate(false).is_ok());
+    }
+
+    #[test]
+    fn test_proof_of_payment() {
+        let input =
+            "bitcoin:BC1QYLH3U67J673H6Y6ALV70M0PL2YZ53TZHVXGG7U?pop=mywallet%3Apop%3Fid%3D1";
+        let uri = UnifiedUri::from_str(input).unwrap();
+        assert_eq!(uri.extras.pop.as_deref(), Some("mywallet:pop?id=1"));
+
+        let input = "bitcoin:BC1QYLH3U67J673H6Y6ALV70M0PL2YZ53TZHVXGG7U?req-pop=callback%3A";
+        let uri = UnifiedUri::from_str(input).unwrap();
+        assert_eq!(uri.extras.pop.as_deref(), Some("callback:"));
+        assert!(uri.extras.validate(false).is_ok());
+
+        for pop in [
+            "https%3A%2F%2Fexample.com",
+            "JavaScript%3Aalert(1)",
+            "nocolon",
+            "1abc%3A",
+        ] {
+            let input = format!("bitcoin:BC1QYLH3U67J673H6Y6ALV70M0PL2YZ53TZHVXGG7U?pop={pop}");
+            assert!(UnifiedUri::from_str(&input).is_err());
+        }
+    }
+
+    #[test]
+    fn test_extra_params() {
+        let input = "bitcoin:BC1QYLH3U67J673H6Y6ALV70M0PL2YZ53TZHVXGG7U?amount=0.001&pj=https://example.com/pj&exp=1700000000&merchant=Bob%27s%20Store&req-foo=bar";
+        let uri = UnifiedUri::from_str(input).unwrap();
+        assert_eq!(
+            uri.extras.extra_params,
+            vec![
+                ("exp".to_string(), "1700000000".to_string()),
+                ("merchant".to_string(), "Bob's Store".to_string()),
+                ("req-foo".to_string(), "bar".to_string()),
+            ]
+        );
+
+        // unknown parameters are kept when the URI is displayed
+        let uri = UnifiedUri::from_str(input).unwrap().assume_checked();
+        assert_eq!(
+            uri.to_string(),
+            "bitcoin:bc1qylh3u67j673h6y6alv70m0pl2yz53tzhvxgg7u?amount=0.001&pj=https://example.com/pj&exp=1700000000&merchant=Bob's%20Store&req-foo=bar"
+        );
+    }
+
     #[test]
     fn test_no_ln_uri() {
         let input = "bitcoin:1andreas3batLhQa2FawWjeyjCqyBzypd";
@@ -0,0 +1,108 @@
+use core::fmt;
+use core::str::FromStr;
+
+use crate::prelude::*;
+
+/// Prefix used to mark a string as a BIP-353 name rather than a lightning address
+pub const BIP353_PREFIX: char = '₿';
+
+/// Max length of a DNS name
+const MAX_NAME_LENGTH: usize = 255;
+
+/// A BIP-353 human readable name, e.g. `₿user@domain.com`, that resolves to
+/// payment instructions through a DNSSEC signed TXT record.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Bip353Name {
+    user: String,
+    domain: String,
+}
+
+fn is_valid_label(label: &str, allow_underscore: bool) -> bool {
+    !label.is_empty()
+        && label.len() <= 63
+        && label
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || (allow_underscore && c == '_'))
+}
+
+impl Bip353Name {
+    pub fn new(user: &str, domain: &str) -> Option<Self> {
+        let user = user.to_lowercase();
+        let domain = domain.trim_end_matches('.').to_lowercase();
+
+        if !user.split('.').all(|l| is_valid_label(l, true))
+            || !domain.contains('.')
+            || !domain.split('.').all(|l| is_valid_label(l, false))
+        {
+            return None;
+        }
+
+        let name = Self { user, domain };
+        if name.dns_name().len() > MAX_NAME_LENGTH {
+            return None;
+        }
+
+        Some(name)
+    }
+
+    pub fn user(&self) -> &str {
+        &self.user
+    }
+
+    pub fn domain(&self) -> &str {
+        &self.domain
+    }
+
+    /// The DNS name the payment instructions are published under
+    pub fn dns_name(&self) -> String {
+        format!("{}.user._bitcoin-payment.{}.", self.user, self.domain)
+    }
+}
+
+impl fmt::Display for Bip353Name {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{BIP353_PREFIX}{}@{}", self.user, self.domain)
+    }
+}
+
+impl FromStr for Bip353Name {
+    type Err = ();
+
+    /// Parses a name with or without the `₿` prefix
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.strip_prefix(BIP353_PREFIX).unwrap_or(s);
+        let (user, domain) = s.split_once('@').ok_or(())?;
+        Bip353Name::new(user, domain).ok_or(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::str::FromStr;
+
+    use super::Bip353Name;
+
+    #[test]
+    fn test_parse_bip353_name() {
+        let name = Bip353Name::from_str("₿matt@mattcorallo.com").unwrap();
+        assert_eq!(name.user(), "matt");
+        assert_eq!(name.domain(), "mattcorallo.com");
+        assert_eq!(
+            name.dns_name(),
+            "matt.user._bitcoin-payment.mattcorallo.com."
+        );
+        assert_eq!(name.to_string(), "₿matt@mattcorallo.com");
+
+        let plain = Bip353Name::from_str("Matt@MattCorallo.com").unwrap();
+        assert_eq!(plain, name);
+    }
+
+    #[test]
+    fn test_invalid_bip353_name() {
+        assert!(Bip353Name::from_str("₿matt").is_err());
+        assert!(Bip353Name::from_str("₿@mattcorallo.com").is_err());
+        assert!(Bip353Name::from_str("₿matt@localhost").is_err());
+        assert!(Bip353Name::from_str("₿matt@mattcorallo.com:8080").is_err());
+        assert!(Bip353Name::from_str("₿ma tt@mattcorallo.com").is_err());
+    }
+}
@@ -0,0 +1,209 @@
+use core::convert::TryInto;
+use core::fmt;
+use core::str::FromStr;
+
+use bitcoin::base58;
+use bitcoin::bip32::{ChainCode, ChildNumber, ExtendedPubKey, Fingerprint};
+use bitcoin::secp256k1::{PublicKey, Secp256k1};
+use bitcoin::{Address, Network};
+
+use crate::prelude::*;
+
+/// Base58 version byte, gives payment codes their `PM8T` prefix
+const VERSION_BYTE: u8 = 0x47;
+const PAYLOAD_LENGTH: usize = 80;
+
+/// A BIP-47 reusable payment code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PaymentCode {
+    version: u8,
+    features: u8,
+    public_key: PublicKey,
+    chain_code: ChainCode,
+}
+
+impl PaymentCode {
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    /// Whether the sender should use bitmessage for notifications
+    pub fn supports_bitmessage(&self) -> bool {
+        self.features & 0x01 != 0
+    }
+
+    pub fn public_key(&self) -> PublicKey {
+        self.public_key
+    }
+
+    pub fn chain_code(&self) -> ChainCode {
+        self.chain_code
+    }
+
+    fn xpub(&self) -> ExtendedPubKey {
+        ExtendedPubKey {
+            network: Network::Bitcoin,
+            depth: 0,
+            parent_fingerprint: Fingerprint::default(),
+            child_number: ChildNumber::from(0),
+            public_key: self.public_key,
+            chain_code: self.chain_code,
+        }
+    }
+
+    /// The key of the first child, which senders pay to notify the receiver
+    pub fn notification_pubkey(&self) -> PublicKey {
+        let secp = Secp256k1::verification_only();
+        self.xpub()
+            .ckd_pub(&secp, ChildNumber::from(0))
+            .expect("non-hardened derivation only fails with negligible probability")
+            .public_key
+    }
+
+    /// Payment codes don't commit to a network, so the caller has to pick one
+    pub fn notification_address(&self, network: Network) -> Address {
+        Address::p2pkh(
+            &bitcoin::PublicKey::new(self.notification_pubkey()),
+            network,
+        )
+    }
+}
+
+/// Max length of a PayNym handle, excluding the `+`
+const MAX_PAYNYM_LENGTH: usize = 64;
+
+/// A PayNym handle like `+stillmountain5a8`, which can be resolved to a
+/// [`PaymentCode`] through the paynym.rs directory
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PayNym {
+    handle: String,
+}
+
+impl PayNym {
+    pub fn new(handle: &str) -> Option<Self> {
+        let handle = handle.strip_prefix('+').unwrap_or(handle).to_lowercase();
+        if handle.is_empty()
+            || handle.len() > MAX_PAYNYM_LENGTH
+            || !handle.chars().all(|c| c.is_ascii_alphanumeric())
+            // handles are words, this keeps phone numbers from parsing as PayNyms
+            || !handle.chars().any(|c| c.is_ascii_alphabetic())
+        {
+            return None;
+        }
+
+        Some(Self { handle })
+    }
+
+    /// The handle without the leading `+`
+    pub fn handle(&self) -> &str {
+        &self.handle
+    }
+}
+
+impl fmt::Display for PayNym {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "+{}", self.handle)
+    }
+}
+
+impl FromStr for PayNym {
+    type Err = ();
+
+    /// Only parses handles with the `+` prefix, otherwise any word would be a PayNym
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if !s.starts_with('+') {
+            return Err(());
+        }
+        PayNym::new(s).ok_or(())
+    }
+}
+
+impl fmt::Display for PaymentCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut data = vec![VERSION_BYTE, self.version, self.features];
+        data.extend_from_slice(&self.public_key.serialize());
+        data.extend_from_slice(self.chain_code.as_bytes());
+        data.extend_from_slice(&[0u8; 13]);
+        f.write_str(&base58::encode_check(&data))
+    }
+}
+
+impl FromStr for PaymentCode {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let data = base58::decode_check(s).map_err(|_| ())?;
+        let (prefix, payload) = data.split_first().ok_or(())?;
+        if *prefix != VERSION_BYTE || payload.len() != PAYLOAD_LENGTH {
+            return Err(());
+        }
+
+        // versions 1 and 2 share the same layout
+        let version = payload[0];
+        if version != 1 && version != 2 {
+            return Err(());
+        }
+
+        let public_key = PublicKey::from_slice(&payload[2..35]).map_err(|_| ())?;
+        let chain_code: [u8; 32] = payload[35..67].try_into().map_err(|_| ())?;
+
+        Ok(Self {
+            version,
+            features: payload[1],
+            public_key,
+            chain_code: ChainCode::from(chain_code),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::str::FromStr;
+
+    use bitcoin::Network;
+
+    use super::{PayNym, PaymentCode};
+
+    // test vectors from BIP-47
+    const ALICE: &str = "PM8TJTLJbPRGxSbc8EJi42Wrr6QbNSaSSVJ5Y3E4pbCYiTHUskHg13935Ubb7q8tx9GVbh2UuRnBc3WSyJHhUrw8KhprKnn9eDznYGieTzFcwQRya4GA";
+    const BOB: &str = "PM8TJS2JxQ5ztXUpBBRnpTbcUXbUHy2T1abfrb3KkAAtMEGNbey4oumH7Hc578WgQJhPjBxteQ5GHHToTYHE3A1w6p7tU6KSoFmWBVbFGjKPisZDbP97";
+
+    #[test]
+    fn test_parse_payment_code() {
+        let alice = PaymentCode::from_str(ALICE).unwrap();
+        assert_eq!(alice.version(), 1);
+        assert!(!alice.supports_bitmessage());
+        assert_eq!(
+            alice.notification_address(Network::Bitcoin).to_string(),
+            "1JDdmqFLhpzcUwPeinhJbUPw4Co3aWLyzW"
+        );
+        assert_eq!(alice.to_string(), ALICE);
+
+        let bob = PaymentCode::from_str(BOB).unwrap();
+        assert_eq!(
+            bob.notification_address(Network::Bitcoin).to_string(),
+            "1ChvUUvht2hUQufHBXF8NgLhW8SwE2ecGV"
+        );
+    }
+
+    #[test]
+    fn test_parse_paynym() {
+        let nym = PayNym::from_str("+StillMountain5a8").unwrap();
+        assert_eq!(nym.handle(), "stillmountain5a8");
+        assert_eq!(nym.to_string(), "+stillmountain5a8");
+
+        assert!(PayNym::from_str("stillmountain5a8").is_err());
+        assert!(PayNym::from_str("+").is_err());
+        assert!(PayNym::from_str("+still mountain").is_err());
+        assert!(PayNym::from_str("+1234567890").is_err());
+    }
+
+    #[test]
+    fn test_invalid_payment_code() {
+        // bad checksum
+        assert!(PaymentCode::from_str(&ALICE.replace("4GA", "4GB")).is_err());
+        // an xpub isn't a payment code
+        assert!(PaymentCode::from_str("xpub6D3anNhuPFsyitz6c7Kgp1sS3Dcz5CReqh3Pf8CBaBxNy9PYSuKKz4869P2hbxQJccVAKAbfU5xz7VYDBQDcubAPNZ73ADBoxvnMB4PXXYE").is_err());
+        assert!(PaymentCode::from_str("").is_err());
+    }
+}
@@ -0,0 +1,54 @@
+use nostr::Url;
+
+use crate::prelude::*;
+
+/// Endpoints only a Cashu mint serves, see NUT-01, NUT-02 and NUT-06
+const MINT_ENDPOINTS: [&str; 3] = ["/v1/info", "/v1/keys", "/v1/keysets"];
+
+/// Parses the url of a Cashu mint from a `cashu:` URI
+pub(crate) fn parse_mint_url(str: &str) -> Option<Url> {
+    let url = Url::parse(str).ok()?;
+    if !matches!(url.scheme(), "http" | "https")
+        || url.host_str().is_none()
+        || url.query().is_some()
+        || url.fragment().is_some()
+    {
+        return None;
+    }
+    Some(url)
+}
+
+/// Bare urls are too ambiguous to be mints unless they point at one of the
+/// endpoints of the mint's API, returns the url of the mint itself
+pub(crate) fn parse_mint_endpoint_url(str: &str) -> Option<Url> {
+    let mut url = parse_mint_url(str)?;
+    let path = url.path().trim_end_matches('/');
+    let base = MINT_ENDPOINTS
+        .iter()
+        .find_map(|endpoint| path.strip_suffix(endpoint))?
+        .to_string();
+    url.set_path(&base);
+    Some(url)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_mint_url() {
+        let url = parse_mint_endpoint_url("https://mint.example.com/v1/info").unwrap();
+        assert_eq!(url.as_str(), "https://mint.example.com/");
+
+        // mints can live under a path
+        let url = parse_mint_endpoint_url("https://example.com/cashu/api/v1/keysets/").unwrap();
+        assert_eq!(url.as_str(), "https://example.com/cashu/api");
+
+        assert!(parse_mint_endpoint_url("https://mint.example.com").is_none());
+        assert!(parse_mint_endpoint_url("https://mint.example.com/v1/info?x=1").is_none());
+        assert!(parse_mint_endpoint_url("ftp://mint.example.com/v1/info").is_none());
+
+        assert!(parse_mint_url("https://mint.example.com").is_some());
+        assert!(parse_mint_url("mint.example.com").is_none());
+    }
+}
@@ -0,0 +1,73 @@
+use bitcoin::Network;
+use miniscript::descriptor::{Descriptor, DescriptorPublicKey};
+use miniscript::ForEachKey;
+
+use crate::prelude::*;
+
+/// The network of the extended keys in a descriptor.
+/// Returns `None` if there are no extended keys or they disagree.
+pub(crate) fn descriptor_network(descriptor: &Descriptor<DescriptorPublicKey>) -> Option<Network> {
+    let mut networks = Vec::new();
+    descriptor.for_each_key(|key| {
+        match key {
+            DescriptorPublicKey::Single(_) => {}
+            DescriptorPublicKey::XPub(xpub) => networks.push(xpub.xkey.network),
+            DescriptorPublicKey::MultiXPub(xpub) => networks.push(xpub.xkey.network),
+        }
+        true
+    });
+
+    let network = *networks.first()?;
+    if networks.iter().all(|n| *n == network) {
+        Some(network)
+    } else {
+        None
+    }
+}
+
+/// Extended keys only distinguish mainnet from everything else
+pub(crate) fn is_valid_for_network(key_network: Network, network: Network) -> bool {
+    match key_network {
+        Network::Bitcoin => network == Network::Bitcoin,
+        _ => network != Network::Bitcoin,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::str::FromStr;
+
+    use bitcoin::Network;
+    use miniscript::descriptor::{Descriptor, DescriptorPublicKey};
+
+    use super::*;
+
+    #[test]
+    fn test_descriptor_network() {
+        let desc = Descriptor::<DescriptorPublicKey>::from_str("wpkh([e2867bb6/84'/0'/0']xpub6D3anNhuPFsyitz6c7Kgp1sS3Dcz5CReqh3Pf8CBaBxNy9PYSuKKz4869P2hbxQJccVAKAbfU5xz7VYDBQDcubAPNZ73ADBoxvnMB4PXXYE/0/*)").unwrap();
+        assert_eq!(descriptor_network(&desc), Some(Network::Bitcoin));
+
+        let desc = Descriptor::<DescriptorPublicKey>::from_str("wpkh([e2867bb6/84'/1'/0']tpubDDPRy5xWxJTuVmsh7YRzK8o2EdMWgn4t41fTLxXRgyRN7EKvN2L8BKCFC1gUfPu8Xp6rr667Yc26zrXsiBZsgBc8dQiYnhPNk2Q7CsBrer5/0/*)").unwrap();
+        assert_eq!(descriptor_network(&desc), Some(Network::Testnet));
+
+        // single keys don't tell us the network
+        let desc = Descriptor::<DescriptorPublicKey>::from_str(
+            "wpkh(0259570c00d5e9780bb014481f361f63e7a6d7f7317d09b3a4f39e50b562176b8e)",
+        )
+        .unwrap();
+        assert_eq!(descriptor_network(&desc), None);
+
+        // mixed networks
+        let desc = Descriptor::<DescriptorPublicKey>::from_str("wsh(sortedmulti(1,xpub6D3anNhuPFsyitz6c7Kgp1sS3Dcz5CReqh3Pf8CBaBxNy9PYSuKKz4869P2hbxQJccVAKAbfU5xz7VYDBQDcubAPNZ73ADBoxvnMB4PXXYE/0/*,tpubDDPRy5xWxJTuVmsh7YRzK8o2EdMWgn4t41fTLxXRgyRN7EKvN2L8BKCFC1gUfPu8Xp6rr667Yc26zrXsiBZsgBc8dQiYnhPNk2Q7CsBrer5/0/*))").unwrap();
+        assert_eq!(descriptor_network(&desc), None);
+    }
+
+    #[test]
+    fn test_is_valid_for_network() {
+        assert!(is_valid_for_network(Network::Bitcoin, Network::Bitcoin));
+        assert!(!is_valid_for_network(Network::Bitcoin, Network::Testnet));
+        assert!(is_valid_for_network(Network::Testnet, Network::Signet));
+        assert!(is_valid_for_network(Network::Testnet, Network::Regtest));
+        assert!(!is_valid_for_network(Network::Testnet, Network::Bitcoin));
+    }
+}
@@ -0,0 +1,864 @@
+use core::fmt;
+
+use core::ops::Range;
+use core::str::FromStr;
+
+use bitcoin::address::{self, Address};
+use bitcoin::bech32;
+use bitcoin::hashes::hex;
+use bitcoin::{base58, bip32, key};
+use lightning::offers::parse::Bolt12ParseError;
+use lightning_invoice::{Bolt11ParseError, ParseOrSemanticError};
+use nostr::nips::nip19;
+
+use crate::prelude::*;
+use crate::PaymentParamsKind;
+
+const BECH32_CHARSET: &str = "qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const BASE58_CHARSET: &str = "123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Characters that aren't part of an encoding, and what they are usually misread for
+const BECH32_MISREADS: [(char, &str); 3] = [('o', "0"), ('i', "l"), ('b', "86")];
+const BASE58_MISREADS: [(char, &str); 4] = [('0', "o"), ('O', "o"), ('I', "1"), ('l', "1i")];
+
+/// Characters OCR and people commonly confuse with each other
+const CONFUSABLES: [&str; 4] = ["qg9", "uv", "s5", "z2"];
+
+/// Only this many misread characters are corrected, each one multiplies the work
+const MAX_MISREADS: usize = 4;
+
+/// How many corrected strings are checked before giving up on a suggestion, enough
+/// to try every single character change of an invoice
+const MAX_CANDIDATES: usize = 50_000;
+
+/// Why a parser rejected the input
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ParseFailureReason {
+    /// The checksum didn't match, usually a typo or something cut off while copying
+    InvalidChecksum,
+    /// The bech32 human readable part isn't one the parser accepts, e.g. `lnxy` for an invoice
+    WrongHrp(String),
+    /// The encoding was valid but its version doesn't belong to a network we know
+    BadNetwork,
+    /// The input parsed but is for a different network than the one expected
+    WrongNetwork,
+    /// The input, its decoded data or the number of seed words had an unexpected length
+    InvalidLength(usize),
+    /// The input has characters that aren't part of its encoding
+    InvalidEncoding,
+    /// The input decoded but what it contains is invalid
+    Invalid,
+}
+
+impl fmt::Display for ParseFailureReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseFailureReason::InvalidChecksum => write!(f, "invalid checksum"),
+            ParseFailureReason::WrongHrp(hrp) => write!(f, "unexpected prefix {hrp}"),
+            ParseFailureReason::BadNetwork => write!(f, "unknown network"),
+            ParseFailureReason::WrongNetwork => write!(f, "wrong network"),
+            ParseFailureReason::InvalidLength(len) => write!(f, "unexpected length {len}"),
+            ParseFailureReason::InvalidEncoding => write!(f, "invalid characters"),
+            ParseFailureReason::Invalid => write!(f, "invalid"),
+        }
+    }
+}
+
+/// A parser the input looked like it was meant for, and why it failed
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ParseAttempt {
+    pub kind: PaymentParamsKind,
+    pub reason: ParseFailureReason,
+}
+
+impl fmt::Display for ParseAttempt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.kind, self.reason)
+    }
+}
+
+/// The error returned when a string can't be parsed into [`crate::PaymentParams`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum WailaParseError {
+    /// The input was empty or only whitespace
+    Empty,
+    /// The input didn't look like anything we know
+    Unrecognized,
+    /// The input looked like one or more formats but was invalid for all of them
+    Invalid(Vec<ParseAttempt>),
+}
+
+impl WailaParseError {
+    /// The parsers the input looked like it was meant for, most likely first
+    pub fn attempts(&self) -> &[ParseAttempt] {
+        match self {
+            WailaParseError::Invalid(attempts) => attempts,
+            WailaParseError::Empty | WailaParseError::Unrecognized => &[],
+        }
+    }
+
+    /// A corrected version of `input`, the string that failed with this error, that
+    /// does parse. Found when the input was off by a character or two, or had
+    /// characters that are commonly misread, e.g. `0` for `o`.
+    ///
+    /// This searches for corrections so is only done when asked for, and gives up
+    /// after a bounded number of candidates. Secrets are never suggested.
+    pub fn suggestion(&self, input: &str) -> Option<String> {
+        let attempts = self.attempts();
+        let correctable = attempts.iter().any(|attempt| {
+            matches!(
+                attempt.reason,
+                ParseFailureReason::InvalidChecksum | ParseFailureReason::InvalidEncoding
+            )
+        });
+        // never echo a corrected secret back, suggestions end up in logs and UIs
+        let sensitive = attempts.iter().any(|attempt| {
+            matches!(
+                attempt.kind,
+                PaymentParamsKind::PrivateKey | PaymentParamsKind::NostrSecretKey
+            )
+        });
+        if !correctable || sensitive {
+            return None;
+        }
+
+        let (_, payload) = payload(input);
+        suggest(&payload)
+    }
+}
+
+impl fmt::Display for WailaParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WailaParseError::Empty => write!(f, "empty input"),
+            WailaParseError::Unrecognized => write!(f, "unrecognized input"),
+            WailaParseError::Invalid(attempts) => {
+                let attempts: Vec<String> = attempts.iter().map(|a| a.to_string()).collect();
+                write!(f, "invalid input ({})", attempts.join(", "))
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for WailaParseError {}
+
+fn attempt(kind: PaymentParamsKind, reason: ParseFailureReason) -> ParseAttempt {
+    ParseAttempt { kind, reason }
+}
+
+/// Collects why the parsers the input looked like it was meant for failed
+#[derive(Debug, Default)]
+pub(crate) struct Attempts {
+    attempts: Vec<ParseAttempt>,
+}
+
+impl Attempts {
+    /// Records that the parser for `kind` failed, `None` meaning the input wasn't meant for it
+    pub(crate) fn record(&mut self, kind: PaymentParamsKind, reason: Option<ParseFailureReason>) {
+        // several parsers can return the same kind, the first one is the most specific
+        if let Some(reason) = reason {
+            if !self.attempts.iter().any(|attempt| attempt.kind == kind) {
+                self.attempts.push(attempt(kind, reason));
+            }
+        }
+    }
+
+    /// Records a parser that doesn't say why it failed, if `str` has the bech32 prefix of `kind`
+    pub(crate) fn record_hrp(&mut self, kind: PaymentParamsKind, str: &str) {
+        let reason = has_hrp(str, kind).then_some(ParseFailureReason::Invalid);
+        self.record(kind, reason);
+    }
+
+    /// Records why a `bitcoin:` URI failed, blaming its address when that doesn't parse
+    pub(crate) fn record_bip21(&mut self, str: &str) {
+        let Some(uri) = crate::strip_prefix_ignore_case(str, "bitcoin:") else {
+            return;
+        };
+        let address = uri.split('?').next().unwrap_or(uri);
+        match Address::from_str(address) {
+            Err(e) => self.record(PaymentParamsKind::OnChain, address_reason(address, &e)),
+            Ok(_) => self.record(PaymentParamsKind::Bip21, Some(ParseFailureReason::Invalid)),
+        }
+    }
+
+    /// The error for `str` after every parser failed
+    pub(crate) fn into_error(self, str: &str) -> WailaParseError {
+        let (scheme, payload) = payload(str);
+        if payload.is_empty() {
+            return WailaParseError::Empty;
+        }
+
+        match scheme {
+            _ if !self.attempts.is_empty() => WailaParseError::Invalid(self.attempts),
+            // none of the payload parsers knew what was wrong, so blame the URI
+            Some(kind) => {
+                WailaParseError::Invalid(vec![attempt(kind, ParseFailureReason::Invalid)])
+            }
+            None => WailaParseError::Unrecognized,
+        }
+    }
+}
+
+/// The kind of the URI scheme `str` has, if any, and the part of it that can be corrected
+fn payload(str: &str) -> (Option<PaymentParamsKind>, String) {
+    let mut str = str.trim();
+    while let Some(payload) = crate::strip_wallet_scheme(str) {
+        str = payload;
+    }
+    let normalized = crate::normalize_scheme(str);
+    let str = normalized.trim();
+    match scheme_kind(str) {
+        Some((kind, payload)) => (Some(kind), payload.to_string()),
+        None => (None, str.to_string()),
+    }
+}
+
+/// The kind a URI scheme is used for, along with the payload after the scheme
+fn scheme_kind(str: &str) -> Option<(PaymentParamsKind, &str)> {
+    let (scheme, payload) = str.split_once(':')?;
+    let kind = match scheme.to_lowercase().as_str() {
+        "bitcoin" => PaymentParamsKind::Bip21,
+        "lightning" => PaymentParamsKind::Bolt11,
+        "lnurl" | "lnurlp" | "lnurlc" | "lnurlw" | "keyauth" => PaymentParamsKind::LnUrl,
+        "nostr" => PaymentParamsKind::Nostr,
+        "nostr+walletauth" => PaymentParamsKind::NostrWalletAuth,
+        "nostr+walletconnect" | "nostrwalletconnect" => PaymentParamsKind::NostrWalletConnect,
+        "fedimint" => PaymentParamsKind::FedimintInvite,
+        "cashu" => PaymentParamsKind::CashuToken,
+        "liquidnetwork" => PaymentParamsKind::Liquid,
+        "ark" => PaymentParamsKind::Ark,
+        "lndconnect" => PaymentParamsKind::LndConnect,
+        "lndhub" => PaymentParamsKind::LndHub,
+        "slash" => PaymentParamsKind::Slashtag,
+        #[cfg(feature = "rgb")]
+        "rgb" => PaymentParamsKind::Rgb,
+        _ => return None,
+    };
+
+    // only the address of a BIP21 style URI can be corrected
+    let payload = match kind {
+        PaymentParamsKind::Bip21 | PaymentParamsKind::Liquid | PaymentParamsKind::Ark => {
+            payload.split('?').next().unwrap_or(payload)
+        }
+        _ => payload,
+    };
+    Some((kind, payload))
+}
+
+/// The kind a bech32 human readable part is used for
+fn hrp_kind(hrp: &str) -> Option<PaymentParamsKind> {
+    let kind = match hrp {
+        "bc" | "tb" | "bcrt" => PaymentParamsKind::OnChain,
+        "lno" => PaymentParamsKind::Bolt12,
+        "lnr" => PaymentParamsKind::Bolt12Refund,
+        "lni" => PaymentParamsKind::Bolt12Invoice,
+        "lnurl" => PaymentParamsKind::LnUrl,
+        "npub" => PaymentParamsKind::Nostr,
+        "nprofile" => PaymentParamsKind::NostrProfile,
+        "note" => PaymentParamsKind::NostrNote,
+        "nevent" => PaymentParamsKind::NostrEvent,
+        "nsec" => PaymentParamsKind::NostrSecretKey,
+        "fed1" => PaymentParamsKind::FedimintInvite,
+        "sp" | "tsp" | "sprt" => PaymentParamsKind::SilentPayment,
+        "ark" | "tark" => PaymentParamsKind::Ark,
+        "ex" | "lq" | "tex" | "tlq" | "ert" | "el" => PaymentParamsKind::Liquid,
+        hrp if ["lnbc", "lntb", "lnsb"].iter().any(|p| hrp.starts_with(p)) => {
+            PaymentParamsKind::Bolt11
+        }
+        _ => return None,
+    };
+    Some(kind)
+}
+
+/// The lowercased human readable part of a bech32 string
+fn bech32_hrp(str: &str) -> Option<String> {
+    let (hrp, _) = str.rsplit_once('1')?;
+    if hrp.is_empty() || !hrp.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return None;
+    }
+    Some(hrp.to_lowercase())
+}
+
+fn has_hrp(str: &str, kind: PaymentParamsKind) -> bool {
+    bech32_hrp(str).and_then(|hrp| hrp_kind(&hrp)) == Some(kind)
+}
+
+/// The kind a base58 string is used for, going by its version prefix
+fn base58_kind(str: &str) -> Option<PaymentParamsKind> {
+    let base58 = str
+        .chars()
+        .all(|c| BASE58_CHARSET.contains(c) || BASE58_MISREADS.iter().any(|(m, _)| *m == c));
+    if !base58 {
+        return None;
+    }
+    if str.starts_with("PM8T") {
+        return Some(PaymentParamsKind::PaymentCode);
+    }
+    let xpub_prefixes = [
+        "xpub", "ypub", "zpub", "Ypub", "Zpub", "tpub", "upub", "vpub", "Upub", "Vpub",
+    ];
+    if xpub_prefixes.iter().any(|p| str.starts_with(p)) {
+        return Some(PaymentParamsKind::Xpub);
+    }
+    // the version is often what was misread, e.g. `l` for `1`
+    let first = str.chars().next()?;
+    let first = BASE58_MISREADS
+        .iter()
+        .find(|(m, _)| *m == first)
+        .and_then(|(_, replacements)| replacements.chars().next())
+        .unwrap_or(first);
+    match first {
+        '1' | '3' | 'm' | 'n' | '2' => Some(PaymentParamsKind::OnChain),
+        '5' | 'K' | 'L' | '9' | 'c' => Some(PaymentParamsKind::PrivateKey),
+        _ => None,
+    }
+}
+
+fn bech32_reason(error: &bech32::Error) -> ParseFailureReason {
+    match error {
+        bech32::Error::InvalidChecksum => ParseFailureReason::InvalidChecksum,
+        _ => ParseFailureReason::InvalidEncoding,
+    }
+}
+
+fn base58_reason(error: &base58::Error) -> ParseFailureReason {
+    match error {
+        base58::Error::BadChecksum(_, _) => ParseFailureReason::InvalidChecksum,
+        base58::Error::BadByte(_) => ParseFailureReason::InvalidEncoding,
+        base58::Error::InvalidLength(len) => ParseFailureReason::InvalidLength(*len),
+        // the right length, so the version is what was wrong
+        base58::Error::InvalidAddressVersion(_) | base58::Error::InvalidExtendedKeyVersion(_) => {
+            ParseFailureReason::BadNetwork
+        }
+        _ => ParseFailureReason::Invalid,
+    }
+}
+
+pub(crate) fn address_reason(str: &str, error: &address::Error) -> Option<ParseFailureReason> {
+    match error {
+        // only tried for the bech32 prefixes of bitcoin networks
+        address::Error::Bech32(e) => Some(bech32_reason(e)),
+        address::Error::InvalidWitnessProgramLength(len)
+        | address::Error::InvalidSegwitV0ProgramLength(len) => {
+            Some(ParseFailureReason::InvalidLength(*len))
+        }
+        address::Error::EmptyBech32Payload
+        | address::Error::InvalidBech32Variant { .. }
+        | address::Error::InvalidWitnessVersion(_)
+        | address::Error::UnparsableWitnessVersion(_)
+        | address::Error::MalformedWitnessVersion => Some(ParseFailureReason::Invalid),
+        // longer strings are rejected by length before being decoded
+        address::Error::Base58(e)
+            if (25..=50).contains(&str.len())
+                && base58_kind(str) == Some(PaymentParamsKind::OnChain) =>
+        {
+            Some(base58_reason(e))
+        }
+        _ => None,
+    }
+}
+
+pub(crate) fn bolt11_reason(str: &str, error: &ParseOrSemanticError) -> Option<ParseFailureReason> {
+    let hrp = bech32_hrp(str).filter(|hrp| hrp.starts_with("ln"))?;
+    if !matches!(hrp_kind(&hrp), None | Some(PaymentParamsKind::Bolt11)) {
+        return None;
+    }
+
+    let reason = match error {
+        ParseOrSemanticError::ParseError(Bolt11ParseError::Bech32Error(e)) => bech32_reason(e),
+        ParseOrSemanticError::ParseError(Bolt11ParseError::UnknownCurrency) => {
+            ParseFailureReason::WrongHrp(hrp)
+        }
+        _ => ParseFailureReason::Invalid,
+    };
+    Some(reason)
+}
+
+pub(crate) fn bolt12_reason(
+    str: &str,
+    kind: PaymentParamsKind,
+    error: &Bolt12ParseError,
+) -> Option<ParseFailureReason> {
+    if !has_hrp(str, kind) {
+        return None;
+    }
+
+    let reason = match error {
+        Bolt12ParseError::Bech32(e) => bech32_reason(e),
+        Bolt12ParseError::InvalidContinuation => ParseFailureReason::InvalidEncoding,
+        _ => ParseFailureReason::Invalid,
+    };
+    Some(reason)
+}
+
+pub(crate) fn nip19_reason(
+    str: &str,
+    kind: PaymentParamsKind,
+    error: &nip19::Error,
+) -> Option<ParseFailureReason> {
+    if !has_hrp(str, kind) {
+        return None;
+    }
+
+    let reason = match error {
+        nip19::Error::Bech32(e) => bech32_reason(e),
+        nip19::Error::WrongPrefixOrVariant => ParseFailureReason::InvalidEncoding,
+        _ => ParseFailureReason::Invalid,
+    };
+    Some(reason)
+}
+
+pub(crate) fn wif_reason(str: &str, error: &key::Error) -> Option<ParseFailureReason> {
+    if base58_kind(str) != Some(PaymentParamsKind::PrivateKey) {
+        return None;
+    }
+
+    match error {
+        key::Error::Base58(e) => Some(base58_reason(e)),
+        _ => Some(ParseFailureReason::Invalid),
+    }
+}
+
+pub(crate) fn xpub_reason(str: &str, error: &bip32::Error) -> Option<ParseFailureReason> {
+    if base58_kind(str) != Some(PaymentParamsKind::Xpub) {
+        return None;
+    }
+
+    let reason = match error {
+        bip32::Error::Base58(e) => base58_reason(e),
+        bip32::Error::WrongExtendedKeyLength(len) => ParseFailureReason::InvalidLength(*len),
+        bip32::Error::UnknownVersion(_) => ParseFailureReason::BadNetwork,
+        _ => ParseFailureReason::Invalid,
+    };
+    Some(reason)
+}
+
+/// Payment codes don't say why they failed, only that the input was meant to be one is known
+pub(crate) fn payment_code_reason(str: &str) -> Option<ParseFailureReason> {
+    (base58_kind(str) == Some(PaymentParamsKind::PaymentCode))
+        .then_some(ParseFailureReason::Invalid)
+}
+
+pub(crate) fn mnemonic_reason(str: &str, error: &bip39::Error) -> Option<ParseFailureReason> {
+    let words: Vec<&str> = str.split_whitespace().collect();
+    if words.len() < 11
+        || !words
+            .iter()
+            .all(|w| w.chars().all(|c| c.is_ascii_alphabetic()))
+    {
+        return None;
+    }
+
+    let reason = match error {
+        bip39::Error::BadWordCount(count) => ParseFailureReason::InvalidLength(*count),
+        bip39::Error::InvalidChecksum => ParseFailureReason::InvalidChecksum,
+        _ => ParseFailureReason::Invalid,
+    };
+    Some(reason)
+}
+
+fn is_hex(str: &str, lengths: Range<usize>) -> bool {
+    lengths.contains(&str.len()) && str.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+pub(crate) fn txid_reason(str: &str, error: &hex::Error) -> Option<ParseFailureReason> {
+    if !is_hex(str, 60..66) {
+        return None;
+    }
+
+    match error {
+        hex::Error::InvalidLength(_, _) | hex::Error::OddLengthString(_) => {
+            Some(ParseFailureReason::InvalidLength(str.len()))
+        }
+        hex::Error::InvalidChar(_) => Some(ParseFailureReason::InvalidEncoding),
+    }
+}
+
+pub(crate) fn node_pubkey_reason(str: &str) -> Option<ParseFailureReason> {
+    if !is_hex(str, 66..71) {
+        return None;
+    }
+
+    match str.len() {
+        66 => Some(ParseFailureReason::Invalid),
+        len => Some(ParseFailureReason::InvalidLength(len)),
+    }
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn lightning_address_reason(str: &str) -> Option<ParseFailureReason> {
+    let looks_like_address =
+        str.contains('@') && !str.contains(char::is_whitespace) && !str.contains(':');
+    looks_like_address.then_some(ParseFailureReason::Invalid)
+}
+
+/// Every way of replacing the misread characters of `str`, `None` if there are too many
+fn replace_misreads(str: &str, misreads: &[(char, &str)]) -> Option<Vec<String>> {
+    let mut candidates = vec![String::new()];
+    let mut count = 0;
+    for c in str.chars() {
+        match misreads.iter().find(|(m, _)| *m == c) {
+            Some((_, replacements)) => {
+                count += 1;
+                if count > MAX_MISREADS {
+                    return None;
+                }
+                candidates = candidates
+                    .iter()
+                    .flat_map(|candidate| {
+                        replacements.chars().map(move |r| format!("{candidate}{r}"))
+                    })
+                    .collect();
+            }
+            None => candidates
+                .iter_mut()
+                .for_each(|candidate| candidate.push(c)),
+        }
+    }
+    Some(candidates)
+}
+
+/// Returns the only string `is_valid` accepts after changing a single character
+/// of `data`, to any of `charset`, or up to two commonly confused ones.
+///
+/// Every candidate checked uses up some of `budget`, once it runs out the search
+/// is abandoned as there could be another correction that wasn't checked.
+fn correct(
+    prefix: &str,
+    data: &str,
+    charset: &str,
+    budget: &mut usize,
+    is_valid: impl Fn(&str) -> bool,
+) -> Option<String> {
+    let chars: Vec<char> = data.chars().collect();
+    // `None` once the budget has run out
+    let mut check = |changes: &[(usize, char)]| -> Option<Option<String>> {
+        *budget = budget.checked_sub(1)?;
+        let mut chars = chars.clone();
+        for (i, c) in changes {
+            chars[*i] = *c;
+        }
+        let candidate = format!("{prefix}{}", chars.iter().collect::<String>());
+        Some(is_valid(&candidate).then_some(candidate))
+    };
+
+    let mut found: Vec<String> = vec![];
+    for (i, current) in chars.iter().enumerate() {
+        for c in charset.chars().filter(|c| c != current) {
+            found.extend(check(&[(i, c)])?);
+        }
+    }
+
+    if found.is_empty() {
+        let confusable = |c: char| CONFUSABLES.iter().find(|group| group.contains(c));
+        let positions: Vec<usize> = (0..chars.len())
+            .filter(|i| confusable(chars[*i]).is_some())
+            .collect();
+        for (n, a) in positions.iter().enumerate() {
+            for b in &positions[n + 1..] {
+                for ca in confusable(chars[*a]).into_iter().flat_map(|g| g.chars()) {
+                    for cb in confusable(chars[*b]).into_iter().flat_map(|g| g.chars()) {
+                        if ca == chars[*a] || cb == chars[*b] {
+                            continue;
+                        }
+                        found.extend(check(&[(*a, ca), (*b, cb)])?);
+                    }
+                }
+            }
+        }
+    }
+
+    // more than one correction is a guess
+    match found.as_slice() {
+        [suggestion] => Some(suggestion.clone()),
+        _ => None,
+    }
+}
+
+fn suggest_bech32(str: &str, budget: &mut usize) -> Option<String> {
+    let uppercase = str.chars().all(|c| !c.is_ascii_lowercase());
+    let lower = str.to_lowercase();
+    let (hrp, data) = lower.rsplit_once('1')?;
+    hrp_kind(hrp)?;
+
+    let prefix = format!("{hrp}1");
+    let suggestion = replace_misreads(data, &BECH32_MISREADS)?
+        .into_iter()
+        .find_map(|data| {
+            let candidate = format!("{prefix}{data}");
+            if bech32::decode(&candidate).is_ok() {
+                return Some(candidate);
+            }
+            correct(&prefix, &data, BECH32_CHARSET, budget, |c| {
+                bech32::decode(c).is_ok()
+            })
+        })?;
+
+    if uppercase {
+        Some(suggestion.to_uppercase())
+    } else {
+        Some(suggestion)
+    }
+}
+
+fn suggest_base58(str: &str, budget: &mut usize) -> Option<String> {
+    base58_kind(str)?;
+    replace_misreads(str, &BASE58_MISREADS)?
+        .into_iter()
+        .find_map(|candidate| {
+            if base58::decode_check(&candidate).is_ok() {
+                return Some(candidate);
+            }
+            correct("", &candidate, BASE58_CHARSET, budget, |c| {
+                base58::decode_check(c).is_ok()
+            })
+        })
+}
+
+/// A corrected version of `str` that parses, and isn't a secret
+fn suggest(str: &str) -> Option<String> {
+    let mut budget = MAX_CANDIDATES;
+    suggest_bech32(str, &mut budget)
+        .or_else(|| suggest_base58(str, &mut budget))
+        .filter(|suggestion| {
+            crate::parse_payment_params(suggestion, Default::default())
+                .map_or(false, |params| !params.is_sensitive())
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use bitcoin::bech32::{u5, ToBase32};
+
+    use super::*;
+
+    const INVOICE: &str = "lnbc20m1pvjluezsp5zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zygspp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypqhp58yjmdan79s6qqdhdzgynm4zwqd5d7xmw5fk98klysy043l2ahrqsfpp3qjmp7lwpagxun9pygexvgpjdc4jdj85fr9yq20q82gphp2nflc7jtzrcazrra7wwgzxqc8u7754cdlpfrmccae92qgzqvzq2ps8pqqqqqqpqqqqq9qqqvpeuqafqxu92d8lr6fvg0r5gv0heeeqgcrqlnm6jhphu9y00rrhy4grqszsvpcgpy9qqqqqqgqqqqq7qqzq9qrsgqdfjcdk6w3ak5pca9hwfwfh63zrrz06wwfya0ydlzpgzxkn5xagsqz7x9j4jwe7yj7vaf2k9lqsdk45kts2fd0fkr28am0u4w95tt2nsq76cqw0";
+
+    fn diagnose(str: &str) -> WailaParseError {
+        crate::parse_payment_params(str, Default::default()).unwrap_err()
+    }
+
+    fn reasons(str: &str) -> Vec<(PaymentParamsKind, ParseFailureReason)> {
+        diagnose(str)
+            .attempts()
+            .iter()
+            .map(|a| (a.kind, a.reason.clone()))
+            .collect()
+    }
+
+    fn segwit_address(hrp: &str, program_len: usize) -> String {
+        let mut data = vec![u5::try_from_u8(0).unwrap()];
+        data.extend(vec![1u8; program_len].to_base32());
+        bech32::encode(hrp, data, bech32::Variant::Bech32).unwrap()
+    }
+
+    #[test]
+    fn test_empty_and_unrecognized() {
+        assert_eq!(diagnose(""), WailaParseError::Empty);
+        assert_eq!(diagnose(" \n"), WailaParseError::Empty);
+        assert_eq!(diagnose("hello"), WailaParseError::Unrecognized);
+        assert_eq!(
+            diagnose("https://example.com"),
+            WailaParseError::Unrecognized
+        );
+    }
+
+    #[test]
+    fn test_diagnose_checksums() {
+        // last character changed
+        assert_eq!(
+            reasons("bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdd"),
+            vec![(
+                PaymentParamsKind::OnChain,
+                ParseFailureReason::InvalidChecksum
+            )]
+        );
+        assert_eq!(
+            reasons("1andreas3batLhQa2FawWjeyjCqyBzypc"),
+            vec![(
+                PaymentParamsKind::OnChain,
+                ParseFailureReason::InvalidChecksum
+            )]
+        );
+        assert_eq!(
+            reasons("npub1u8lnhlw5usp3t9vmpz60ejpyt649z33hu82wc2hpv6m5xdqmuxhs46turx"),
+            vec![(
+                PaymentParamsKind::Nostr,
+                ParseFailureReason::InvalidChecksum
+            )]
+        );
+        assert_eq!(
+            reasons("xpub6D3anNhuPFsyitz6c7Kgp1sS3Dcz5CReqh3Pf8CBaBxNy9PYSuKKz4869P2hbxQJccVAKAbfU5xz7VYDBQDcubAPNZ73ADBoxvnMB4PXXYF"),
+            vec![(PaymentParamsKind::Xpub, ParseFailureReason::InvalidChecksum)]
+        );
+        assert_eq!(
+            reasons("abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon"),
+            vec![(
+                PaymentParamsKind::Mnemonic,
+                ParseFailureReason::InvalidChecksum
+            )]
+        );
+    }
+
+    #[test]
+    fn test_diagnose_lengths() {
+        assert_eq!(
+            reasons("abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about"),
+            vec![(
+                PaymentParamsKind::Mnemonic,
+                ParseFailureReason::InvalidLength(13)
+            )]
+        );
+        assert_eq!(
+            reasons(&"ab".repeat(31)),
+            vec![(
+                PaymentParamsKind::Txid,
+                ParseFailureReason::InvalidLength(62)
+            )]
+        );
+        // a v0 witness program must be 20 or 32 bytes
+        let address = segwit_address("bc", 25);
+        assert_eq!(
+            reasons(&address),
+            vec![(
+                PaymentParamsKind::OnChain,
+                ParseFailureReason::InvalidLength(25)
+            )]
+        );
+    }
+
+    #[test]
+    fn test_diagnose_network() {
+        // a valid invoice for a currency we don't know
+        let (_, data, _) = bech32::decode(INVOICE).unwrap();
+        let invoice = bech32::encode("lnxy20m", data, bech32::Variant::Bech32).unwrap();
+        assert_eq!(
+            reasons(&invoice),
+            vec![(
+                PaymentParamsKind::Bolt11,
+                ParseFailureReason::WrongHrp("lnxy20m".to_string())
+            )]
+        );
+
+        // a base58 address with an unknown version byte
+        let mut data = vec![0x06];
+        data.extend_from_slice(&[1; 20]);
+        let address = base58::encode_check(&data);
+        assert!(address.starts_with('3'));
+        assert_eq!(
+            reasons(&address),
+            vec![(PaymentParamsKind::OnChain, ParseFailureReason::BadNetwork)]
+        );
+    }
+
+    #[test]
+    fn test_suggestions() {
+        let suggestion = |str: &str| diagnose(str).suggestion(str);
+
+        let address = "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq";
+        // a single wrong character
+        let err = diagnose("bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdd");
+        assert_eq!(
+            err.suggestion("bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdd")
+                .as_deref(),
+            Some(address)
+        );
+        assert_eq!(
+            err.to_string(),
+            "invalid input (on_chain: invalid checksum)"
+        );
+        // misread characters that aren't part of bech32
+        assert_eq!(
+            suggestion("bc1qarosrrr7xfkvy5l643lydnw9re59gtzzwf5mdq").as_deref(),
+            Some(address)
+        );
+        // two commonly confused characters
+        assert_eq!(
+            suggestion("bc1gar0srrr7xfkvy5l643lydnw9re59gtz2wf5mdq").as_deref(),
+            Some(address)
+        );
+        assert_eq!(
+            suggestion(&address.to_uppercase().replace('0', "O")),
+            Some(address.to_uppercase())
+        );
+        // only the address of a URI is corrected
+        assert_eq!(
+            suggestion("bitcoin:bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdd?amount=1").as_deref(),
+            Some(address)
+        );
+
+        let address = "1andreas3batLhQa2FawWjeyjCqyBzypd";
+        assert_eq!(
+            suggestion("landreas3batLhQa2FawWjeyjCqyBzypd").as_deref(),
+            Some(address)
+        );
+        assert_eq!(
+            suggestion("1andreas3batLhQa2FawWjeyjCqyBzypc").as_deref(),
+            Some(address)
+        );
+
+        // secrets are never suggested
+        let wif = "KwDiBf89QgGbjEhKnhXJuH7LrciVrZi3qYjgd9M7rFU73sVHnoWm";
+        let err = diagnose(wif);
+        assert_eq!(
+            err.attempts()[0].reason,
+            ParseFailureReason::InvalidChecksum
+        );
+        assert_eq!(err.suggestion(wif), None);
+
+        // too far off to guess
+        assert_eq!(
+            suggestion("bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwfqqqq"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_suggestions_are_bounded() {
+        // an invoice with a typo is still within the budget
+        let mut invoice = INVOICE.to_string();
+        invoice.replace_range(100..101, "x");
+        let err = diagnose(&invoice);
+        assert_eq!(
+            err.attempts()[0].reason,
+            ParseFailureReason::InvalidChecksum
+        );
+        assert_eq!(err.suggestion(&invoice).as_deref(), Some(INVOICE));
+
+        // the search gives up once the budget runs out
+        let (prefix, data) = "bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdd".split_at(3);
+        let is_valid = |c: &str| bech32::decode(c).is_ok();
+        let mut budget = 10;
+        assert_eq!(
+            correct(prefix, data, BECH32_CHARSET, &mut budget, is_valid),
+            None
+        );
+        assert_eq!(budget, 0);
+        let mut budget = MAX_CANDIDATES;
+        assert!(correct(prefix, data, BECH32_CHARSET, &mut budget, is_valid).is_some());
+    }
+
+    #[test]
+    fn test_diagnose_schemes() {
+        assert_eq!(
+            reasons("lightning:invalid"),
+            vec![(PaymentParamsKind::Bolt11, ParseFailureReason::Invalid)]
+        );
+        // the address is fine but the amount isn't
+        assert_eq!(
+            reasons("bitcoin:1andreas3batLhQa2FawWjeyjCqyBzypd?amount=abc"),
+            vec![(PaymentParamsKind::Bip21, ParseFailureReason::Invalid)]
+        );
+        assert_eq!(
+            reasons("bitcoin:1andreas3batLhQa2FawWjeyjCqyBzypc?amount=1"),
+            vec![(
+                PaymentParamsKind::OnChain,
+                ParseFailureReason::InvalidChecksum
+            )]
+        );
+
+        let err = diagnose("lightning:invalid");
+        assert_eq!(err.to_string(), "invalid input (bolt11: invalid)");
+    }
+}
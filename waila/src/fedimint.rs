@@ -0,0 +1,467 @@
+use alloc::collections::BTreeMap;
+use core::convert::TryInto;
+use core::fmt::{self, Write};
+use core::str::FromStr;
+
+use base64::Engine;
+use bitcoin::bech32::{self, FromBase32, ToBase32, Variant};
+#[cfg(feature = "resolver")]
+use bitcoin::hashes::{sha256, Hash};
+use nostr::Url;
+
+use crate::prelude::*;
+
+/// Invite codes are bech32m with a `fed1` hrp, so they start with `fed11`
+const INVITE_CODE_HRP: &str = "fed1";
+const FEDERATION_ID_LENGTH: usize = 32;
+const FEDERATION_ID_PREFIX_LENGTH: usize = 4;
+/// A blind signature and the note's spend key
+const NOTE_LENGTH: usize = 48 + 32;
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::new(), |mut hex, b| {
+        let _ = write!(hex, "{b:02x}");
+        hex
+    })
+}
+
+#[cfg(feature = "resolver")]
+fn write_big_size(out: &mut Vec<u8>, n: u64) {
+    match n {
+        0..=0xfc => out.push(n as u8),
+        0xfd..=0xffff => {
+            out.push(0xfd);
+            out.extend_from_slice(&(n as u16).to_be_bytes());
+        }
+        0x1_0000..=0xffff_ffff => {
+            out.push(0xfe);
+            out.extend_from_slice(&(n as u32).to_be_bytes());
+        }
+        _ => {
+            out.push(0xff);
+            out.extend_from_slice(&n.to_be_bytes());
+        }
+    }
+}
+
+#[cfg(feature = "resolver")]
+fn write_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    write_big_size(out, bytes.len() as u64);
+    out.extend_from_slice(bytes);
+}
+
+/// The id of the federation run by the given guardians, each by their peer id
+/// along with their API url and name. Fedimint hashes the guardians in its
+/// consensus encoding, so a federation's config can be checked against the id
+/// of an invite code.
+#[cfg(feature = "resolver")]
+pub(crate) fn federation_id<'a>(
+    guardians: impl ExactSizeIterator<Item = (u16, &'a str, &'a str)>,
+) -> String {
+    let mut data = vec![];
+    write_big_size(&mut data, guardians.len() as u64);
+    for (peer, url, name) in guardians {
+        write_big_size(&mut data, peer.into());
+        write_bytes(&mut data, url.as_bytes());
+        write_bytes(&mut data, name.as_bytes());
+    }
+    to_hex(sha256::Hash::hash(&data).as_ref())
+}
+
+/// Reads fedimint's consensus encoding, which uses lightning's BigSize for
+/// integers and lengths
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.pos == self.data.len()
+    }
+
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        let end = self.pos.checked_add(len)?;
+        let bytes = self.data.get(self.pos..end)?;
+        self.pos = end;
+        Some(bytes)
+    }
+
+    fn take_array<const N: usize>(&mut self) -> Option<[u8; N]> {
+        self.take(N)?.try_into().ok()
+    }
+
+    fn big_size(&mut self) -> Option<u64> {
+        let (value, min) = match *self.take(1)?.first()? {
+            0xfd => (u16::from_be_bytes(self.take_array()?) as u64, 0xfd),
+            0xfe => (u32::from_be_bytes(self.take_array()?) as u64, 0x1_0000),
+            0xff => (u64::from_be_bytes(self.take_array()?), 0x1_0000_0000),
+            n => return Some(n as u64),
+        };
+        // non-canonical encodings are rejected like lightning does
+        (value >= min).then_some(value)
+    }
+
+    fn bytes(&mut self) -> Option<&'a [u8]> {
+        let len = self.big_size()?;
+        self.take(len.try_into().ok()?)
+    }
+
+    /// Enum variants are encoded as their index and then their fields as bytes,
+    /// so unknown variants can be skipped
+    fn variant(&mut self) -> Option<(u64, Reader<'a>)> {
+        let variant = self.big_size()?;
+        Some((variant, Reader::new(self.bytes()?)))
+    }
+
+    /// Each item is at least a byte, so a bigger length can't be valid
+    fn len(&mut self) -> Option<usize> {
+        let len = self.big_size()?;
+        if len > (self.data.len() - self.pos) as u64 {
+            return None;
+        }
+        len.try_into().ok()
+    }
+
+    fn guardian(&mut self) -> Option<(u16, Url)> {
+        let url = Url::parse(core::str::from_utf8(self.bytes()?).ok()?).ok()?;
+        let peer = self.big_size()?.try_into().ok()?;
+        Some((peer, url))
+    }
+}
+
+/// A Fedimint invite code, decoded without depending on fedimint itself.
+/// Enable the `fedimint` feature to convert it into a `fedimint_core` invite code.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FedimintInviteCode {
+    federation_id: [u8; FEDERATION_ID_LENGTH],
+    guardians: Vec<(u16, Url)>,
+    /// Kept to re-encode fields we don't know about
+    data: Vec<u8>,
+}
+
+impl FedimintInviteCode {
+    /// The id of the federation in hex
+    pub fn federation_id(&self) -> String {
+        to_hex(&self.federation_id)
+    }
+
+    /// The guardians by their peer id, along with their API url
+    pub fn guardians(&self) -> &[(u16, Url)] {
+        &self.guardians
+    }
+
+    #[cfg(feature = "fedimint")]
+    pub fn to_invite_code(&self) -> Option<fedimint_core::api::InviteCode> {
+        fedimint_core::api::InviteCode::from_str(&self.to_string()).ok()
+    }
+
+    fn decode(data: Vec<u8>) -> Option<Self> {
+        let mut reader = Reader::new(&data);
+        let mut federation_id = None;
+        let mut guardians = vec![];
+        for _ in 0..reader.len()? {
+            let (variant, mut fields) = reader.variant()?;
+            match variant {
+                0 => guardians.push(fields.guardian()?),
+                1 => federation_id = Some(fields.take_array()?),
+                // added in a newer version, older clients ignore these too
+                _ => continue,
+            }
+            if !fields.is_empty() {
+                return None;
+            }
+        }
+        if !reader.is_empty() || guardians.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            federation_id: federation_id?,
+            guardians,
+            data,
+        })
+    }
+}
+
+impl fmt::Display for FedimintInviteCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        bech32::encode_to_fmt(f, INVITE_CODE_HRP, self.data.to_base32(), Variant::Bech32m)
+            .map_err(|_| fmt::Error)?
+    }
+}
+
+impl FromStr for FedimintInviteCode {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (hrp, data, variant) = bech32::decode(s).map_err(|_| ())?;
+        if hrp != INVITE_CODE_HRP || variant != Variant::Bech32m {
+            return Err(());
+        }
+        let data = Vec::<u8>::from_base32(&data).map_err(|_| ())?;
+        FedimintInviteCode::decode(data).ok_or(())
+    }
+}
+
+/// Fedimint out of band e-cash notes, decoded without depending on fedimint itself.
+/// Enable the `fedimint` feature to convert them into `fedimint_mint_client` notes.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FedimintNotes {
+    federation_id_prefix: [u8; FEDERATION_ID_PREFIX_LENGTH],
+    /// Number of notes for each denomination in msats
+    denominations: BTreeMap<u64, usize>,
+    data: Vec<u8>,
+}
+
+impl FedimintNotes {
+    /// The first 4 bytes of the id of the federation that issued the notes, in hex
+    pub fn federation_id_prefix(&self) -> String {
+        to_hex(&self.federation_id_prefix)
+    }
+
+    /// The number of notes of each denomination in millisatoshis, smallest first
+    pub fn denominations(&self) -> Vec<(u64, usize)> {
+        self.denominations.iter().map(|(a, n)| (*a, *n)).collect()
+    }
+
+    pub fn note_count(&self) -> usize {
+        self.denominations.values().sum()
+    }
+
+    pub fn total_amount_msats(&self) -> u64 {
+        self.denominations
+            .iter()
+            .map(|(amount, count)| amount.saturating_mul(*count as u64))
+            .fold(0, u64::saturating_add)
+    }
+
+    #[cfg(feature = "fedimint")]
+    pub fn to_oob_notes(&self) -> Option<fedimint_mint_client::OOBNotes> {
+        fedimint_mint_client::OOBNotes::from_str(&self.to_string()).ok()
+    }
+
+    fn decode_notes(fields: &mut Reader) -> Option<BTreeMap<u64, usize>> {
+        let mut denominations = BTreeMap::new();
+        for _ in 0..fields.len()? {
+            let amount = fields.big_size()?;
+            let count = fields.len()?;
+            fields.take(count.checked_mul(NOTE_LENGTH)?)?;
+            if count > 0 {
+                denominations.insert(amount, count);
+            }
+        }
+        Some(denominations)
+    }
+
+    fn decode(data: Vec<u8>) -> Option<Self> {
+        let mut reader = Reader::new(&data);
+        let mut prefix: Option<[u8; FEDERATION_ID_PREFIX_LENGTH]> = None;
+        let mut invite_prefix = None;
+        let mut denominations = None;
+        for _ in 0..reader.len()? {
+            let (variant, mut fields) = reader.variant()?;
+            match variant {
+                0 => denominations = Some(FedimintNotes::decode_notes(&mut fields)?),
+                1 => prefix = Some(fields.take_array()?),
+                2 => {
+                    // unlike in invite codes, the peer id comes before the url
+                    let guardians = (0..fields.len()?)
+                        .map(|_| {
+                            let peer = fields.big_size()?;
+                            fields.bytes()?;
+                            Some(peer)
+                        })
+                        .collect::<Option<Vec<_>>>()?;
+                    let federation_id: [u8; FEDERATION_ID_LENGTH] = fields.take_array()?;
+                    if guardians.is_empty() {
+                        return None;
+                    }
+                    invite_prefix = federation_id[..FEDERATION_ID_PREFIX_LENGTH].try_into().ok();
+                }
+                _ => continue,
+            }
+            if !fields.is_empty() {
+                return None;
+            }
+        }
+        if !reader.is_empty() {
+            return None;
+        }
+
+        let federation_id_prefix = match (prefix, invite_prefix) {
+            (Some(a), Some(b)) if a != b => return None,
+            (Some(prefix), _) | (None, Some(prefix)) => prefix,
+            (None, None) => return None,
+        };
+        let denominations = denominations.filter(|d| !d.is_empty())?;
+
+        Some(Self {
+            federation_id_prefix,
+            denominations,
+            data,
+        })
+    }
+}
+
+impl fmt::Display for FedimintNotes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&base64::engine::general_purpose::STANDARD.encode(&self.data))
+    }
+}
+
+impl FromStr for FedimintNotes {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let data = base64::engine::general_purpose::URL_SAFE
+            .decode(s)
+            .or_else(|_| base64::engine::general_purpose::STANDARD.decode(s))
+            .map_err(|_| ())?;
+        FedimintNotes::decode(data).ok_or(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::str::FromStr;
+
+    use super::*;
+
+    const INVITE_CODE: &str = "fed11qgqzc2nhwden5te0vejkg6tdd9h8gepwvejkg6tdd9h8garhduhx6at5d9h8jmn9wshxxmmd9uqqzgxg6s3evnr6m9zdxr6hxkdkukexpcs3mn7mj3g5pc5dfh63l4tj6g9zk4er";
+
+    #[test]
+    fn test_big_size() {
+        assert_eq!(Reader::new(&[0xfc]).big_size(), Some(0xfc));
+        assert_eq!(Reader::new(&[0xfd, 0x01, 0x00]).big_size(), Some(0x100));
+        assert_eq!(
+            Reader::new(&[0xfe, 0x00, 0x01, 0x00, 0x00]).big_size(),
+            Some(0x1_0000)
+        );
+        // not the shortest encoding
+        assert_eq!(Reader::new(&[0xfd, 0x00, 0xfc]).big_size(), None);
+        assert_eq!(Reader::new(&[0xfd, 0x01]).big_size(), None);
+    }
+
+    #[test]
+    fn test_parse_invite_code() {
+        let code = FedimintInviteCode::from_str(INVITE_CODE).unwrap();
+        assert_eq!(
+            code.federation_id(),
+            "c8d423964c7ad944d30f57359b6e5b260e211dcfdb945140e28d4df51fd572d2"
+        );
+        assert_eq!(
+            code.guardians(),
+            [(
+                0,
+                Url::parse("wss://fedimintd.fediminttwo.mutinynet.com").unwrap()
+            )]
+        );
+        assert_eq!(code.to_string(), INVITE_CODE);
+    }
+
+    #[cfg(feature = "fedimint")]
+    #[test]
+    fn test_to_invite_code() {
+        let code = FedimintInviteCode::from_str(INVITE_CODE).unwrap();
+        let invite = code.to_invite_code().unwrap();
+        assert_eq!(invite.federation_id().to_string(), code.federation_id());
+        assert_eq!(invite.to_string(), INVITE_CODE);
+    }
+
+    #[cfg(feature = "resolver")]
+    #[test]
+    fn test_federation_id() {
+        let guardians = [
+            (0, "wss://fedimintd.mplsfed.foo/", "Hal"),
+            (1, "wss://fedimintd.mplsfed.xyz/", "Satoshi"),
+        ];
+        assert_eq!(
+            federation_id(guardians.iter().copied()),
+            "eaddc06945eecaacb75e13f4c38e9bbe3ef12def9fc152633939bb9ab6881992"
+        );
+    }
+
+    #[cfg(all(feature = "resolver", feature = "fedimint"))]
+    #[test]
+    fn test_federation_id_matches_fedimint() {
+        let json = r#"{"api_endpoints":{"0":{"url":"wss://fedimintd.mplsfed.foo/","name":"Hal"},"1":{"url":"wss://fedimintd.mplsfed.xyz/","name":"Satoshi"}},"consensus_version":{"major":0,"minor":0},"meta":{}}"#;
+        let config: fedimint_core::config::GlobalClientConfig = serde_json::from_str(json).unwrap();
+        let guardians = config
+            .api_endpoints
+            .iter()
+            .map(|(peer, url)| (peer.to_usize() as u16, url.url.as_str(), url.name.as_str()));
+        assert_eq!(
+            federation_id(guardians),
+            config.calculate_federation_id().to_string()
+        );
+    }
+
+    #[test]
+    fn test_invite_code_unknown_fields() {
+        let code = FedimintInviteCode::from_str(INVITE_CODE).unwrap();
+
+        let mut data = code.data.clone();
+        data[0] += 1;
+        data.extend_from_slice(&[0x07, 0x02, 0xab, 0xcd]);
+        let encoded = bech32::encode(INVITE_CODE_HRP, data.to_base32(), Variant::Bech32m).unwrap();
+
+        let parsed = FedimintInviteCode::from_str(&encoded).unwrap();
+        assert_eq!(parsed.federation_id(), code.federation_id());
+        assert_eq!(parsed.to_string(), encoded);
+    }
+
+    #[test]
+    fn test_invalid_invite_code() {
+        let code = FedimintInviteCode::from_str(INVITE_CODE).unwrap();
+
+        // trailing data
+        let mut data = code.data.clone();
+        data.push(0);
+        let encoded = bech32::encode(INVITE_CODE_HRP, data.to_base32(), Variant::Bech32m).unwrap();
+        assert!(FedimintInviteCode::from_str(&encoded).is_err());
+
+        // wrong hrp
+        let encoded = bech32::encode("fed2", code.data.to_base32(), Variant::Bech32m).unwrap();
+        assert!(FedimintInviteCode::from_str(&encoded).is_err());
+
+        // bech32 instead of bech32m
+        let encoded =
+            bech32::encode(INVITE_CODE_HRP, code.data.to_base32(), Variant::Bech32).unwrap();
+        assert!(FedimintInviteCode::from_str(&encoded).is_err());
+    }
+
+    #[test]
+    fn test_parse_notes() {
+        let mut data = vec![0x02];
+        // federation id prefix
+        data.extend_from_slice(&[0x01, 0x04, 0xc8, 0xd4, 0x23, 0x96]);
+        // 3 notes of 1 sat and 1 of 4 sats
+        let mut notes = vec![0x02, 0xfd, 0x03, 0xe8, 0x03];
+        notes.extend_from_slice(&[1; NOTE_LENGTH * 3]);
+        notes.extend_from_slice(&[0xfd, 0x0f, 0xa0, 0x01]);
+        notes.extend_from_slice(&[2; NOTE_LENGTH]);
+        data.extend_from_slice(&[0x00, 0xfd]);
+        data.extend_from_slice(&(notes.len() as u16).to_be_bytes());
+        data.extend_from_slice(&notes);
+
+        let encoded = base64::engine::general_purpose::STANDARD.encode(&data);
+        let parsed = FedimintNotes::from_str(&encoded).unwrap();
+        assert_eq!(parsed.federation_id_prefix(), "c8d42396");
+        assert_eq!(parsed.denominations(), vec![(1_000, 3), (4_000, 1)]);
+        assert_eq!(parsed.note_count(), 4);
+        assert_eq!(parsed.total_amount_msats(), 7_000);
+        assert_eq!(parsed.to_string(), encoded);
+
+        let url_safe = base64::engine::general_purpose::URL_SAFE.encode(&data);
+        assert_eq!(FedimintNotes::from_str(&url_safe).unwrap(), parsed);
+
+        // a note cut short
+        let encoded = base64::engine::general_purpose::STANDARD.encode(&data[..data.len() - 1]);
+        assert!(FedimintNotes::from_str(&encoded).is_err());
+    }
+}
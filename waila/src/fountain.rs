@@ -0,0 +1,437 @@
+use alloc::collections::{BTreeMap, BTreeSet};
+use core::convert::TryInto;
+
+use bitcoin::hashes::{sha256, Hash};
+
+use crate::prelude::*;
+use crate::ur::{self, Cbor};
+use crate::PaymentParams;
+
+/// Xoshiro256**, the PRNG the UR fountain encoder uses to pick which
+/// fragments are mixed into each part.
+struct Xoshiro256 {
+    s: [u64; 4],
+}
+
+impl Xoshiro256 {
+    fn new(seed: [u8; 32]) -> Self {
+        let mut s = [0u64; 4];
+        for (i, chunk) in seed.chunks(8).enumerate() {
+            s[i] = u64::from_be_bytes(chunk.try_into().unwrap());
+        }
+        Self { s }
+    }
+
+    fn next(&mut self) -> u64 {
+        let result = self.s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = self.s[1] << 17;
+
+        self.s[2] ^= self.s[0];
+        self.s[3] ^= self.s[1];
+        self.s[1] ^= self.s[2];
+        self.s[0] ^= self.s[3];
+        self.s[2] ^= t;
+        self.s[3] = self.s[3].rotate_left(45);
+
+        result
+    }
+
+    fn next_double(&mut self) -> f64 {
+        self.next() as f64 / (u64::MAX as f64 + 1.0)
+    }
+
+    fn next_int(&mut self, low: usize, high: usize) -> usize {
+        (self.next_double() * (high - low + 1) as f64) as usize + low
+    }
+}
+
+/// Picks the degree of a part with Vose's alias method, weighted by `1/degree`
+fn choose_degree(seq_len: usize, rng: &mut Xoshiro256) -> usize {
+    let weights: Vec<f64> = (1..=seq_len).map(|i| 1.0 / i as f64).collect();
+    let total: f64 = weights.iter().sum();
+    let mut scaled: Vec<f64> = weights.iter().map(|w| w * seq_len as f64 / total).collect();
+
+    let (mut small, mut large): (Vec<usize>, Vec<usize>) =
+        (0..seq_len).rev().partition(|i| scaled[*i] < 1.0);
+    let mut probs = vec![0.0; seq_len];
+    let mut aliases = vec![0; seq_len];
+    while !small.is_empty() && !large.is_empty() {
+        let a = small.pop().unwrap();
+        let g = large.pop().unwrap();
+        probs[a] = scaled[a];
+        aliases[a] = g;
+        scaled[g] += scaled[a] - 1.0;
+        if scaled[g] < 1.0 {
+            small.push(g);
+        } else {
+            large.push(g);
+        }
+    }
+    for i in large.into_iter().chain(small) {
+        probs[i] = 1.0;
+    }
+
+    let r1 = rng.next_double();
+    let r2 = rng.next_double();
+    let i = (seq_len as f64 * r1) as usize;
+    let index = if r2 < probs[i] { i } else { aliases[i] };
+    index + 1
+}
+
+/// The indexes of the fragments XORed together in the given part
+fn choose_fragments(seq_num: u32, seq_len: usize, checksum: u32) -> BTreeSet<usize> {
+    if seq_num as usize <= seq_len {
+        return BTreeSet::from([seq_num as usize - 1]);
+    }
+
+    let mut seed = seq_num.to_be_bytes().to_vec();
+    seed.extend_from_slice(&checksum.to_be_bytes());
+    let mut rng = Xoshiro256::new(sha256::Hash::hash(&seed).to_byte_array());
+
+    let degree = choose_degree(seq_len, &mut rng);
+    let mut remaining: Vec<usize> = (0..seq_len).collect();
+    let mut shuffled = Vec::with_capacity(seq_len);
+    while !remaining.is_empty() {
+        let index = rng.next_int(0, remaining.len() - 1);
+        shuffled.push(remaining.remove(index));
+    }
+
+    shuffled.into_iter().take(degree).collect()
+}
+
+fn xor_into(target: &mut [u8], other: &[u8]) {
+    for (a, b) in target.iter_mut().zip(other) {
+        *a ^= b;
+    }
+}
+
+/// Animated QRs never have anywhere near this many fragments, this just
+/// stops a malicious part from making us allocate gigabytes
+const MAX_SEQ_LEN: usize = 10_000;
+
+/// Same for the length of the reassembled message, this fits even large PSBTs
+const MAX_MESSAGE_LEN: usize = 4_000_000;
+
+/// Whether a part's lengths are sane and agree with each other
+fn valid_lengths(seq_len: usize, message_len: usize, fragment_len: usize) -> bool {
+    fragment_len != 0
+        && message_len != 0
+        && message_len <= MAX_MESSAGE_LEN
+        && seq_len <= MAX_SEQ_LEN
+        && message_len.div_ceil(fragment_len) == seq_len
+}
+
+/// The parameters every part of a multi-part UR has to agree on
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct UrHeader {
+    ur_type: String,
+    seq_len: usize,
+    message_len: usize,
+    checksum: u32,
+    fragment_len: usize,
+}
+
+struct UrPart {
+    header: UrHeader,
+    seq_num: u32,
+    fragment: Vec<u8>,
+}
+
+fn parse_part(str: &str) -> Option<UrPart> {
+    let lower = str.trim().to_lowercase();
+    let mut split = lower.strip_prefix("ur:")?.split('/');
+    let (ur_type, sequence, body) = (split.next()?, split.next()?, split.next()?);
+    if split.next().is_some() {
+        return None;
+    }
+
+    let cbor = Cbor::decode(&ur::decode_bytewords(body)?)?;
+    let items = match cbor {
+        Cbor::Array(items) if items.len() == 5 => items,
+        _ => return None,
+    };
+
+    let seq_num: u32 = items[0].as_uint()?.try_into().ok()?;
+    let seq_len: usize = items[1].as_uint()?.try_into().ok()?;
+    let message_len: usize = items[2].as_uint()?.try_into().ok()?;
+    let checksum: u32 = items[3].as_uint()?.try_into().ok()?;
+    let fragment = items[4].as_bytes()?.to_vec();
+
+    // the sequence in the path is informational, but it should match the payload
+    if sequence != format!("{seq_num}-{seq_len}") {
+        return None;
+    }
+    if seq_num == 0 || !valid_lengths(seq_len, message_len, fragment.len()) {
+        return None;
+    }
+
+    Some(UrPart {
+        header: UrHeader {
+            ur_type: ur_type.to_string(),
+            seq_len,
+            message_len,
+            checksum,
+            fragment_len: fragment.len(),
+        },
+        seq_num,
+        fragment,
+    })
+}
+
+/// Reassembles a multi-part UR from the frames of an animated QR code.
+///
+/// Parts can be received in any order and duplicates are ignored. Once enough
+/// parts have been seen, [`UrDecoder::result`] returns the decoded payload.
+#[derive(Debug, Clone, Default)]
+pub struct UrDecoder {
+    header: Option<UrHeader>,
+    simple: BTreeMap<usize, Vec<u8>>,
+    mixed: Vec<(BTreeSet<usize>, Vec<u8>)>,
+    seen: BTreeSet<u32>,
+    message: Option<Vec<u8>>,
+    result: Option<PaymentParams<'static>>,
+}
+
+impl UrDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feeds a scanned part, returns false if it wasn't a valid part of this UR.
+    /// Single-part URs complete the decoder immediately.
+    pub fn receive(&mut self, part: &str) -> bool {
+        if self.is_complete() {
+            return false;
+        }
+
+        if self.header.is_none() {
+            if let Some(params) = ur::parse_ur(part) {
+                self.result = Some(params);
+                return true;
+            }
+        }
+
+        let part = match parse_part(part) {
+            Some(part) => part,
+            None => return false,
+        };
+        match &self.header {
+            Some(header) if *header != part.header => return false,
+            Some(_) => {}
+            None => self.header = Some(part.header.clone()),
+        }
+
+        if !self.seen.insert(part.seq_num) {
+            return true;
+        }
+
+        let indexes = choose_fragments(part.seq_num, part.header.seq_len, part.header.checksum);
+        self.process(indexes, part.fragment);
+        if self.simple.len() == part.header.seq_len {
+            self.finish();
+        }
+
+        true
+    }
+
+    fn process(&mut self, indexes: BTreeSet<usize>, fragment: Vec<u8>) {
+        let mut queue = vec![(indexes, fragment)];
+        while let Some((mut indexes, mut fragment)) = queue.pop() {
+            // remove every fragment we already know
+            for (index, known) in &self.simple {
+                if indexes.remove(index) {
+                    xor_into(&mut fragment, known);
+                }
+            }
+
+            match indexes.len() {
+                0 => {}
+                1 => {
+                    let index = *indexes.iter().next().unwrap();
+                    self.simple.insert(index, fragment);
+                    // anything mixed with this fragment can now be reduced
+                    let (reducible, rest) = self
+                        .mixed
+                        .drain(..)
+                        .partition(|(mixed, _)| mixed.contains(&index));
+                    self.mixed = rest;
+                    queue.extend(reducible);
+                }
+                _ => {
+                    if !self.mixed.iter().any(|(mixed, _)| *mixed == indexes) {
+                        self.mixed.push((indexes, fragment));
+                    }
+                }
+            }
+        }
+    }
+
+    fn finish(&mut self) {
+        let header = self.header.as_ref().unwrap();
+        let mut message: Vec<u8> = self.simple.values().flatten().copied().collect();
+        message.truncate(header.message_len);
+
+        if ur::crc32(&message) != header.checksum {
+            // a corrupt part slipped through, start over
+            *self = Self::default();
+            return;
+        }
+
+        self.result = ur::decode_payload(&header.ur_type, &message);
+        self.message = Some(message);
+    }
+
+    /// The UR type, e.g. `crypto-psbt`, once the first part is received
+    pub fn ur_type(&self) -> Option<&str> {
+        self.header.as_ref().map(|h| h.ur_type.as_str())
+    }
+
+    /// How many fragments are needed to complete the UR
+    pub fn expected_parts(&self) -> Option<usize> {
+        self.header.as_ref().map(|h| h.seq_len)
+    }
+
+    /// The fraction of fragments recovered so far, between 0 and 1
+    pub fn progress(&self) -> f64 {
+        if self.is_complete() {
+            return 1.0;
+        }
+        match self.expected_parts() {
+            Some(expected) => self.simple.len() as f64 / expected as f64,
+            None => 0.0,
+        }
+    }
+
+    /// Whether all the fragments have been received, even if the payload
+    /// turned out to be a type we don't support
+    pub fn is_complete(&self) -> bool {
+        self.message.is_some() || self.result.is_some()
+    }
+
+    /// The decoded payload, once complete
+    pub fn result(&self) -> Option<PaymentParams<'static>> {
+        self.result.clone()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const PARTS: [&str; 9] = [
+        "ur:bytes/1-9/lpadascfadaxcywenbpljkhdcahkadaemejtswhhylkepmykhhtsytsnoyoyaxaedsuttydmmhhpktpmsrjtdkgslpgh",
+        "ur:bytes/2-9/lpaoascfadaxcywenbpljkhdcagwdpfnsboxgwlbaawzuefywkdplrsrjynbvygabwjldapfcsgmghhkhstlrdcxaefz",
+        "ur:bytes/3-9/lpaxascfadaxcywenbpljkhdcahelbknlkuejnbadmssfhfrdpsbiegecpasvssovlgeykssjykklronvsjksopdzmol",
+        "ur:bytes/4-9/lpaaascfadaxcywenbpljkhdcasotkhemthydawydtaxneurlkosgwcekonertkbrlwmplssjtammdplolsbrdzcrtas",
+        "ur:bytes/5-9/lpahascfadaxcywenbpljkhdcatbbdfmssrkzmcwnezelennjpfzbgmuktrhtejscktelgfpdlrkfyfwdajldejokbwf",
+        "ur:bytes/6-9/lpamascfadaxcywenbpljkhdcackjlhkhybssklbwefectpfnbbectrljectpavyrolkzczcpkmwidmwoxkilghdsowp",
+        "ur:bytes/7-9/lpatascfadaxcywenbpljkhdcavszmwnjkwtclrtvaynhpahrtoxmwvwatmedibkaegdosftvandiodagdhthtrlnnhy",
+        "ur:bytes/8-9/lpayascfadaxcywenbpljkhdcadmsponkkbbhgsoltjntegepmttmoonftnbuoiyrehfrtsabzsttorodklubbuyaetk",
+        "ur:bytes/9-9/lpasascfadaxcywenbpljkhdcajskecpmdckihdyhphfotjojtfmlnwmadspaxrkytbztpbauotbgtgtaeaevtgavtny",
+    ];
+
+    #[test]
+    fn test_xoshiro() {
+        let mut rng = Xoshiro256::new(sha256::Hash::hash(b"Wolf").to_byte_array());
+        let numbers: Vec<u64> = (0..10).map(|_| rng.next() % 100).collect();
+        assert_eq!(numbers, vec![42, 81, 85, 8, 82, 84, 76, 73, 70, 88]);
+    }
+
+    #[test]
+    fn test_choose_fragments() {
+        assert_eq!(choose_fragments(3, 9, 0xeda0ae73), BTreeSet::from([2]));
+        assert_eq!(choose_fragments(10, 9, 0xeda0ae73), BTreeSet::from([0]));
+        assert_eq!(choose_fragments(12, 9, 0xeda0ae73), BTreeSet::from([2, 6]));
+        assert_eq!(
+            choose_fragments(13, 9, 0xeda0ae73),
+            BTreeSet::from([3, 7, 8])
+        );
+    }
+
+    #[test]
+    fn test_decode_simple_parts() {
+        let mut decoder = UrDecoder::new();
+        assert_eq!(decoder.progress(), 0.0);
+
+        for (i, part) in PARTS.iter().enumerate().rev() {
+            assert!(!decoder.is_complete());
+            assert!(decoder.receive(part));
+            if i == 5 {
+                // duplicates are fine
+                assert!(decoder.receive(part));
+                assert_eq!(decoder.ur_type(), Some("bytes"));
+                assert_eq!(decoder.expected_parts(), Some(9));
+                assert_eq!(decoder.progress(), 4.0 / 9.0);
+            }
+        }
+
+        assert!(decoder.is_complete());
+        assert_eq!(decoder.progress(), 1.0);
+        // 256 random bytes aren't anything we recognize
+        assert!(decoder.result().is_none());
+        assert_eq!(decoder.message.as_ref().unwrap().len(), 259);
+    }
+
+    #[test]
+    fn test_decode_mixed_parts() {
+        let mut decoder = UrDecoder::new();
+        // fragments 2 and 6 mixed together, then 3, 7 and 8
+        assert!(decoder.receive("ur:bytes/12-9/lpbnascfadaxcywenbpljkhdcarllaluzmdmgstospeyiefmwejlwtpedamktksrvlcygmzemovovllarodtmtbnptrs"));
+        assert!(decoder.receive("UR:BYTES/13-9/LPBTASCFADAXCYWENBPLJKHDCAMTKGTPKNGHCHCHYKETWSVWGWFDHPGMGTYLCTOTZOPDRPAYOSCHCMHPLFFZIACHRFGD"));
+        assert_eq!(decoder.progress(), 0.0);
+
+        // skip fragments 2, 7 and 8
+        for part in [0, 1, 3, 4, 5, 6] {
+            assert!(decoder.receive(PARTS[part]));
+        }
+        assert!(!decoder.is_complete());
+
+        assert!(decoder.receive(PARTS[7]));
+        assert!(decoder.is_complete());
+        assert_eq!(decoder.message.as_ref().unwrap().len(), 259);
+    }
+
+    #[test]
+    fn test_decode_psbt() {
+        let mut decoder = UrDecoder::new();
+        assert!(decoder.receive("UR:CRYPTO-PSBT/2-3/LPAOAXCSVTCYDKHHUEASHDGRTYGHMWCEFETTQDOTCNWNFXFRTBPDHSAEAEAEAEAEAECMAEBBKPCKKOVSCFMEMTTYGHMWCEFETTQDOTCNWNFXFRTBAEAEAEAEGWADAAECLTTKAXRHIAFWLGLAAEAEAEKGZMCATBWSSASEETNYRNADRECLTDMWET"));
+        assert!(decoder.receive("UR:CRYPTO-PSBT/1-3/LPADAXCSVTCYDKHHUEASHDGRHDUEJOJKIDJYZMADAEJSAOAEAEAEADDSLYJSEMCKURWZLPWLEMPMWYOXQDKGKSAEBNAHIYSBQDPMIEIECHBWSGFWCHCWYNAEAEAEAEAEZCZMZMZMAOGDSRAEAEAEAEAEAECMAEBBKPCKKOVSCFMEMTMEFELNEN"));
+        assert!(decoder.result().is_none());
+        assert!(decoder.receive("UR:CRYPTO-PSBT/3-3/LPAXAXCSVTCYDKHHUEASHDGRGRZMNTGMFSDPSNOLLRTERTPRHYSEPEIAGMNYFGMHAOKBQZBATPFTRLAYYNURHDTBFGKGVSZOMOWMFPSEZTIYGMNYFHCAJZDLMOWYIEMHZOBEVOLNKGRPGHAEAELAADAEAELAAEAEAELAAEAEAEAEAEAXSBFNVD"));
+
+        let psbt = decoder.result().unwrap().psbt().unwrap();
+        assert_eq!(psbt.unsigned_tx.output.len(), 2);
+        // no more parts are accepted once complete
+        assert!(!decoder.receive(PARTS[0]));
+    }
+
+    #[test]
+    fn test_decode_single_part() {
+        let mut decoder = UrDecoder::new();
+        assert!(decoder.receive("ur:bytes/hddridiaehjshsjpdyjkjpjpjpemksiyjekokkecjzeneeeojzkkiejtktesjpihecesiojyknknktiyecjniejsfwuyldrk"));
+        assert!(decoder.is_complete());
+        assert!(decoder.result().unwrap().address().is_some());
+    }
+
+    #[test]
+    fn test_valid_lengths() {
+        assert!(valid_lengths(9, 200, 23));
+        assert!(!valid_lengths(8, 200, 23));
+        assert!(!valid_lengths(1, 0, 23));
+        assert!(!valid_lengths(1, 200, 0));
+        // would overflow when rounding up
+        assert!(!valid_lengths(1, usize::MAX, 23));
+        assert!(!valid_lengths(2, MAX_MESSAGE_LEN + 1, MAX_MESSAGE_LEN));
+    }
+
+    #[test]
+    fn test_decode_rejects_other_parts() {
+        let mut decoder = UrDecoder::new();
+        assert!(decoder.receive(PARTS[0]));
+        assert!(!decoder.receive("not a ur"));
+        assert!(!decoder.receive(&PARTS[1].replace("ur:bytes", "ur:crypto-psbt")));
+        // the path says a different part than the payload
+        assert!(!decoder.receive(&PARTS[1].replace("2-9", "3-9")));
+        assert_eq!(decoder.progress(), 1.0 / 9.0);
+    }
+}
@@ -0,0 +1,156 @@
+use serde::de::DeserializeOwned;
+
+use crate::resolver::{ResolveError, ResolverConfig};
+
+/// A response to a request made through an [`HttpClient`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub body: Vec<u8>,
+}
+
+impl HttpResponse {
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    /// Turns responses with a non 2xx status into [`ResolveError::Status`]
+    pub(crate) fn error_for_status(self) -> Result<Self, ResolveError> {
+        if self.is_success() {
+            Ok(self)
+        } else {
+            Err(ResolveError::Status(self.status))
+        }
+    }
+
+    pub(crate) fn json<T: DeserializeOwned>(&self) -> Result<T, ResolveError> {
+        serde_json::from_slice(&self.body).map_err(ResolveError::Json)
+    }
+}
+
+/// The http client all resolver requests are made with. Implement it to route
+/// requests through your own client, e.g. one that goes over Tor or records metrics.
+///
+/// Timeouts and proxies are up to the implementation, a client that gives up on a
+/// request should return [`ResolveError::Timeout`].
+// the futures aren't required to be `Send` so clients can be used on wasm
+#[allow(async_fn_in_trait)]
+pub trait HttpClient {
+    async fn get(&self, url: &str, headers: &[(&str, &str)]) -> Result<HttpResponse, ResolveError>;
+
+    async fn post(
+        &self,
+        url: &str,
+        headers: &[(&str, &str)],
+        body: Vec<u8>,
+    ) -> Result<HttpResponse, ResolveError>;
+}
+
+/// The default [`HttpClient`], backed by reqwest.
+///
+/// When targeting `wasm32` reqwest makes its requests with the browser's fetch API.
+#[derive(Debug, Clone)]
+pub struct ReqwestClient {
+    client: reqwest::Client,
+}
+
+impl ReqwestClient {
+    /// Builds a client with the configured timeout and proxy
+    pub fn new(config: &ResolverConfig) -> Result<Self, ResolveError> {
+        #[allow(unused_mut)]
+        let mut builder = reqwest::Client::builder();
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            if let Some(timeout) = config.timeout {
+                builder = builder.timeout(timeout);
+            }
+            if let Some(proxy) = config.proxy.as_deref() {
+                builder = builder.proxy(reqwest::Proxy::all(proxy)?);
+            }
+        }
+        #[cfg(target_arch = "wasm32")]
+        let _ = config;
+
+        Ok(Self {
+            client: builder.build()?,
+        })
+    }
+
+    async fn send(
+        &self,
+        request: reqwest::RequestBuilder,
+        headers: &[(&str, &str)],
+    ) -> Result<HttpResponse, ResolveError> {
+        let request = headers.iter().fold(request, |request, (name, value)| {
+            request.header(*name, *value)
+        });
+        let response = request.send().await?;
+        Ok(HttpResponse {
+            status: response.status().as_u16(),
+            body: response.bytes().await?.to_vec(),
+        })
+    }
+}
+
+impl From<reqwest::Client> for ReqwestClient {
+    fn from(client: reqwest::Client) -> Self {
+        Self { client }
+    }
+}
+
+impl HttpClient for ReqwestClient {
+    async fn get(&self, url: &str, headers: &[(&str, &str)]) -> Result<HttpResponse, ResolveError> {
+        self.send(self.client.get(url), headers).await
+    }
+
+    async fn post(
+        &self,
+        url: &str,
+        headers: &[(&str, &str)],
+        body: Vec<u8>,
+    ) -> Result<HttpResponse, ResolveError> {
+        self.send(self.client.post(url).body(body), headers).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_error_for_status() {
+        let response = HttpResponse {
+            status: 204,
+            body: vec![],
+        };
+        assert!(response.error_for_status().is_ok());
+
+        let response = HttpResponse {
+            status: 404,
+            body: vec![],
+        };
+        assert!(matches!(
+            response.error_for_status(),
+            Err(ResolveError::Status(404))
+        ));
+    }
+
+    #[test]
+    fn test_json() {
+        let response = HttpResponse {
+            status: 200,
+            body: br#"{"names": ["satoshi"]}"#.to_vec(),
+        };
+        let json: serde_json::Value = response.json().unwrap();
+        assert_eq!(json["names"][0], "satoshi");
+
+        let response = HttpResponse {
+            status: 200,
+            body: b"not json".to_vec(),
+        };
+        assert!(matches!(
+            response.json::<serde_json::Value>(),
+            Err(ResolveError::Json(_))
+        ));
+    }
+}
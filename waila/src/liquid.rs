@@ -0,0 +1,613 @@
+use alloc::borrow::Cow;
+use core::convert::TryInto;
+use core::fmt;
+use core::str::FromStr;
+
+use bitcoin::base58;
+use bitcoin::bech32::{self, FromBase32, ToBase32, Variant};
+use bitcoin::hashes::hex::FromHex;
+use bitcoin::secp256k1::PublicKey;
+use bitcoin::{Amount, Denomination};
+use nostr::prelude::url::form_urlencoded;
+
+use crate::prelude::*;
+
+/// Liquid URI Scheme
+pub const LIQUID_URI_SCHEME: &str = "liquidnetwork";
+
+/// A network running the Liquid/Elements protocol
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LiquidNetwork {
+    Liquid,
+    LiquidTestnet,
+    ElementsRegtest,
+}
+
+struct AddressParams {
+    network: LiquidNetwork,
+    p2pkh_prefix: u8,
+    p2sh_prefix: u8,
+    blinded_prefix: u8,
+    bech_hrp: &'static str,
+    blech_hrp: &'static str,
+}
+
+const ADDRESS_PARAMS: [AddressParams; 3] = [
+    AddressParams {
+        network: LiquidNetwork::Liquid,
+        p2pkh_prefix: 57,
+        p2sh_prefix: 39,
+        blinded_prefix: 12,
+        bech_hrp: "ex",
+        blech_hrp: "lq",
+    },
+    AddressParams {
+        network: LiquidNetwork::LiquidTestnet,
+        p2pkh_prefix: 36,
+        p2sh_prefix: 19,
+        blinded_prefix: 23,
+        bech_hrp: "tex",
+        blech_hrp: "tlq",
+    },
+    AddressParams {
+        network: LiquidNetwork::ElementsRegtest,
+        p2pkh_prefix: 235,
+        p2sh_prefix: 75,
+        blinded_prefix: 4,
+        bech_hrp: "ert",
+        blech_hrp: "el",
+    },
+];
+
+impl LiquidNetwork {
+    fn params(&self) -> &'static AddressParams {
+        ADDRESS_PARAMS
+            .iter()
+            .find(|p| p.network == *self)
+            .expect("all networks have params")
+    }
+
+    /// The asset id of L-BTC on this network, regtest networks use a random one
+    pub fn policy_asset(&self) -> Option<AssetId> {
+        let hex = match self {
+            LiquidNetwork::Liquid => {
+                "6f0279e9ed041c3d710a9f57d0c02928416460c4b722ae3457a11eec381c526d"
+            }
+            LiquidNetwork::LiquidTestnet => {
+                "144c654344aa716d6f3abcc1ca90e5641e4e2a7f633bc09fe3baf64585819a49"
+            }
+            LiquidNetwork::ElementsRegtest => return None,
+        };
+        AssetId::from_str(hex).ok()
+    }
+}
+
+/// A Liquid asset id, stored in the same byte order it is displayed in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AssetId([u8; 32]);
+
+impl fmt::Display for AssetId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in self.0 {
+            write!(f, "{byte:02x}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for AssetId {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        <[u8; 32]>::from_hex(s).map(AssetId).map_err(|_| ())
+    }
+}
+
+/// What a Liquid address pays to
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum LiquidPayload {
+    PubkeyHash([u8; 20]),
+    ScriptHash([u8; 20]),
+    WitnessProgram { version: u8, program: Vec<u8> },
+}
+
+const BLECH32_CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const BLECH32_GENERATOR: [u64; 5] = [
+    0x7d52fba40bd886,
+    0x5e8dbf1a03950c,
+    0x1c3a3c74072a18,
+    0x385d72fa0e5139,
+    0x7093e5a608865b,
+];
+const BLECH32_CHECKSUM_LENGTH: usize = 12;
+const BLECH32_CONST: u64 = 1;
+const BLECH32M_CONST: u64 = 0x455972a3350f7a1;
+
+fn blech32_polymod(hrp: &str, data: &[u8]) -> u64 {
+    let hrp = hrp.bytes();
+    let values = hrp
+        .clone()
+        .map(|b| b >> 5)
+        .chain(core::iter::once(0))
+        .chain(hrp.map(|b| b & 0x1f))
+        .chain(data.iter().copied());
+
+    let mut chk: u64 = 1;
+    for value in values {
+        let top = chk >> 55;
+        chk = ((chk & 0x7fffffffffffff) << 5) ^ u64::from(value);
+        for (i, gen) in BLECH32_GENERATOR.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                chk ^= gen;
+            }
+        }
+    }
+    chk
+}
+
+/// Blech32 is the bech32 variant used by confidential addresses, it has a longer checksum
+/// to fit the blinding key. Returns the lowercase hrp, the data and if it was blech32m.
+fn blech32_decode(s: &str) -> Result<(String, Vec<u8>, bool), ()> {
+    if s.chars().any(|c| c.is_lowercase()) && s.chars().any(|c| c.is_uppercase()) {
+        return Err(());
+    }
+    let s = s.to_lowercase();
+    let (hrp, data) = s.rsplit_once('1').ok_or(())?;
+    if hrp.is_empty() || data.len() < BLECH32_CHECKSUM_LENGTH {
+        return Err(());
+    }
+
+    let data = data
+        .bytes()
+        .map(|c| {
+            BLECH32_CHARSET
+                .iter()
+                .position(|x| *x == c)
+                .map(|v| v as u8)
+        })
+        .collect::<Option<Vec<u8>>>()
+        .ok_or(())?;
+
+    let is_blech32m = match blech32_polymod(hrp, &data) {
+        BLECH32_CONST => false,
+        BLECH32M_CONST => true,
+        _ => return Err(()),
+    };
+
+    let data = data[..data.len() - BLECH32_CHECKSUM_LENGTH].to_vec();
+    Ok((hrp.to_string(), data, is_blech32m))
+}
+
+fn blech32_encode(hrp: &str, data: &[u8], is_blech32m: bool) -> String {
+    let mut values = data.to_vec();
+    values.extend([0; BLECH32_CHECKSUM_LENGTH]);
+    let constant = if is_blech32m {
+        BLECH32M_CONST
+    } else {
+        BLECH32_CONST
+    };
+    let polymod = blech32_polymod(hrp, &values) ^ constant;
+
+    let checksum = (0..BLECH32_CHECKSUM_LENGTH)
+        .map(|i| ((polymod >> (5 * (BLECH32_CHECKSUM_LENGTH - 1 - i))) & 0x1f) as u8);
+    let encoded = data
+        .iter()
+        .copied()
+        .chain(checksum)
+        .map(|v| BLECH32_CHARSET[v as usize] as char)
+        .collect::<String>();
+    format!("{hrp}1{encoded}")
+}
+
+fn is_valid_witness_program(version: u8, program: &[u8]) -> bool {
+    version <= 16
+        && (2..=40).contains(&program.len())
+        && (version != 0 || program.len() == 20 || program.len() == 32)
+}
+
+/// An address on a Liquid network
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LiquidAddress {
+    pub network: LiquidNetwork,
+    pub payload: LiquidPayload,
+    /// Set for confidential addresses, the key used to blind the output's amount and asset
+    pub blinding_pubkey: Option<PublicKey>,
+}
+
+impl LiquidAddress {
+    pub fn is_confidential(&self) -> bool {
+        self.blinding_pubkey.is_some()
+    }
+
+    /// The same address without the blinding key, outputs sent to it are not blinded
+    pub fn to_unconfidential(&self) -> LiquidAddress {
+        LiquidAddress {
+            blinding_pubkey: None,
+            ..self.clone()
+        }
+    }
+
+    fn from_base58(s: &str) -> Result<Self, ()> {
+        let data = base58::decode_check(s).map_err(|_| ())?;
+        let (prefix, data) = data.split_first().ok_or(())?;
+
+        // confidential addresses wrap the unconfidential prefix and hash with the blinding key
+        let blinded = ADDRESS_PARAMS.iter().find(|p| p.blinded_prefix == *prefix);
+        let (prefix, blinding_pubkey, hash) = match blinded {
+            Some(_) if data.len() == 1 + 33 + 20 => {
+                let pubkey = PublicKey::from_slice(&data[1..34]).map_err(|_| ())?;
+                (&data[0], Some(pubkey), &data[34..])
+            }
+            _ => (prefix, None, data),
+        };
+        let hash: [u8; 20] = hash.try_into().map_err(|_| ())?;
+
+        ADDRESS_PARAMS
+            .iter()
+            .filter(|p| blinded.map_or(true, |b| b.network == p.network))
+            .find_map(|p| {
+                if *prefix == p.p2pkh_prefix {
+                    Some((p.network, LiquidPayload::PubkeyHash(hash)))
+                } else if *prefix == p.p2sh_prefix {
+                    Some((p.network, LiquidPayload::ScriptHash(hash)))
+                } else {
+                    None
+                }
+            })
+            .map(|(network, payload)| LiquidAddress {
+                network,
+                payload,
+                blinding_pubkey,
+            })
+            .ok_or(())
+    }
+
+    fn from_bech32(s: &str) -> Result<Self, ()> {
+        let (hrp, data, variant) = bech32::decode(s).map_err(|_| ())?;
+        let network = ADDRESS_PARAMS
+            .iter()
+            .find(|p| p.bech_hrp == hrp)
+            .map(|p| p.network)
+            .ok_or(())?;
+
+        let (version, data) = data.split_first().ok_or(())?;
+        let version = version.to_u8();
+        let program = Vec::<u8>::from_base32(data).map_err(|_| ())?;
+        let expected_variant = if version == 0 {
+            Variant::Bech32
+        } else {
+            Variant::Bech32m
+        };
+        if variant != expected_variant || !is_valid_witness_program(version, &program) {
+            return Err(());
+        }
+
+        Ok(LiquidAddress {
+            network,
+            payload: LiquidPayload::WitnessProgram { version, program },
+            blinding_pubkey: None,
+        })
+    }
+
+    fn from_blech32(s: &str) -> Result<Self, ()> {
+        let (hrp, data, is_blech32m) = blech32_decode(s)?;
+        let network = ADDRESS_PARAMS
+            .iter()
+            .find(|p| p.blech_hrp == hrp)
+            .map(|p| p.network)
+            .ok_or(())?;
+
+        let (version, data) = data.split_first().ok_or(())?;
+        let version = *version;
+        let data = data
+            .iter()
+            .map(|v| bech32::u5::try_from_u8(*v))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| ())?;
+        let data = Vec::<u8>::from_base32(&data).map_err(|_| ())?;
+        if data.len() < 33 || is_blech32m != (version != 0) {
+            return Err(());
+        }
+
+        let (pubkey, program) = data.split_at(33);
+        if !is_valid_witness_program(version, program) {
+            return Err(());
+        }
+
+        Ok(LiquidAddress {
+            network,
+            payload: LiquidPayload::WitnessProgram {
+                version,
+                program: program.to_vec(),
+            },
+            blinding_pubkey: Some(PublicKey::from_slice(pubkey).map_err(|_| ())?),
+        })
+    }
+}
+
+fn base58_data(
+    prefix: u8,
+    params: &AddressParams,
+    blinding_pubkey: Option<PublicKey>,
+    hash: &[u8; 20],
+) -> Vec<u8> {
+    let mut data = match blinding_pubkey {
+        Some(pubkey) => {
+            let mut data = vec![params.blinded_prefix, prefix];
+            data.extend_from_slice(&pubkey.serialize());
+            data
+        }
+        None => vec![prefix],
+    };
+    data.extend_from_slice(hash);
+    data
+}
+
+impl fmt::Display for LiquidAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let params = self.network.params();
+        match (&self.payload, self.blinding_pubkey) {
+            (LiquidPayload::PubkeyHash(hash), blinding_pubkey) => {
+                let data = base58_data(params.p2pkh_prefix, params, blinding_pubkey, hash);
+                f.write_str(&base58::encode_check(&data))
+            }
+            (LiquidPayload::ScriptHash(hash), blinding_pubkey) => {
+                let data = base58_data(params.p2sh_prefix, params, blinding_pubkey, hash);
+                f.write_str(&base58::encode_check(&data))
+            }
+            (LiquidPayload::WitnessProgram { version, program }, Some(pubkey)) => {
+                let mut bytes = pubkey.serialize().to_vec();
+                bytes.extend_from_slice(program);
+                let mut data = vec![*version];
+                data.extend(bytes.to_base32().into_iter().map(|v| v.to_u8()));
+                f.write_str(&blech32_encode(params.blech_hrp, &data, *version != 0))
+            }
+            (LiquidPayload::WitnessProgram { version, program }, None) => {
+                let variant = if *version == 0 {
+                    Variant::Bech32
+                } else {
+                    Variant::Bech32m
+                };
+                let mut data = vec![bech32::u5::try_from_u8(*version).map_err(|_| fmt::Error)?];
+                data.extend(program.to_base32());
+                bech32::encode_to_fmt(f, params.bech_hrp, data, variant).map_err(|_| fmt::Error)?
+            }
+        }
+    }
+}
+
+impl FromStr for LiquidAddress {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        LiquidAddress::from_bech32(s)
+            .or_else(|_| LiquidAddress::from_blech32(s))
+            .or_else(|_| LiquidAddress::from_base58(s))
+    }
+}
+
+/// A `liquidnetwork:` URI, these follow BIP21 with the addition of an `assetid` parameter
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LiquidUri<'a> {
+    pub address: LiquidAddress,
+    /// Amount in whole units of the asset, assumes the asset has a precision of 8 like L-BTC
+    pub amount: Option<Amount>,
+    pub asset_id: Option<AssetId>,
+    pub label: Option<Cow<'a, str>>,
+    pub message: Option<Cow<'a, str>>,
+}
+
+impl LiquidUri<'_> {
+    /// Returns true if the URI requests L-BTC, URIs without an asset id default to it
+    pub fn is_policy_asset(&self) -> bool {
+        match self.asset_id {
+            None => true,
+            Some(asset_id) => self.address.network.policy_asset() == Some(asset_id),
+        }
+    }
+}
+
+impl fmt::Display for LiquidUri<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut query = form_urlencoded::Serializer::new(String::new());
+        if let Some(amount) = self.amount {
+            query.append_pair("amount", &amount.to_string_in(Denomination::Bitcoin));
+        }
+        if let Some(asset_id) = self.asset_id {
+            query.append_pair("assetid", &asset_id.to_string());
+        }
+        if let Some(label) = &self.label {
+            query.append_pair("label", label);
+        }
+        if let Some(message) = &self.message {
+            query.append_pair("message", message);
+        }
+
+        write!(f, "{LIQUID_URI_SCHEME}:{}", self.address)?;
+        let query = query.finish();
+        if !query.is_empty() {
+            write!(f, "?{query}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for LiquidUri<'static> {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (scheme, rest) = s.split_once(':').ok_or(())?;
+        if !scheme.eq_ignore_ascii_case(LIQUID_URI_SCHEME) {
+            return Err(());
+        }
+
+        let (address, query) = rest.split_once('?').unwrap_or((rest, ""));
+        let mut uri = LiquidUri {
+            address: LiquidAddress::from_str(address)?,
+            amount: None,
+            asset_id: None,
+            label: None,
+            message: None,
+        };
+
+        for (key, value) in form_urlencoded::parse(query.as_bytes()) {
+            match key.as_ref() {
+                "amount" if uri.amount.is_none() => {
+                    let amount =
+                        Amount::from_str_in(&value, Denomination::Bitcoin).map_err(|_| ())?;
+                    uri.amount = Some(amount);
+                }
+                "assetid" if uri.asset_id.is_none() => {
+                    uri.asset_id = Some(AssetId::from_str(&value)?);
+                }
+                "label" if uri.label.is_none() => uri.label = Some(Cow::Owned(value.into_owned())),
+                "message" if uri.message.is_none() => {
+                    uri.message = Some(Cow::Owned(value.into_owned()))
+                }
+                "amount" | "assetid" | "label" | "message" => return Err(()),
+                key if key.starts_with("req-") => return Err(()),
+                _ => {}
+            }
+        }
+
+        // the amount is meaningless without knowing which asset it is for
+        if uri.amount.is_some() && uri.asset_id.is_none() {
+            return Err(());
+        }
+
+        Ok(uri)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::str::FromStr;
+
+    use bitcoin::Amount;
+
+    use super::*;
+
+    const USDT: &str = "ce091c998b83c78bb71a632313ba3760f1763d9cfcffae02258ffa9865a37bd2";
+
+    #[test]
+    fn test_parse_liquid_address() {
+        let address =
+            LiquidAddress::from_str("ex1qqypqxpq9qcrsszg2pvxq6rs0zqg3yyc5r30d03").unwrap();
+        assert_eq!(address.network, LiquidNetwork::Liquid);
+        assert_eq!(
+            address.payload,
+            LiquidPayload::WitnessProgram {
+                version: 0,
+                program: (1..=20).collect()
+            }
+        );
+        assert_eq!(
+            address.to_string(),
+            "ex1qqypqxpq9qcrsszg2pvxq6rs0zqg3yyc5r30d03"
+        );
+
+        let address = LiquidAddress::from_str("PwMiDAQnrkKZL9dTfCpiavfCy8LZrFtiGG").unwrap();
+        assert_eq!(address.network, LiquidNetwork::Liquid);
+        assert!(matches!(address.payload, LiquidPayload::PubkeyHash(_)));
+        assert_eq!(address.to_string(), "PwMiDAQnrkKZL9dTfCpiavfCy8LZrFtiGG");
+
+        let address = LiquidAddress::from_str("GhGrVD3c5VyncM7uDepyrfm3e3hb2jQBsu").unwrap();
+        assert!(matches!(address.payload, LiquidPayload::ScriptHash(_)));
+
+        let address =
+            LiquidAddress::from_str("tex1qqypqxpq9qcrsszg2pvxq6rs0zqg3yyc5ehayn6").unwrap();
+        assert_eq!(address.network, LiquidNetwork::LiquidTestnet);
+
+        // bitcoin addresses are not liquid addresses
+        assert!(LiquidAddress::from_str("1andreas3batLhQa2FawWjeyjCqyBzypd").is_err());
+        assert!(LiquidAddress::from_str("bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq").is_err());
+    }
+
+    #[test]
+    fn test_parse_confidential_address() {
+        let blinding_pubkey = PublicKey::from_str(
+            "03f9bb4439168b190c7f308b36fedc74f258a1bc2a0f1ddee2e1135d663b689216",
+        )
+        .unwrap();
+
+        let address = LiquidAddress::from_str("el1qq0umk3pez693jrrlxz9ndlkuwne93gdu9g83mhhzuyf46e3mdzfpva0w48gqgzgrklncnm0k5zeyw8my2ypfsmxh4xcjh2rse").unwrap();
+        assert_eq!(address.network, LiquidNetwork::ElementsRegtest);
+        assert!(address.is_confidential());
+        assert_eq!(address.blinding_pubkey, Some(blinding_pubkey));
+        assert_eq!(
+            address.to_string(),
+            "el1qq0umk3pez693jrrlxz9ndlkuwne93gdu9g83mhhzuyf46e3mdzfpva0w48gqgzgrklncnm0k5zeyw8my2ypfsmxh4xcjh2rse"
+        );
+        assert_eq!(
+            address.to_unconfidential().to_string(),
+            "ert1qwhh2n5qypypm0eufahm2pvj8raj9zq5c27cysu"
+        );
+
+        let address = LiquidAddress::from_str("lq1qq0umk3pez693jrrlxz9ndlkuwne93gdu9g83mhhzuyf46e3mdzfpva0w48gqgzgrklncnm0k5zeyw8my2ypfsswhx9kv8d3vr").unwrap();
+        assert_eq!(address.network, LiquidNetwork::Liquid);
+        assert_eq!(address.blinding_pubkey, Some(blinding_pubkey));
+        assert_eq!(
+            address.to_unconfidential().to_string(),
+            "ex1qwhh2n5qypypm0eufahm2pvj8raj9zq5csvju0x"
+        );
+
+        let address = LiquidAddress::from_str(
+            "VTq7j1ojC3exhmXkWLAYRvZHempyzZdGke1cYeQPwrBkGEE2KnNn8793DHDmnd7mDQiEz6PyQNYsXomr",
+        )
+        .unwrap();
+        assert_eq!(address.network, LiquidNetwork::Liquid);
+        assert_eq!(address.blinding_pubkey, Some(blinding_pubkey));
+        assert!(matches!(address.payload, LiquidPayload::PubkeyHash(_)));
+        assert_eq!(
+            address.to_string(),
+            "VTq7j1ojC3exhmXkWLAYRvZHempyzZdGke1cYeQPwrBkGEE2KnNn8793DHDmnd7mDQiEz6PyQNYsXomr"
+        );
+        assert_eq!(
+            address.to_unconfidential().to_string(),
+            "Q81x6FeZiXFKfSwrkKZ9ry3fFJBYrUiRiX"
+        );
+
+        // bad checksum
+        assert!(LiquidAddress::from_str("lq1qq0umk3pez693jrrlxz9ndlkuwne93gdu9g83mhhzuyf46e3mdzfpva0w48gqgzgrklncnm0k5zeyw8my2ypfsswhx9kv8d3vq").is_err());
+    }
+
+    #[test]
+    fn test_parse_liquid_uri() {
+        let uri = LiquidUri::from_str(&format!(
+            "liquidnetwork:ex1qqypqxpq9qcrsszg2pvxq6rs0zqg3yyc5r30d03?amount=10.5&assetid={USDT}&label=coffee"
+        ))
+        .unwrap();
+        assert_eq!(uri.address.network, LiquidNetwork::Liquid);
+        assert_eq!(uri.amount, Some(Amount::from_sat(1_050_000_000)));
+        assert_eq!(uri.asset_id, Some(AssetId::from_str(USDT).unwrap()));
+        assert_eq!(uri.label.as_deref(), Some("coffee"));
+        assert_eq!(uri.message, None);
+        assert!(!uri.is_policy_asset());
+        assert_eq!(
+            uri.to_string(),
+            format!("liquidnetwork:ex1qqypqxpq9qcrsszg2pvxq6rs0zqg3yyc5r30d03?amount=10.5&assetid={USDT}&label=coffee")
+        );
+
+        let uri = LiquidUri::from_str("liquidnetwork:ex1qqypqxpq9qcrsszg2pvxq6rs0zqg3yyc5r30d03")
+            .unwrap();
+        assert_eq!(uri.amount, None);
+        assert!(uri.is_policy_asset());
+    }
+
+    #[test]
+    fn test_invalid_liquid_uri() {
+        // amount without an asset id
+        assert!(LiquidUri::from_str(
+            "liquidnetwork:ex1qqypqxpq9qcrsszg2pvxq6rs0zqg3yyc5r30d03?amount=1"
+        )
+        .is_err());
+        // unknown required param
+        assert!(LiquidUri::from_str(
+            "liquidnetwork:ex1qqypqxpq9qcrsszg2pvxq6rs0zqg3yyc5r30d03?req-foo=bar"
+        )
+        .is_err());
+        // bitcoin address
+        assert!(LiquidUri::from_str("liquidnetwork:1andreas3batLhQa2FawWjeyjCqyBzypd").is_err());
+        assert!(LiquidUri::from_str("bitcoin:ex1qqypqxpq9qcrsszg2pvxq6rs0zqg3yyc5r30d03").is_err());
+    }
+}
@@ -0,0 +1,139 @@
+use core::fmt;
+use core::str::FromStr;
+
+use base64::alphabet;
+use base64::engine::general_purpose::GeneralPurposeConfig;
+use base64::engine::{DecodePaddingMode, GeneralPurpose};
+use base64::Engine;
+use nostr::Url;
+
+use crate::prelude::*;
+
+/// lndconnect URI Scheme
+pub const LNDCONNECT_URI_SCHEME: &str = "lndconnect";
+
+/// Default gRPC port of LND
+const DEFAULT_PORT: u16 = 10009;
+
+/// lndconnect uses url safe base64, some implementations keep the padding
+const BASE64: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::URL_SAFE,
+    GeneralPurposeConfig::new()
+        .with_encode_padding(false)
+        .with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+/// An `lndconnect://` URI used to connect a wallet to a remote LND node.
+///
+/// The macaroon grants access to the node so it is redacted from [`fmt::Debug`] output.
+#[derive(Clone, PartialEq, Eq)]
+pub struct LndConnectUri {
+    pub host: String,
+    pub port: u16,
+    /// DER encoded TLS certificate, omitted when the node uses a certificate signed by a CA
+    pub cert: Option<Vec<u8>>,
+    pub macaroon: Vec<u8>,
+}
+
+impl fmt::Debug for LndConnectUri {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LndConnectUri")
+            .field("host", &self.host)
+            .field("port", &self.port)
+            .field("cert", &self.cert.as_ref().map(|c| c.len()))
+            .field("macaroon", &"<redacted>")
+            .finish()
+    }
+}
+
+impl fmt::Display for LndConnectUri {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{LNDCONNECT_URI_SCHEME}://{}:{}?", self.host, self.port)?;
+        if let Some(cert) = &self.cert {
+            write!(f, "cert={}&", BASE64.encode(cert))?;
+        }
+        write!(f, "macaroon={}", BASE64.encode(&self.macaroon))
+    }
+}
+
+impl FromStr for LndConnectUri {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let url = Url::parse(s).map_err(|_| ())?;
+        if url.scheme() != LNDCONNECT_URI_SCHEME {
+            return Err(());
+        }
+
+        let host = url.host_str().ok_or(())?.to_string();
+        let port = url.port().unwrap_or(DEFAULT_PORT);
+
+        let mut cert = None;
+        let mut macaroon = None;
+        for (key, value) in url.query_pairs() {
+            match key.as_ref() {
+                "cert" => cert = Some(BASE64.decode(value.as_bytes()).map_err(|_| ())?),
+                "macaroon" => macaroon = Some(BASE64.decode(value.as_bytes()).map_err(|_| ())?),
+                _ => {}
+            }
+        }
+
+        Ok(LndConnectUri {
+            host,
+            port,
+            cert,
+            macaroon: macaroon.ok_or(())?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::str::FromStr;
+
+    use super::LndConnectUri;
+
+    #[test]
+    fn test_parse_lndconnect() {
+        let uri = LndConnectUri::from_str(
+            "lndconnect://mynode.local:10009?cert=MIICuDCCAl-gAwIBAgIRAOA&macaroon=AgEDbG5kAvgBAwoQ",
+        )
+        .unwrap();
+        assert_eq!(uri.host, "mynode.local");
+        assert_eq!(uri.port, 10009);
+        assert_eq!(
+            uri.cert.as_deref(),
+            Some(
+                &[
+                    0x30, 0x82, 0x02, 0xb8, 0x30, 0x82, 0x02, 0x5f, 0xa0, 0x03, 0x02, 0x01, 0x02,
+                    0x02, 0x11, 0x00, 0xe0
+                ][..]
+            )
+        );
+        assert_eq!(uri.macaroon[..4], [0x02, 0x01, 0x03, 0x6c]);
+        assert_eq!(
+            uri.to_string(),
+            "lndconnect://mynode.local:10009?cert=MIICuDCCAl-gAwIBAgIRAOA&macaroon=AgEDbG5kAvgBAwoQ"
+        );
+        assert!(!format!("{uri:?}").contains("AgEDbG5kAvgBAwoQ"));
+    }
+
+    #[test]
+    fn test_parse_lndconnect_without_cert() {
+        let uri = LndConnectUri::from_str(
+            "lndconnect://abcdefghijklmnop.onion?macaroon=AgEDbG5kAvgBAwoQ",
+        )
+        .unwrap();
+        assert_eq!(uri.host, "abcdefghijklmnop.onion");
+        assert_eq!(uri.port, 10009);
+        assert_eq!(uri.cert, None);
+    }
+
+    #[test]
+    fn test_invalid_lndconnect() {
+        // missing macaroon
+        assert!(LndConnectUri::from_str("lndconnect://mynode.local:10009?cert=MIIC").is_err());
+        assert!(LndConnectUri::from_str("lndconnect://mynode.local:10009?macaroon=!!").is_err());
+        assert!(LndConnectUri::from_str("https://mynode.local:10009?macaroon=AgED").is_err());
+    }
+}
@@ -0,0 +1,273 @@
+use core::fmt;
+use core::str::FromStr;
+
+use bitcoin::bip32::{ChildNumber, DerivationPath, ExtendedPrivKey};
+#[cfg(feature = "std")]
+use bitcoin::hashes::hex::FromHex;
+use bitcoin::hashes::{sha256, Hash, HashEngine, Hmac, HmacEngine};
+use bitcoin::secp256k1::ecdsa::Signature;
+use bitcoin::secp256k1::{All, Message, PublicKey, Secp256k1};
+#[cfg(feature = "std")]
+use lnurl::lnurl::LnUrl;
+#[cfg(feature = "std")]
+use nostr::Url;
+
+use crate::prelude::*;
+
+/// What the service is asking the user to do, from the optional LUD-04 `action` param
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LnUrlAuthAction {
+    /// Create a new account
+    Register,
+    /// Log in to an existing account
+    Login,
+    /// Link the key to an existing account
+    Link,
+    /// Authorize an action, e.g. a withdrawal
+    Auth,
+}
+
+impl fmt::Display for LnUrlAuthAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LnUrlAuthAction::Register => write!(f, "register"),
+            LnUrlAuthAction::Login => write!(f, "login"),
+            LnUrlAuthAction::Link => write!(f, "link"),
+            LnUrlAuthAction::Auth => write!(f, "auth"),
+        }
+    }
+}
+
+impl FromStr for LnUrlAuthAction {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "register" => Ok(LnUrlAuthAction::Register),
+            "login" => Ok(LnUrlAuthAction::Login),
+            "link" => Ok(LnUrlAuthAction::Link),
+            "auth" => Ok(LnUrlAuthAction::Auth),
+            _ => Err(()),
+        }
+    }
+}
+
+/// An LNURL-auth challenge, LUD-04
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LnUrlAuthRequest {
+    url: String,
+    k1: [u8; 32],
+    action: Option<LnUrlAuthAction>,
+    domain: String,
+}
+
+impl LnUrlAuthRequest {
+    /// Extracts the challenge from an LNURL, `None` if it isn't a valid auth LNURL
+    #[cfg(feature = "std")]
+    pub fn from_lnurl(lnurl: &LnUrl) -> Option<Self> {
+        let url = Url::parse(&lnurl.url).ok()?;
+        let query = |key: &str| {
+            url.query_pairs()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| v.into_owned())
+        };
+        if query("tag")? != "login" {
+            return None;
+        }
+        let k1 = <[u8; 32]>::from_hex(&query("k1")?).ok()?;
+        // unknown actions are ignored rather than rejecting the whole request
+        let action = query("action").and_then(|a| LnUrlAuthAction::from_str(&a).ok());
+
+        Some(Self {
+            url: lnurl.url.clone(),
+            k1,
+            action,
+            domain: url.host_str()?.to_string(),
+        })
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// The challenge to sign
+    pub fn k1(&self) -> [u8; 32] {
+        self.k1
+    }
+
+    pub fn action(&self) -> Option<LnUrlAuthAction> {
+        self.action
+    }
+
+    /// The full domain name of the service, linking keys are derived from it
+    pub fn domain(&self) -> &str {
+        &self.domain
+    }
+
+    /// Signs the challenge, returning the linking key and signature to send to the service
+    pub fn sign<S: LnUrlAuthSigner>(&self, signer: &S) -> Result<(PublicKey, Signature), S::Error> {
+        let key = signer.linking_key(&self.domain)?;
+        let sig = signer.sign(&self.domain, &self.k1)?;
+        Ok((key, sig))
+    }
+
+    /// The url that logs in with the given linking key and signature
+    pub fn callback_url(&self, key: &PublicKey, sig: &Signature) -> String {
+        format!("{}&sig={sig}&key={key}", self.url)
+    }
+}
+
+/// Provides the linking keys and signatures for LNURL-auth, so the key material
+/// never has to be handed to waila, e.g. when it lives on a hardware signer.
+pub trait LnUrlAuthSigner {
+    type Error: fmt::Display;
+
+    /// The public linking key used for the given domain
+    fn linking_key(&self, domain: &str) -> Result<PublicKey, Self::Error>;
+
+    /// Signs the `k1` challenge with the linking key of the given domain
+    fn sign(&self, domain: &str, k1: &[u8; 32]) -> Result<Signature, Self::Error>;
+}
+
+/// A signer deriving a linking key per domain from a BIP-32 root key, as described in LUD-05
+pub struct Lud05Signer {
+    root: ExtendedPrivKey,
+    hashing_key: [u8; 32],
+    secp: Secp256k1<All>,
+}
+
+impl fmt::Debug for Lud05Signer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Lud05Signer")
+            .field("fingerprint", &self.root.fingerprint(&self.secp))
+            .finish()
+    }
+}
+
+impl Lud05Signer {
+    pub fn new(root: ExtendedPrivKey) -> Result<Self, bitcoin::bip32::Error> {
+        let secp = Secp256k1::new();
+        let path = [
+            ChildNumber::from_hardened_idx(138)?,
+            ChildNumber::from_normal_idx(0)?,
+        ];
+        let hashing_key = root.derive_priv(&secp, &path)?.private_key.secret_bytes();
+        Ok(Self {
+            root,
+            hashing_key,
+            secp,
+        })
+    }
+
+    /// `m/138'/<long1>/<long2>/<long3>/<long4>` where the longs are the first
+    /// 16 bytes of `hmacSha256(hashingKey, domain)`
+    pub fn derivation_path(&self, domain: &str) -> DerivationPath {
+        let mut engine = HmacEngine::<sha256::Hash>::new(&self.hashing_key);
+        engine.input(domain.as_bytes());
+        let hmac = Hmac::<sha256::Hash>::from_engine(engine).to_byte_array();
+
+        let mut path = vec![ChildNumber::Hardened { index: 138 }];
+        path.extend(
+            hmac[..16]
+                .chunks_exact(4)
+                .map(|c| ChildNumber::from(u32::from_be_bytes([c[0], c[1], c[2], c[3]]))),
+        );
+        DerivationPath::from(path)
+    }
+
+    fn linking_xpriv(&self, domain: &str) -> Result<ExtendedPrivKey, bitcoin::bip32::Error> {
+        self.root
+            .derive_priv(&self.secp, &self.derivation_path(domain))
+    }
+}
+
+impl LnUrlAuthSigner for Lud05Signer {
+    type Error = bitcoin::bip32::Error;
+
+    fn linking_key(&self, domain: &str) -> Result<PublicKey, Self::Error> {
+        Ok(self
+            .linking_xpriv(domain)?
+            .private_key
+            .public_key(&self.secp))
+    }
+
+    fn sign(&self, domain: &str, k1: &[u8; 32]) -> Result<Signature, Self::Error> {
+        let key = self.linking_xpriv(domain)?.private_key;
+        let message = Message::from_slice(k1).expect("k1 is 32 bytes");
+        Ok(self.secp.sign_ecdsa(&message, &key))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bitcoin::Network;
+
+    use super::*;
+
+    const K1: &str = "e2af6254a8df433264fa23f67eb8188635d15ce883e8fc020989d5f82ae6f11e";
+
+    fn lnurl(query: &str) -> LnUrl {
+        LnUrl::from_url(format!("https://site.com/api/auth?{query}"))
+    }
+
+    #[test]
+    fn test_from_lnurl() {
+        let request =
+            LnUrlAuthRequest::from_lnurl(&lnurl(&format!("tag=login&k1={K1}&action=register")))
+                .unwrap();
+        assert_eq!(request.domain(), "site.com");
+        assert_eq!(request.k1().to_vec(), Vec::<u8>::from_hex(K1).unwrap());
+        assert_eq!(request.action(), Some(LnUrlAuthAction::Register));
+
+        // action is optional
+        let request = LnUrlAuthRequest::from_lnurl(&lnurl(&format!("tag=login&k1={K1}"))).unwrap();
+        assert_eq!(request.action(), None);
+
+        // not auth, or a bad k1
+        assert!(
+            LnUrlAuthRequest::from_lnurl(&lnurl(&format!("tag=withdrawRequest&k1={K1}"))).is_none()
+        );
+        assert!(LnUrlAuthRequest::from_lnurl(&lnurl("tag=login&k1=abcd")).is_none());
+    }
+
+    #[test]
+    fn test_lud05_derivation_path() {
+        // the LUD-05 test vector gives the hashing key directly
+        let root = ExtendedPrivKey::new_master(Network::Bitcoin, &[0; 32]).unwrap();
+        let mut signer = Lud05Signer::new(root).unwrap();
+        signer.hashing_key = <[u8; 32]>::from_hex(
+            "7d417a6a5e9a6a4a879aeaba11a11838764c8fa2b959c242d43dea682b3e409b",
+        )
+        .unwrap();
+        let expected = vec![
+            ChildNumber::Hardened { index: 138 },
+            ChildNumber::from(1588488367),
+            ChildNumber::from(2659270754),
+            ChildNumber::from(38110259),
+            ChildNumber::from(4136336762),
+        ];
+        assert_eq!(
+            signer.derivation_path("site.com"),
+            DerivationPath::from(expected)
+        );
+    }
+
+    #[test]
+    fn test_sign() {
+        let root = ExtendedPrivKey::new_master(Network::Bitcoin, &[1; 32]).unwrap();
+        let signer = Lud05Signer::new(root).unwrap();
+        let request = LnUrlAuthRequest::from_lnurl(&lnurl(&format!("tag=login&k1={K1}"))).unwrap();
+
+        let (key, sig) = request.sign(&signer).unwrap();
+        let message = Message::from_slice(&request.k1()).unwrap();
+        assert!(signer.secp.verify_ecdsa(&message, &sig, &key).is_ok());
+
+        // each domain gets its own key
+        assert_ne!(signer.linking_key("other.com").unwrap(), key);
+
+        assert_eq!(
+            request.callback_url(&key, &sig),
+            format!("https://site.com/api/auth?tag=login&k1={K1}&sig={sig}&key={key}")
+        );
+    }
+}
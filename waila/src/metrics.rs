@@ -0,0 +1,225 @@
+#[cfg(feature = "resolver")]
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use crate::{PaymentParams, WailaParseError};
+
+/// Why a string failed to parse
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FailureCategory {
+    /// The input was empty or only whitespace
+    EmptyInput,
+    /// The input looked like a known format, e.g. a `lightning:` URI, but was invalid
+    InvalidPayload,
+    /// The input didn't look like anything we know
+    Unrecognized,
+}
+
+impl FailureCategory {
+    fn categorize(error: &WailaParseError) -> Self {
+        match error {
+            WailaParseError::Empty => FailureCategory::EmptyInput,
+            WailaParseError::Unrecognized => FailureCategory::Unrecognized,
+            WailaParseError::Invalid(_) => FailureCategory::InvalidPayload,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FailureCategory::EmptyInput => "empty_input",
+            FailureCategory::InvalidPayload => "invalid_payload",
+            FailureCategory::Unrecognized => "unrecognized",
+        }
+    }
+}
+
+/// Callbacks for monitoring how waila is used in the wild.
+/// Every method has a no-op default so implementors only need to handle what they care about.
+pub trait MetricsRecorder: Send + Sync {
+    /// Called every time a string is parsed
+    fn parse_attempt(&self) {}
+    /// Called when a string was parsed successfully, `kind` is the variant name, e.g. `bolt11`
+    fn parse_success(&self, _kind: &'static str) {}
+    /// Called when a string failed to parse
+    fn parse_failure(&self, _category: FailureCategory) {}
+    /// Called when a network resolution finishes, `resolver` is the kind of lookup, e.g. `lnurl`
+    fn resolution(&self, _resolver: &'static str, _latency: Duration, _success: bool) {}
+}
+
+static RECORDER: OnceLock<Box<dyn MetricsRecorder>> = OnceLock::new();
+
+/// Installs the global metrics recorder, this can only be done once.
+/// Returns the recorder back if one was already installed.
+pub fn set_recorder(recorder: Box<dyn MetricsRecorder>) -> Result<(), Box<dyn MetricsRecorder>> {
+    RECORDER.set(recorder)
+}
+
+fn recorder() -> Option<&'static dyn MetricsRecorder> {
+    RECORDER.get().map(|r| r.as_ref())
+}
+
+/// A ready to use [`MetricsRecorder`] that keeps simple in-memory counters.
+#[derive(Debug, Default)]
+pub struct Counters {
+    attempts: AtomicU64,
+    successes: Mutex<Vec<(&'static str, u64)>>,
+    failures: Mutex<Vec<(FailureCategory, u64)>>,
+    resolutions: AtomicU64,
+    resolution_failures: AtomicU64,
+    total_resolution_latency_ms: AtomicU64,
+}
+
+fn increment<K: PartialEq>(counts: &Mutex<Vec<(K, u64)>>, key: K) {
+    if let Ok(mut counts) = counts.lock() {
+        match counts.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((key, 1)),
+        }
+    }
+}
+
+impl Counters {
+    /// Number of strings parsed
+    pub fn attempts(&self) -> u64 {
+        self.attempts.load(Ordering::Relaxed)
+    }
+
+    /// Number of successful parses for the given kind
+    pub fn successes(&self, kind: &str) -> u64 {
+        self.successes
+            .lock()
+            .ok()
+            .and_then(|counts| counts.iter().find(|(k, _)| *k == kind).map(|(_, c)| *c))
+            .unwrap_or(0)
+    }
+
+    /// Number of failed parses for the given category
+    pub fn failures(&self, category: FailureCategory) -> u64 {
+        self.failures
+            .lock()
+            .ok()
+            .and_then(|counts| counts.iter().find(|(c, _)| *c == category).map(|(_, c)| *c))
+            .unwrap_or(0)
+    }
+
+    /// Number of network resolutions made
+    pub fn resolutions(&self) -> u64 {
+        self.resolutions.load(Ordering::Relaxed)
+    }
+
+    /// Number of network resolutions that failed
+    pub fn resolution_failures(&self) -> u64 {
+        self.resolution_failures.load(Ordering::Relaxed)
+    }
+
+    /// Average latency of all network resolutions
+    pub fn average_resolution_latency(&self) -> Option<Duration> {
+        let total = self.total_resolution_latency_ms.load(Ordering::Relaxed);
+        match self.resolutions() {
+            0 => None,
+            count => Some(Duration::from_millis(total / count)),
+        }
+    }
+}
+
+impl MetricsRecorder for Counters {
+    fn parse_attempt(&self) {
+        self.attempts.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn parse_success(&self, kind: &'static str) {
+        increment(&self.successes, kind);
+    }
+
+    fn parse_failure(&self, category: FailureCategory) {
+        increment(&self.failures, category);
+    }
+
+    fn resolution(&self, _resolver: &'static str, latency: Duration, success: bool) {
+        self.resolutions.fetch_add(1, Ordering::Relaxed);
+        if !success {
+            self.resolution_failures.fetch_add(1, Ordering::Relaxed);
+        }
+        self.total_resolution_latency_ms
+            .fetch_add(latency.as_millis() as u64, Ordering::Relaxed);
+    }
+}
+
+impl<T: MetricsRecorder> MetricsRecorder for std::sync::Arc<T> {
+    fn parse_attempt(&self) {
+        self.as_ref().parse_attempt()
+    }
+
+    fn parse_success(&self, kind: &'static str) {
+        self.as_ref().parse_success(kind)
+    }
+
+    fn parse_failure(&self, category: FailureCategory) {
+        self.as_ref().parse_failure(category)
+    }
+
+    fn resolution(&self, resolver: &'static str, latency: Duration, success: bool) {
+        self.as_ref().resolution(resolver, latency, success)
+    }
+}
+
+pub(crate) fn record_parse(result: &Result<PaymentParams, WailaParseError>) {
+    if let Some(recorder) = recorder() {
+        recorder.parse_attempt();
+        match result {
+            Ok(params) => recorder.parse_success(params.kind().as_str()),
+            Err(e) => recorder.parse_failure(FailureCategory::categorize(e)),
+        }
+    }
+}
+
+/// Runs a resolution future, reporting its latency to the recorder.
+/// Latencies aren't recorded when targeting `wasm32` as there is no monotonic clock available.
+#[cfg(feature = "resolver")]
+pub(crate) async fn record_resolution<T, E, F>(resolver: &'static str, future: F) -> Result<T, E>
+where
+    F: Future<Output = Result<T, E>>,
+{
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let start = std::time::Instant::now();
+        let result = future.await;
+        if let Some(recorder) = recorder() {
+            recorder.resolution(resolver, start.elapsed(), result.is_ok());
+        }
+        result
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        let _ = resolver;
+        future.await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::str::FromStr;
+    use std::sync::Arc;
+
+    use super::*;
+
+    #[test]
+    fn test_counters() {
+        let counters = Arc::new(Counters::default());
+        // other tests may parse concurrently so only check lower bounds
+        let _ = set_recorder(Box::new(counters.clone()));
+
+        PaymentParams::from_str("1andreas3batLhQa2FawWjeyjCqyBzypd").unwrap();
+        PaymentParams::from_str("ben@opreturnbot.com").unwrap();
+        assert!(PaymentParams::from_str("").is_err());
+        assert!(PaymentParams::from_str("lightning:invalid").is_err());
+
+        assert!(counters.attempts() >= 4);
+        assert!(counters.successes("on_chain") >= 1);
+        assert!(counters.successes("lightning_address") >= 1);
+        assert!(counters.failures(FailureCategory::EmptyInput) >= 1);
+        assert!(counters.failures(FailureCategory::InvalidPayload) >= 1);
+    }
+}
@@ -0,0 +1,62 @@
+use core::fmt;
+
+use bip39::Mnemonic;
+
+/// A BIP39 seed phrase, from plain words, a SeedQR or a CompactSeedQR.
+///
+/// Anyone with the words can spend the wallet's funds, so they are redacted from
+/// [`fmt::Debug`] output. bip39's own Debug prints the word indices.
+#[derive(Clone, PartialEq, Eq)]
+pub struct SeedPhrase {
+    mnemonic: Mnemonic,
+}
+
+impl SeedPhrase {
+    /// Sensitive, gives full control over the wallet
+    pub fn mnemonic(&self) -> &Mnemonic {
+        &self.mnemonic
+    }
+
+    pub fn word_count(&self) -> usize {
+        self.mnemonic.word_count()
+    }
+}
+
+impl From<Mnemonic> for SeedPhrase {
+    fn from(mnemonic: Mnemonic) -> Self {
+        Self { mnemonic }
+    }
+}
+
+impl fmt::Debug for SeedPhrase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SeedPhrase")
+            .field("mnemonic", &"<redacted>")
+            .field("word_count", &self.word_count())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bip39::Language;
+
+    use super::*;
+    use crate::prelude::*;
+
+    #[test]
+    fn test_debug_is_redacted() {
+        let mnemonic = Mnemonic::from_entropy_in(Language::English, &[0; 16]).unwrap();
+        let seed = SeedPhrase::from(mnemonic.clone());
+        assert_eq!(seed.mnemonic(), &mnemonic);
+        assert_eq!(seed.word_count(), 12);
+
+        let debug = format!("{seed:?}");
+        assert!(!debug.contains("abandon"));
+        assert!(!debug.contains(&format!("{mnemonic:?}")));
+        assert_eq!(
+            debug,
+            "SeedPhrase { mnemonic: \"<redacted>\", word_count: 12 }"
+        );
+    }
+}
@@ -0,0 +1,266 @@
+use core::convert::TryInto;
+
+use crate::prelude::*;
+
+/// The abbreviations a URI record's first byte can stand for, from the NFC Forum URI RTD
+const URI_PREFIXES: [&str; 36] = [
+    "",
+    "http://www.",
+    "https://www.",
+    "http://",
+    "https://",
+    "tel:",
+    "mailto:",
+    "ftp://anonymous:anonymous@",
+    "ftp://ftp.",
+    "ftps://",
+    "sftp://",
+    "smb://",
+    "nfs://",
+    "ftp://",
+    "dav://",
+    "news:",
+    "telnet://",
+    "imap:",
+    "rtsp://",
+    "urn:",
+    "pop:",
+    "sip:",
+    "sips:",
+    "tftp:",
+    "btspp://",
+    "btl2cap://",
+    "btgoep://",
+    "tcpobex://",
+    "irdaobex://",
+    "file://",
+    "urn:epc:id:",
+    "urn:epc:tag:",
+    "urn:epc:pat:",
+    "urn:epc:raw:",
+    "urn:epc:",
+    "urn:nfc:",
+];
+
+const MESSAGE_BEGIN: u8 = 0x80;
+const MESSAGE_END: u8 = 0x40;
+const CHUNKED: u8 = 0x20;
+const SHORT_RECORD: u8 = 0x10;
+const ID_LENGTH: u8 = 0x08;
+
+const TNF_WELL_KNOWN: u8 = 0x01;
+const TNF_ABSOLUTE_URI: u8 = 0x03;
+
+/// Tags store the message in a TLV block, e.g. NTAG21x chips used by Bolt Cards
+const TLV_NULL: u8 = 0x00;
+const TLV_NDEF_MESSAGE: u8 = 0x03;
+const TLV_TERMINATOR: u8 = 0xFE;
+
+/// Smart posters are used to nest more records to a URI, up to this depth
+const MAX_DEPTH: usize = 4;
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Option<&'a [u8]> {
+        if self.bytes.len() < len {
+            return None;
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Some(taken)
+    }
+
+    fn byte(&mut self) -> Option<u8> {
+        self.take(1).map(|b| b[0])
+    }
+}
+
+fn parse_uri(payload: &[u8]) -> Option<String> {
+    let (code, rest) = payload.split_first()?;
+    let prefix = URI_PREFIXES.get(usize::from(*code))?;
+    Some(format!("{prefix}{}", core::str::from_utf8(rest).ok()?))
+}
+
+fn parse_text(payload: &[u8]) -> Option<String> {
+    let (status, rest) = payload.split_first()?;
+    let text = rest.get(usize::from(status & 0x3f)..)?;
+    if status & 0x80 == 0 {
+        return core::str::from_utf8(text).ok().map(|t| t.to_string());
+    }
+
+    // UTF-16 is big endian unless it has a byte order mark saying otherwise
+    let little_endian = text.starts_with(&[0xff, 0xfe]);
+    let units: Vec<u16> = text
+        .chunks_exact(2)
+        .map(|c| {
+            let c = c.try_into().expect("chunks of 2");
+            if little_endian {
+                u16::from_le_bytes(c)
+            } else {
+                u16::from_be_bytes(c)
+            }
+        })
+        .collect();
+    let text = String::from_utf16(&units).ok()?;
+    Some(text.trim_start_matches('\u{feff}').to_string())
+}
+
+/// The URIs and text of an NDEF message, in the order of its records
+fn parse_message(bytes: &[u8], depth: usize) -> Option<Vec<String>> {
+    let mut reader = Reader { bytes };
+    let mut strings = vec![];
+    let mut first = true;
+    loop {
+        let header = reader.byte()?;
+        if first != (header & MESSAGE_BEGIN != 0) {
+            return None;
+        }
+        first = false;
+        // waila payloads are small enough to never be split into chunks
+        if header & CHUNKED != 0 {
+            return None;
+        }
+
+        let type_len = reader.byte()?;
+        let payload_len = if header & SHORT_RECORD != 0 {
+            usize::from(reader.byte()?)
+        } else {
+            u32::from_be_bytes(reader.take(4)?.try_into().ok()?) as usize
+        };
+        let id_len = if header & ID_LENGTH != 0 {
+            reader.byte()?
+        } else {
+            0
+        };
+        let record_type = reader.take(usize::from(type_len))?;
+        reader.take(usize::from(id_len))?;
+        let payload = reader.take(payload_len)?;
+
+        match (header & 0x07, record_type) {
+            (TNF_WELL_KNOWN, b"U") => strings.extend(parse_uri(payload)),
+            (TNF_WELL_KNOWN, b"T") => strings.extend(parse_text(payload)),
+            (TNF_WELL_KNOWN, b"Sp") if depth < MAX_DEPTH => {
+                strings.extend(parse_message(payload, depth + 1).unwrap_or_default())
+            }
+            (TNF_ABSOLUTE_URI, uri) => {
+                strings.extend(core::str::from_utf8(uri).ok().map(|u| u.to_string()))
+            }
+            _ => {}
+        }
+
+        if header & MESSAGE_END != 0 {
+            return Some(strings);
+        }
+    }
+}
+
+/// Finds the NDEF message in the TLV blocks read from a tag
+fn parse_tlv(bytes: &[u8]) -> Option<&[u8]> {
+    let mut reader = Reader { bytes };
+    loop {
+        let tag = reader.byte()?;
+        match tag {
+            TLV_NULL => continue,
+            TLV_TERMINATOR => return None,
+            _ => {}
+        }
+
+        let len = match reader.byte()? {
+            0xff => usize::from(u16::from_be_bytes(reader.take(2)?.try_into().ok()?)),
+            len => usize::from(len),
+        };
+        let value = reader.take(len)?;
+        if tag == TLV_NDEF_MESSAGE {
+            return Some(value);
+        }
+    }
+}
+
+/// The URI and text records of an NDEF message, either on its own or in the
+/// TLV blocks of a tag's memory. `None` if it isn't a valid message.
+pub(crate) fn parse_ndef(bytes: &[u8]) -> Option<Vec<String>> {
+    let message = match bytes.first()? {
+        // the first record of a message always has the begin flag set
+        header if header & MESSAGE_BEGIN != 0 => bytes,
+        _ => parse_tlv(bytes)?,
+    };
+    parse_message(message, 0)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn record(header: u8, record_type: &[u8], payload: &[u8]) -> Vec<u8> {
+        let mut record = vec![
+            header | SHORT_RECORD,
+            record_type.len() as u8,
+            payload.len() as u8,
+        ];
+        record.extend_from_slice(record_type);
+        record.extend_from_slice(payload);
+        record
+    }
+
+    #[test]
+    fn test_uri_record() {
+        let mut payload = vec![0x00];
+        payload.extend_from_slice(b"lnurlw://boltcard.example.com/ln?p=ab&c=cd");
+        let message = record(MESSAGE_BEGIN | MESSAGE_END | TNF_WELL_KNOWN, b"U", &payload);
+        assert_eq!(
+            parse_ndef(&message),
+            Some(vec![
+                "lnurlw://boltcard.example.com/ln?p=ab&c=cd".to_string()
+            ])
+        );
+
+        // an abbreviated prefix
+        let message = record(
+            MESSAGE_BEGIN | MESSAGE_END | TNF_WELL_KNOWN,
+            b"U",
+            b"\x04example.com",
+        );
+        assert_eq!(
+            parse_ndef(&message),
+            Some(vec!["https://example.com".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_text_records() {
+        let mut message = record(MESSAGE_BEGIN | TNF_WELL_KNOWN, b"T", b"\x02enhello");
+        let mut utf16 = vec![0x82, b'e', b'n'];
+        utf16.extend("hi".encode_utf16().flat_map(|u| u.to_be_bytes()));
+        message.extend(record(MESSAGE_END | TNF_WELL_KNOWN, b"T", &utf16));
+        assert_eq!(
+            parse_ndef(&message),
+            Some(vec!["hello".to_string(), "hi".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_smart_poster_in_tlv() {
+        let uri = record(
+            MESSAGE_BEGIN | MESSAGE_END | TNF_WELL_KNOWN,
+            b"U",
+            b"\x00bitcoin:bc1qtest",
+        );
+        let poster = record(MESSAGE_BEGIN | MESSAGE_END | TNF_WELL_KNOWN, b"Sp", &uri);
+        let mut tlv = vec![TLV_NULL, TLV_NDEF_MESSAGE, poster.len() as u8];
+        tlv.extend_from_slice(&poster);
+        tlv.push(TLV_TERMINATOR);
+        assert_eq!(parse_ndef(&tlv), Some(vec!["bitcoin:bc1qtest".to_string()]));
+    }
+
+    #[test]
+    fn test_invalid_ndef() {
+        assert_eq!(parse_ndef(&[]), None);
+        // truncated payload
+        assert_eq!(parse_ndef(&[0xd1, 0x01, 0x05, b'U', 0x00]), None);
+        // no message in the TLV
+        assert_eq!(parse_ndef(&[TLV_NULL, TLV_TERMINATOR]), None);
+    }
+}
@@ -0,0 +1,112 @@
+use core::fmt;
+use core::str::FromStr;
+
+use crate::prelude::*;
+
+/// The name NIP-05 uses for the root identifier of a domain, shown as just the domain
+const ROOT_NAME: &str = "_";
+
+/// A NIP-05 nostr identifier, e.g. `bob@example.com`, that maps to a nostr pubkey
+/// through `https://example.com/.well-known/nostr.json`.
+///
+/// These look exactly like lightning addresses, so they are only returned as an
+/// alternative by [`crate::PaymentParams::parse_multiple`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Nip05Identifier {
+    name: String,
+    domain: String,
+}
+
+fn is_valid_label(label: &str) -> bool {
+    !label.is_empty()
+        && label.len() <= 63
+        && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+impl Nip05Identifier {
+    pub fn new(name: &str, domain: &str) -> Option<Self> {
+        let name = name.to_lowercase();
+        let domain = domain.to_lowercase();
+
+        // NIP-05 only allows a-z0-9-_. in the local part
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+            || !domain.contains('.')
+            || !domain.split('.').all(is_valid_label)
+        {
+            return None;
+        }
+
+        Some(Self { name, domain })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn domain(&self) -> &str {
+        &self.domain
+    }
+
+    /// The url of the `nostr.json` document the identifier is resolved with
+    pub fn url(&self) -> String {
+        format!(
+            "https://{}/.well-known/nostr.json?name={}",
+            self.domain, self.name
+        )
+    }
+
+    /// Whether this is the `_@domain` identifier, which clients display as just the domain
+    pub fn is_root(&self) -> bool {
+        self.name == ROOT_NAME
+    }
+}
+
+impl fmt::Display for Nip05Identifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}@{}", self.name, self.domain)
+    }
+}
+
+impl FromStr for Nip05Identifier {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, domain) = s.split_once('@').ok_or(())?;
+        Nip05Identifier::new(name, domain).ok_or(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::str::FromStr;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_nip05() {
+        let id = Nip05Identifier::from_str("Bob@Example.com").unwrap();
+        assert_eq!(id.name(), "bob");
+        assert_eq!(id.domain(), "example.com");
+        assert_eq!(id.to_string(), "bob@example.com");
+        assert_eq!(
+            id.url(),
+            "https://example.com/.well-known/nostr.json?name=bob"
+        );
+        assert!(!id.is_root());
+
+        let id = Nip05Identifier::from_str("_@example.com").unwrap();
+        assert!(id.is_root());
+    }
+
+    #[test]
+    fn test_invalid_nip05() {
+        assert!(Nip05Identifier::from_str("example.com").is_err());
+        assert!(Nip05Identifier::from_str("@example.com").is_err());
+        assert!(Nip05Identifier::from_str("bob+zap@example.com").is_err());
+        assert!(Nip05Identifier::from_str("bob@localhost").is_err());
+        assert!(Nip05Identifier::from_str("bob@example.com:8080").is_err());
+    }
+}
@@ -0,0 +1,81 @@
+use core::fmt;
+use core::str::FromStr;
+
+use nostr::nips::nip19;
+use nostr::secp256k1::Secp256k1;
+use nostr::{FromBech32, Keys, PublicKey, SecretKey};
+
+/// A bech32 `nsec` nostr private key.
+///
+/// Anyone with the key can post as the user, so it is redacted from [`fmt::Debug`]
+/// output and there is intentionally no [`fmt::Display`] implementation.
+#[derive(Clone, PartialEq, Eq)]
+pub struct NostrSecretKey {
+    secret_key: SecretKey,
+}
+
+impl NostrSecretKey {
+    /// Sensitive, gives full control over the nostr identity
+    pub fn secret_key(&self) -> &SecretKey {
+        &self.secret_key
+    }
+
+    /// The public key of the identity, safe to show to the user
+    pub fn public_key(&self) -> PublicKey {
+        Keys::new_with_ctx(&Secp256k1::signing_only(), self.secret_key.clone()).public_key()
+    }
+}
+
+impl fmt::Debug for NostrSecretKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NostrSecretKey")
+            .field("secret_key", &"<redacted>")
+            .field("public_key", &self.public_key().to_hex())
+            .finish()
+    }
+}
+
+impl FromStr for NostrSecretKey {
+    type Err = nip19::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // only bech32, a hex key can't be told apart from a pubkey or txid
+        let secret_key = SecretKey::from_bech32(s)?;
+        Ok(Self { secret_key })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::str::FromStr;
+
+    use super::*;
+
+    const NSEC: &str = "nsec1vl029mgpspedva04g90vltkh6fvh240zqtv9k0t9af8935ke9laqsnlfe5";
+    const SECRET_HEX: &str = "67dea2ed018072d675f5415ecfaed7d2597555e202d85b3d65ea4e58d2d92ffa";
+
+    #[test]
+    fn test_parse_nsec() {
+        let key = NostrSecretKey::from_str(NSEC).unwrap();
+        assert_eq!(key.secret_key().to_secret_hex(), SECRET_HEX);
+        assert_eq!(
+            key.public_key().to_hex(),
+            "7e7e9c42a91bfef19fa929e5fda1b72e0ebc1a4c1141673e2794234d86addf4e"
+        );
+
+        assert!(NostrSecretKey::from_str(SECRET_HEX).is_err());
+        assert!(NostrSecretKey::from_str(
+            "npub180cvv07tjdrrgpa0j7j7tmnyl2yr6yr7l8j4s3evf6u64th6gkwsyjh6w6"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_debug_is_redacted() {
+        let key = NostrSecretKey::from_str(NSEC).unwrap();
+        let debug = format!("{key:?}");
+        assert!(debug.contains("<redacted>"));
+        assert!(!debug.contains(SECRET_HEX));
+        assert!(!debug.contains(NSEC));
+    }
+}
@@ -1,11 +1,13 @@
+use alloc::borrow::Cow;
 use core::fmt;
+use core::str::FromStr;
 use itertools::Itertools;
 use nostr::key::PublicKey;
 use nostr::nips::nip47::{Error, Method};
 use nostr::prelude::url::form_urlencoded::byte_serialize;
 use nostr::Url;
-use std::borrow::Cow;
-use std::str::FromStr;
+
+use crate::prelude::*;
 
 fn url_encode<T>(data: T) -> String
 where
@@ -62,9 +64,27 @@ pub const NIP49_URI_SCHEME: &str = "nostr+walletauth";
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct NIP49Budget {
     pub time_period: NIP49BudgetPeriod,
+    /// Max amount in sats per period
     pub amount: u64,
 }
 
+impl NIP49Budget {
+    pub fn new(amount: u64, time_period: NIP49BudgetPeriod) -> Self {
+        Self {
+            time_period,
+            amount,
+        }
+    }
+
+    pub fn amount_msats(&self) -> u64 {
+        self.amount.saturating_mul(1_000)
+    }
+
+    pub fn period(&self) -> NIP49BudgetPeriod {
+        self.time_period
+    }
+}
+
 impl fmt::Display for NIP49Budget {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}/{}", self.amount, self.time_period)
@@ -112,6 +132,72 @@ pub struct NIP49URI {
     pub identity: Option<PublicKey>,
 }
 
+impl NIP49URI {
+    /// Start building a URI for an app with the given pubkey, see [`NIP49URIBuilder`]
+    pub fn builder(public_key: PublicKey, relay_url: Url, secret: String) -> NIP49URIBuilder {
+        NIP49URIBuilder {
+            uri: NIP49URI {
+                public_key,
+                relay_url,
+                secret,
+                required_commands: vec![],
+                optional_commands: vec![],
+                budget: None,
+                identity: None,
+            },
+        }
+    }
+
+    /// The NIP-47 methods the app can't work without
+    pub fn required_commands(&self) -> &[Method] {
+        &self.required_commands
+    }
+
+    /// The NIP-47 methods the app can use if the wallet allows them
+    pub fn optional_commands(&self) -> &[Method] {
+        &self.optional_commands
+    }
+
+    pub fn budget(&self) -> Option<&NIP49Budget> {
+        self.budget.as_ref()
+    }
+}
+
+/// Builds a [`NIP49URI`], at least one required command has to be added
+#[derive(Debug, Clone)]
+pub struct NIP49URIBuilder {
+    uri: NIP49URI,
+}
+
+impl NIP49URIBuilder {
+    pub fn required_command(mut self, method: Method) -> Self {
+        self.uri.required_commands.push(method);
+        self
+    }
+
+    pub fn optional_command(mut self, method: Method) -> Self {
+        self.uri.optional_commands.push(method);
+        self
+    }
+
+    pub fn budget(mut self, budget: NIP49Budget) -> Self {
+        self.uri.budget = Some(budget);
+        self
+    }
+
+    pub fn identity(mut self, identity: PublicKey) -> Self {
+        self.uri.identity = Some(identity);
+        self
+    }
+
+    pub fn build(self) -> Result<NIP49URI, Error> {
+        if self.uri.required_commands.is_empty() || self.uri.secret.is_empty() {
+            return Err(Error::InvalidURI);
+        }
+        Ok(self.uri)
+    }
+}
+
 impl FromStr for NIP49URI {
     type Err = Error;
     fn from_str(uri: &str) -> Result<Self, Self::Err> {
@@ -189,7 +275,7 @@ impl fmt::Display for NIP49URI {
             "{NIP49_URI_SCHEME}://{}?relay={}&secret={}&required_commands={}",
             self.public_key,
             url_encode(self.relay_url.to_string()),
-            self.secret,
+            url_encode(&self.secret),
             url_encode(
                 self.required_commands
                     .iter()
@@ -218,3 +304,65 @@ impl fmt::Display for NIP49URI {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use core::str::FromStr;
+
+    use super::*;
+
+    const PUBKEY: &str = "b889ff5b1513b641e2a139f661a661364979c5beee91842f8f0ef42ab558e9d4";
+
+    #[test]
+    fn test_build_nip49_uri() {
+        let uri = NIP49URI::builder(
+            PublicKey::from_str(PUBKEY).unwrap(),
+            Url::parse("wss://relay.damus.io").unwrap(),
+            "b8a30fafa48d4795b6c0eec169a383de".to_string(),
+        )
+        .required_command(Method::PayInvoice)
+        .optional_command(Method::GetBalance)
+        .budget(NIP49Budget::new(10_000, NIP49BudgetPeriod::Daily))
+        .build()
+        .unwrap();
+
+        assert_eq!(
+            uri.to_string(),
+            format!("nostr+walletauth://{PUBKEY}?relay=wss%3A%2F%2Frelay.damus.io%2F&secret=b8a30fafa48d4795b6c0eec169a383de&required_commands=pay_invoice&optional_commands=get_balance&budget=10000%2Fdaily")
+        );
+        assert_eq!(NIP49URI::from_str(&uri.to_string()).unwrap(), uri);
+    }
+
+    #[test]
+    fn test_nip49_uri_roundtrip() {
+        let uri = NIP49URI::builder(
+            PublicKey::from_str(PUBKEY).unwrap(),
+            Url::parse("wss://relay.damus.io").unwrap(),
+            "a secret&with=symbols".to_string(),
+        )
+        .required_command(Method::PayInvoice)
+        .required_command(Method::MakeInvoice)
+        .identity(PublicKey::from_str(PUBKEY).unwrap())
+        .build()
+        .unwrap();
+
+        let parsed = NIP49URI::from_str(&uri.to_string()).unwrap();
+        assert_eq!(parsed, uri);
+        assert_eq!(parsed.secret, "a secret&with=symbols");
+        assert_eq!(parsed.budget(), None);
+    }
+
+    #[test]
+    fn test_build_without_commands() {
+        let builder = NIP49URI::builder(
+            PublicKey::from_str(PUBKEY).unwrap(),
+            Url::parse("wss://relay.damus.io").unwrap(),
+            "b8a30fafa48d4795b6c0eec169a383de".to_string(),
+        );
+        assert!(builder.clone().build().is_err());
+        assert!(builder
+            .optional_command(Method::GetBalance)
+            .build()
+            .is_err());
+    }
+}
@@ -0,0 +1,175 @@
+use core::fmt;
+use core::str::FromStr;
+
+use nostr::key::{PublicKey, SecretKey};
+use nostr::prelude::url::form_urlencoded::byte_serialize;
+use nostr::Url;
+
+use crate::prelude::*;
+
+/// NWC URI Scheme
+pub const NWC_URI_SCHEME: &str = "nostr+walletconnect";
+
+/// Scheme used by some older wallets, e.g. Alby
+const LEGACY_NWC_URI_SCHEME: &str = "nostrwalletconnect";
+
+/// A NIP-47 `nostr+walletconnect://` URI, the connection a wallet service hands to an app.
+///
+/// The secret lets the app spend from the wallet so it is redacted from
+/// [`fmt::Debug`] output and shouldn't be logged or displayed.
+#[derive(Clone, PartialEq, Eq)]
+pub struct NostrWalletConnectUri {
+    public_key: PublicKey,
+    relays: Vec<Url>,
+    secret: SecretKey,
+    lud16: Option<String>,
+}
+
+impl NostrWalletConnectUri {
+    /// The pubkey of the wallet service
+    pub fn public_key(&self) -> PublicKey {
+        self.public_key
+    }
+
+    /// The relays the wallet service listens on, there is always at least one
+    pub fn relays(&self) -> &[Url] {
+        &self.relays
+    }
+
+    /// Sensitive, the key the app signs its requests with
+    pub fn secret(&self) -> &SecretKey {
+        &self.secret
+    }
+
+    /// A lightning address the app can set on the user's profile
+    pub fn lud16(&self) -> Option<&str> {
+        self.lud16.as_deref()
+    }
+}
+
+impl fmt::Debug for NostrWalletConnectUri {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NostrWalletConnectUri")
+            .field("public_key", &self.public_key.to_hex())
+            .field(
+                "relays",
+                &self.relays.iter().map(|r| r.as_str()).collect::<Vec<_>>(),
+            )
+            .field("secret", &"<redacted>")
+            .field("lud16", &self.lud16)
+            .finish()
+    }
+}
+
+impl fmt::Display for NostrWalletConnectUri {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{NWC_URI_SCHEME}://{}?", self.public_key.to_hex())?;
+        for relay in &self.relays {
+            let relay: String = byte_serialize(relay.as_str().as_bytes()).collect();
+            write!(f, "relay={relay}&")?;
+        }
+        write!(f, "secret={}", self.secret.to_secret_hex())?;
+        if let Some(lud16) = &self.lud16 {
+            let lud16: String = byte_serialize(lud16.as_bytes()).collect();
+            write!(f, "&lud16={lud16}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for NostrWalletConnectUri {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let url = Url::parse(s).map_err(|_| ())?;
+        if url.scheme() != NWC_URI_SCHEME && url.scheme() != LEGACY_NWC_URI_SCHEME {
+            return Err(());
+        }
+
+        let public_key = url
+            .host_str()
+            .and_then(|key| PublicKey::from_hex(key).ok())
+            .ok_or(())?;
+
+        let mut relays = vec![];
+        let mut secret = None;
+        let mut lud16 = None;
+        for (key, value) in url.query_pairs() {
+            match key.as_ref() {
+                "relay" => relays.push(Url::parse(&value).map_err(|_| ())?),
+                "secret" if secret.is_none() => {
+                    secret = Some(SecretKey::from_hex(value.as_ref()).map_err(|_| ())?);
+                }
+                "secret" => return Err(()),
+                "lud16" => lud16 = Some(value.to_string()),
+                _ => {}
+            }
+        }
+
+        if relays.is_empty() {
+            return Err(());
+        }
+
+        Ok(Self {
+            public_key,
+            relays,
+            secret: secret.ok_or(())?,
+            lud16,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::str::FromStr;
+
+    use super::*;
+
+    const PUBKEY: &str = "b889ff5b1513b641e2a139f661a661364979c5beee91842f8f0ef42ab558e9d4";
+    const SECRET: &str = "71a8c14c1407c113601079c4302dab36460f0ccd0ad506f1f2dc73b5100e4f3c";
+
+    #[test]
+    fn test_parse_nwc() {
+        let str = format!("nostr+walletconnect://{PUBKEY}?relay=wss%3A%2F%2Frelay.damus.io&secret={SECRET}&lud16=ben%40opreturnbot.com");
+        let uri = NostrWalletConnectUri::from_str(&str).unwrap();
+        assert_eq!(uri.public_key().to_hex(), PUBKEY);
+        assert_eq!(uri.relays(), [Url::parse("wss://relay.damus.io").unwrap()]);
+        assert_eq!(uri.secret().to_secret_hex(), SECRET);
+        assert_eq!(uri.lud16(), Some("ben@opreturnbot.com"));
+        assert_eq!(uri.to_string(), str.replace("damus.io&", "damus.io%2F&"));
+
+        let debug = format!("{uri:?}");
+        assert!(!debug.contains(SECRET));
+    }
+
+    #[test]
+    fn test_parse_nwc_multiple_relays() {
+        let str = format!("nostrwalletconnect://{PUBKEY}?relay=wss://relay.damus.io&relay=wss://nos.lol&secret={SECRET}");
+        let uri = NostrWalletConnectUri::from_str(&str).unwrap();
+        assert_eq!(uri.relays().len(), 2);
+        assert_eq!(uri.lud16(), None);
+        assert!(uri.to_string().starts_with(NWC_URI_SCHEME));
+
+        let roundtrip = NostrWalletConnectUri::from_str(&uri.to_string()).unwrap();
+        assert_eq!(roundtrip, uri);
+    }
+
+    #[test]
+    fn test_invalid_nwc() {
+        // no secret
+        assert!(NostrWalletConnectUri::from_str(&format!(
+            "nostr+walletconnect://{PUBKEY}?relay=wss://relay.damus.io"
+        ))
+        .is_err());
+        // no relay
+        assert!(NostrWalletConnectUri::from_str(&format!(
+            "nostr+walletconnect://{PUBKEY}?secret={SECRET}"
+        ))
+        .is_err());
+        // wallet auth is the other direction
+        assert!(NostrWalletConnectUri::from_str(&format!(
+            "nostr+walletauth://{PUBKEY}?relay=wss://relay.damus.io&secret={SECRET}"
+        ))
+        .is_err());
+    }
+}
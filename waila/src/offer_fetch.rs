@@ -0,0 +1,283 @@
+use core::fmt;
+
+use lightning::offers::invoice::Bolt12Invoice;
+use lightning::offers::offer::{Amount, Offer};
+
+/// Fetches a BOLT12 invoice for an offer, wallets implement this with their
+/// onion messenger so waila never has to talk to the network itself.
+// the future isn't required to be `Send` so fetchers can be used on wasm
+#[allow(async_fn_in_trait)]
+pub trait OfferInvoiceFetcher {
+    type Error: fmt::Debug;
+
+    /// Sends an invoice request for the offer and returns the invoice the recipient replied with.
+    /// `amount_msats` is set when it has to be included in the invoice request.
+    async fn fetch_invoice(
+        &self,
+        offer: &Offer,
+        amount_msats: Option<u64>,
+    ) -> Result<Bolt12Invoice, Self::Error>;
+}
+
+/// Errors that can happen while requesting an invoice for an offer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InvoiceRequestError<E> {
+    /// The [`OfferInvoiceFetcher`] failed to fetch the invoice
+    Fetch(E),
+    /// The offer has no amount so one has to be given
+    AmountRequired,
+    /// The offer is in a currency other than bitcoin, so the amount has to be given in msats
+    UnsupportedCurrency,
+    /// The given amount is less than the offer asks for
+    AmountTooLow,
+    /// The offer has already expired
+    OfferExpired,
+    /// The invoice is not for the requested offer
+    OfferMismatch,
+    /// The invoice is for a chain the offer doesn't support
+    WrongChain,
+    /// The invoice amount is not the amount that was requested
+    WrongAmount { expected: u64, actual: u64 },
+    /// The invoice has already expired
+    InvoiceExpired,
+    /// The invoice has no blinded paths to pay it through
+    NoPaymentPaths,
+}
+
+impl<E: fmt::Debug> fmt::Display for InvoiceRequestError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E: fmt::Debug> std::error::Error for InvoiceRequestError<E> {}
+
+/// The amount in msats the invoice must be for, `amount_msats` is what the user chose to pay.
+fn requested_amount<E>(
+    offer: &Offer,
+    amount_msats: Option<u64>,
+) -> Result<u64, InvoiceRequestError<E>> {
+    match (offer.amount(), amount_msats) {
+        (None, None) => Err(InvoiceRequestError::AmountRequired),
+        (Some(Amount::Currency { .. }), None) => Err(InvoiceRequestError::UnsupportedCurrency),
+        (Some(Amount::Bitcoin { amount_msats }), None) => Ok(*amount_msats),
+        (Some(Amount::Bitcoin { amount_msats: min }), Some(amount)) if amount < *min => {
+            Err(InvoiceRequestError::AmountTooLow)
+        }
+        (_, Some(amount)) => Ok(amount),
+    }
+}
+
+/// Checks that the invoice is a valid reply to a request for `amount_msats` from the offer.
+/// The invoice's signature is already verified when it is parsed.
+fn validate_invoice<E>(
+    offer: &Offer,
+    invoice: &Bolt12Invoice,
+    amount_msats: u64,
+) -> Result<(), InvoiceRequestError<E>> {
+    let same_offer = invoice.signing_pubkey() == offer.signing_pubkey()
+        && invoice.description() == offer.description()
+        && invoice.issuer() == offer.issuer()
+        && invoice.metadata() == offer.metadata()
+        && invoice.amount() == offer.amount()
+        && invoice.message_paths() == offer.paths();
+    if !same_offer {
+        return Err(InvoiceRequestError::OfferMismatch);
+    }
+    if !offer.supports_chain(invoice.chain()) {
+        return Err(InvoiceRequestError::WrongChain);
+    }
+    if invoice.amount_msats() != amount_msats {
+        return Err(InvoiceRequestError::WrongAmount {
+            expected: amount_msats,
+            actual: invoice.amount_msats(),
+        });
+    }
+    #[cfg(feature = "std")]
+    if invoice.is_expired() {
+        return Err(InvoiceRequestError::InvoiceExpired);
+    }
+    if invoice.payment_paths().is_empty() {
+        return Err(InvoiceRequestError::NoPaymentPaths);
+    }
+
+    Ok(())
+}
+
+/// Requests an invoice for the offer through the wallet's `fetcher` and validates it
+/// against the offer, so a recipient can't get paid for a different amount or offer.
+///
+/// `amount_msats` is required for offers without an amount and must be at least the
+/// offer's amount when both are set.
+pub async fn request_invoice<F: OfferInvoiceFetcher>(
+    fetcher: &F,
+    offer: &Offer,
+    amount_msats: Option<u64>,
+) -> Result<Bolt12Invoice, InvoiceRequestError<F::Error>> {
+    let expected = requested_amount(offer, amount_msats)?;
+    #[cfg(feature = "std")]
+    if offer.is_expired() {
+        return Err(InvoiceRequestError::OfferExpired);
+    }
+
+    let invoice = fetcher
+        .fetch_invoice(offer, amount_msats)
+        .await
+        .map_err(InvoiceRequestError::Fetch)?;
+    validate_invoice(offer, &invoice, expected)?;
+
+    Ok(invoice)
+}
+
+#[cfg(test)]
+mod test {
+    use core::convert::Infallible;
+
+    use bitcoin::secp256k1::{KeyPair, PublicKey, Secp256k1, SecretKey};
+    use futures::executor::block_on;
+    use lightning::blinded_path::{BlindedHop, BlindedPath};
+    use lightning::ln::features::BlindedHopFeatures;
+    use lightning::ln::PaymentHash;
+    use lightning::offers::invoice::BlindedPayInfo;
+    use lightning::offers::offer::OfferBuilder;
+
+    use super::*;
+    use crate::prelude::*;
+
+    fn keys(byte: u8) -> KeyPair {
+        KeyPair::from_secret_key(
+            &Secp256k1::new(),
+            &SecretKey::from_slice(&[byte; 32]).unwrap(),
+        )
+    }
+
+    fn payment_path() -> (BlindedPayInfo, BlindedPath) {
+        let pubkey = keys(3).public_key();
+        let path = BlindedPath {
+            introduction_node_id: pubkey,
+            blinding_point: pubkey,
+            blinded_hops: vec![BlindedHop {
+                blinded_node_id: pubkey,
+                encrypted_payload: vec![0; 32],
+            }],
+        };
+        let payinfo = BlindedPayInfo {
+            fee_base_msat: 1,
+            fee_proportional_millionths: 1_000,
+            cltv_expiry_delta: 42,
+            htlc_minimum_msat: 100,
+            htlc_maximum_msat: 1_000_000_000,
+            features: BlindedHopFeatures::empty(),
+        };
+        (payinfo, path)
+    }
+
+    /// Replies to invoice requests as the recipient would, optionally for another offer
+    struct TestFetcher {
+        reply_offer: Option<Offer>,
+    }
+
+    impl OfferInvoiceFetcher for TestFetcher {
+        type Error = ();
+
+        async fn fetch_invoice(
+            &self,
+            offer: &Offer,
+            amount_msats: Option<u64>,
+        ) -> Result<Bolt12Invoice, Self::Error> {
+            let secp = Secp256k1::new();
+            let offer = self.reply_offer.as_ref().unwrap_or(offer);
+            let mut builder = offer
+                .request_invoice(vec![1; 32], keys(1).public_key())
+                .map_err(|_| ())?;
+            if let Some(amount_msats) = amount_msats {
+                builder = builder.amount_msats(amount_msats).map_err(|_| ())?;
+            }
+            let request = builder
+                .build()
+                .map_err(|_| ())?
+                .sign::<_, Infallible>(|r| {
+                    Ok(secp.sign_schnorr_no_aux_rand(r.tagged_hash().as_digest(), &keys(1)))
+                })
+                .map_err(|_| ())?;
+
+            // created now so it doesn't expire
+            let now = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap();
+            request
+                .respond_with_no_std(vec![payment_path()], PaymentHash([0; 32]), now)
+                .map_err(|_| ())?
+                .build()
+                .map_err(|_| ())?
+                .sign::<_, Infallible>(|i| {
+                    Ok(secp.sign_schnorr_no_aux_rand(i.tagged_hash().as_digest(), &keys(2)))
+                })
+                .map_err(|_| ())
+        }
+    }
+
+    fn offer(amount_msats: Option<u64>, signing_pubkey: PublicKey) -> Offer {
+        let mut builder = OfferBuilder::new("coffee".to_string(), signing_pubkey);
+        if let Some(amount_msats) = amount_msats {
+            builder = builder.amount_msats(amount_msats);
+        }
+        builder.build().unwrap()
+    }
+
+    #[test]
+    fn test_request_invoice() {
+        let fetcher = TestFetcher { reply_offer: None };
+
+        let offer = offer(Some(10_000), keys(2).public_key());
+        let invoice = block_on(request_invoice(&fetcher, &offer, None)).unwrap();
+        assert_eq!(invoice.amount_msats(), 10_000);
+
+        // paying more than asked is fine
+        let invoice = block_on(request_invoice(&fetcher, &offer, Some(20_000))).unwrap();
+        assert_eq!(invoice.amount_msats(), 20_000);
+
+        assert_eq!(
+            block_on(request_invoice(&fetcher, &offer, Some(1_000))).unwrap_err(),
+            InvoiceRequestError::AmountTooLow
+        );
+    }
+
+    #[test]
+    fn test_amount_required() {
+        let fetcher = TestFetcher { reply_offer: None };
+        let offer = offer(None, keys(2).public_key());
+        assert_eq!(
+            block_on(request_invoice(&fetcher, &offer, None)).unwrap_err(),
+            InvoiceRequestError::AmountRequired
+        );
+        assert!(block_on(request_invoice(&fetcher, &offer, Some(5_000))).is_ok());
+    }
+
+    #[test]
+    fn test_invoice_for_other_offer() {
+        let other = offer(Some(10_000), keys(2).public_key());
+        let fetcher = TestFetcher {
+            reply_offer: Some(offer(Some(1_000), keys(2).public_key())),
+        };
+        assert_eq!(
+            block_on(request_invoice(&fetcher, &other, None)).unwrap_err(),
+            InvoiceRequestError::OfferMismatch
+        );
+    }
+
+    #[test]
+    fn test_validate_wrong_amount() {
+        let fetcher = TestFetcher { reply_offer: None };
+        let offer = offer(Some(10_000), keys(2).public_key());
+        let invoice = block_on(fetcher.fetch_invoice(&offer, None)).unwrap();
+        assert_eq!(
+            validate_invoice::<()>(&offer, &invoice, 15_000),
+            Err(InvoiceRequestError::WrongAmount {
+                expected: 15_000,
+                actual: 10_000
+            })
+        );
+    }
+}
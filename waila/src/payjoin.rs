@@ -0,0 +1,176 @@
+use core::convert::TryInto;
+
+use bitcoin::bech32::{u5, FromBase32};
+use nostr::Url;
+
+use crate::prelude::*;
+
+/// The bech32 alphabet, BIP-77 fragment parameters are bech32 strings without a checksum
+const BECH32_CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+
+/// Which version of the payjoin protocol an endpoint speaks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PayjoinVersion {
+    /// BIP-78, the receiver runs an http server the sender posts to
+    V1,
+    /// BIP-77, the receiver is reached asynchronously through a directory over OHTTP
+    V2,
+}
+
+/// Decodes a `<hrp>1<data>` fragment parameter, the hrp is matched case insensitively
+fn decode_fragment_param(param: &str, hrp: &str) -> Option<Vec<u8>> {
+    let param = param.to_ascii_lowercase();
+    let data = param.strip_prefix(hrp)?.strip_prefix('1')?;
+    let data = data
+        .bytes()
+        .map(|c| {
+            let value = BECH32_CHARSET.iter().position(|&b| b == c)?;
+            u5::try_from_u8(value as u8).ok()
+        })
+        .collect::<Option<Vec<u5>>>()?;
+    Vec::<u8>::from_base32(&data).ok()
+}
+
+/// The `pj` endpoint of a BIP-21 URI along with the BIP-77 parameters in its fragment
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PayjoinEndpoint {
+    url: Url,
+    receiver_key: Option<Vec<u8>>,
+    ohttp_keys: Option<Vec<u8>>,
+    expiry: Option<u64>,
+}
+
+impl PayjoinEndpoint {
+    pub fn new(url: Url) -> Self {
+        let mut receiver_key = None;
+        let mut ohttp_keys = None;
+        let mut expiry = None;
+        // the parameters used to be separated by `&` before BIP-77 settled on `+`
+        for param in url.fragment().unwrap_or_default().split(['+', '&']) {
+            if let Some(key) = decode_fragment_param(param, "rk") {
+                receiver_key = Some(key);
+            } else if let Some(keys) = decode_fragment_param(param, "oh") {
+                ohttp_keys = Some(keys);
+            } else if let Some(exp) = decode_fragment_param(param, "ex") {
+                expiry = exp.try_into().ok().map(u32::from_be_bytes).map(u64::from);
+            }
+        }
+
+        Self {
+            url,
+            receiver_key,
+            ohttp_keys,
+            expiry,
+        }
+    }
+
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    /// Endpoints with a receiver key in their fragment are BIP-77 mailboxes
+    pub fn version(&self) -> PayjoinVersion {
+        if self.receiver_key.is_some() {
+            PayjoinVersion::V2
+        } else {
+            PayjoinVersion::V1
+        }
+    }
+
+    /// The receiver's public key for a BIP-77 session
+    pub fn receiver_key(&self) -> Option<&[u8]> {
+        self.receiver_key.as_deref()
+    }
+
+    /// The directory's OHTTP key configuration, if the URI included it
+    pub fn ohttp_keys(&self) -> Option<&[u8]> {
+        self.ohttp_keys.as_deref()
+    }
+
+    /// When the BIP-77 session expires, in seconds since the unix epoch
+    pub fn expiry(&self) -> Option<u64> {
+        self.expiry
+    }
+
+    #[cfg(feature = "std")]
+    pub fn is_expired(&self) -> bool {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        self.expiry.map_or(false, |expiry| expiry <= now)
+    }
+
+    pub fn is_onion(&self) -> bool {
+        self.url
+            .host_str()
+            .map_or(false, |host| host.ends_with(".onion"))
+    }
+
+    /// BIP-78 only allows https, or plain http to an onion service
+    pub fn is_secure(&self) -> bool {
+        match self.url.scheme() {
+            "https" => true,
+            "http" => self.is_onion(),
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bitcoin::bech32::ToBase32;
+
+    use super::*;
+
+    fn encode_fragment_param(hrp: &str, data: &[u8]) -> String {
+        let data: String = data
+            .to_base32()
+            .into_iter()
+            .map(|u| BECH32_CHARSET[usize::from(u.to_u8())] as char)
+            .collect();
+        format!("{hrp}1{data}").to_uppercase()
+    }
+
+    #[test]
+    fn test_v1_endpoint() {
+        let endpoint = PayjoinEndpoint::new(Url::parse("https://example.com/pj").unwrap());
+        assert_eq!(endpoint.version(), PayjoinVersion::V1);
+        assert_eq!(endpoint.expiry(), None);
+        assert!(endpoint.is_secure());
+        assert!(!endpoint.is_onion());
+
+        let endpoint = PayjoinEndpoint::new(Url::parse("http://example.com/pj").unwrap());
+        assert!(!endpoint.is_secure());
+
+        let endpoint = PayjoinEndpoint::new(
+            Url::parse("http://abcdefghijklmnopqrstuvwxyzabcdefghijklmnopqrstuvwxyz2345.onion/pj")
+                .unwrap(),
+        );
+        assert!(endpoint.is_secure());
+        assert!(endpoint.is_onion());
+    }
+
+    #[test]
+    fn test_v2_endpoint() {
+        let receiver_key = [2; 33];
+        let ohttp_keys = [1; 34];
+        let url = format!(
+            "https://payjo.in/TXJCGKTKXLUUZ#{}+{}+{}",
+            encode_fragment_param("rk", &receiver_key),
+            encode_fragment_param("oh", &ohttp_keys),
+            encode_fragment_param("ex", &1_720_547_781u32.to_be_bytes()),
+        );
+        let endpoint = PayjoinEndpoint::new(Url::parse(&url).unwrap());
+        assert_eq!(endpoint.version(), PayjoinVersion::V2);
+        assert_eq!(endpoint.receiver_key(), Some(&receiver_key[..]));
+        assert_eq!(endpoint.ohttp_keys(), Some(&ohttp_keys[..]));
+        assert_eq!(endpoint.expiry(), Some(1_720_547_781));
+        #[cfg(feature = "std")]
+        assert!(endpoint.is_expired());
+
+        // not bech32
+        let endpoint = PayjoinEndpoint::new(Url::parse("https://payjo.in/abc#RK1B").unwrap());
+        assert_eq!(endpoint.version(), PayjoinVersion::V1);
+    }
+}
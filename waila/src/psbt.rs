@@ -0,0 +1,107 @@
+use base64::Engine;
+use bitcoin::bip32::ChildNumber;
+use bitcoin::hashes::hex::FromHex;
+use bitcoin::psbt::PartiallySignedTransaction;
+use bitcoin::{Amount, Network};
+
+use crate::prelude::*;
+
+/// The magic bytes every PSBT starts with, `psbt` followed by 0xff
+const PSBT_MAGIC: [u8; 5] = [0x70, 0x73, 0x62, 0x74, 0xff];
+
+/// Parses a base64 or hex encoded PSBT
+pub(crate) fn parse_psbt(str: &str) -> Option<PartiallySignedTransaction> {
+    let str = str.trim();
+    let bytes = if str
+        .get(..10)
+        .is_some_and(|p| p.eq_ignore_ascii_case("70736274ff"))
+    {
+        Vec::<u8>::from_hex(str).ok()?
+    } else {
+        base64::engine::general_purpose::STANDARD.decode(str).ok()?
+    };
+
+    if !bytes.starts_with(&PSBT_MAGIC) {
+        return None;
+    }
+
+    PartiallySignedTransaction::deserialize(&bytes).ok()
+}
+
+/// The sum of all the outputs of the unsigned transaction, including change
+pub(crate) fn total_output_amount(psbt: &PartiallySignedTransaction) -> Amount {
+    let sats = psbt.unsigned_tx.output.iter().map(|o| o.value).sum();
+    Amount::from_sat(sats)
+}
+
+/// The networks we can derive from the PSBT, from its global xpubs and the
+/// BIP-44 style coin types of its key derivation paths.
+pub(crate) fn psbt_networks(psbt: &PartiallySignedTransaction) -> Vec<Network> {
+    let mut networks: Vec<Network> = psbt.xpub.keys().map(|xpub| xpub.network).collect();
+
+    let paths = psbt
+        .xpub
+        .values()
+        .chain(psbt.inputs.iter().flat_map(|i| i.bip32_derivation.values()))
+        .chain(
+            psbt.outputs
+                .iter()
+                .flat_map(|o| o.bip32_derivation.values()),
+        )
+        .map(|(_, path)| path);
+    for path in paths {
+        // only the coin types of hardened purpose paths mean anything
+        let network = match path.as_ref() {
+            [ChildNumber::Hardened { .. }, ChildNumber::Hardened { index: 0 }, ..] => {
+                Network::Bitcoin
+            }
+            [ChildNumber::Hardened { .. }, ChildNumber::Hardened { index: 1 }, ..] => {
+                Network::Testnet
+            }
+            _ => continue,
+        };
+        networks.push(network);
+    }
+
+    networks.sort();
+    networks.dedup();
+    networks
+}
+
+#[cfg(test)]
+mod test {
+    use bitcoin::{Amount, Network};
+
+    use super::*;
+
+    const PSBT: &str = "cHNidP8BAHECAAAAASaBcTce3/KF6Tet7qSze3gADAVmy7OtZGQXE8pCFxv2AAAAAAD9////AlDDAAAAAAAAFgAUdR526BmRltRUlBxF0bOjI/FDO9aoYQAAAAAAABYAFHUedugZkZbUVJQcRdGzoyPxQzvWAAAAAAAAAAA=";
+
+    #[test]
+    fn test_parse_psbt() {
+        let psbt = parse_psbt(PSBT).unwrap();
+        assert_eq!(psbt.unsigned_tx.input.len(), 1);
+        assert_eq!(total_output_amount(&psbt), Amount::from_sat(75_000));
+        assert!(psbt_networks(&psbt).is_empty());
+
+        let hex = parse_psbt("70736274ff0100710200000001268171371edff285e937adeea4b37b78000c0566cbb3ad64641713ca42171bf60000000000fdffffff0250c3000000000000160014751e76e8199196d454941c45d1b3a323f1433bd6a861000000000000160014751e76e8199196d454941c45d1b3a323f1433bd60000000000000000").unwrap();
+        assert_eq!(hex, psbt);
+    }
+
+    #[test]
+    fn test_psbt_networks() {
+        let xpub = parse_psbt("cHNidP8BAHECAAAAASaBcTce3/KF6Tet7qSze3gADAVmy7OtZGQXE8pCFxv2AAAAAAD9////AlDDAAAAAAAAFgAUdR526BmRltRUlBxF0bOjI/FDO9aoYQAAAAAAABYAFHUedugZkZbUVJQcRdGzoyPxQzvWAAAAAE8BBDWHzwO5Y0KNgAAAAHv/HdbvwsE4mr4BtUv/nVI9Lc2mhNPAsl7Br2NSmkaQAn60Dtg6twj231jWRnvo+5LrQcH8ZlKaPx1sL5LuZJD7EOKGe7ZUAACAAQAAgAAAAIAAAAAA").unwrap();
+        assert_eq!(psbt_networks(&xpub), vec![Network::Testnet]);
+
+        let path = parse_psbt("cHNidP8BAHECAAAAASaBcTce3/KF6Tet7qSze3gADAVmy7OtZGQXE8pCFxv2AAAAAAD9////AlDDAAAAAAAAFgAUdR526BmRltRUlBxF0bOjI/FDO9aoYQAAAAAAABYAFHUedugZkZbUVJQcRdGzoyPxQzvWAAAAAAAiBgJZVwwA1el4C7AUSB82H2Pnptf3MX0Js6TznlC1Yhdrjhjihnu2VAAAgAAAAIAAAACAAAAAAAAAAAAAAAA=").unwrap();
+        assert_eq!(psbt_networks(&path), vec![Network::Bitcoin]);
+    }
+
+    #[test]
+    fn test_invalid_psbt() {
+        assert!(parse_psbt("aGVsbG8gd29ybGQ=").is_none());
+        assert!(parse_psbt("70736274ff").is_none());
+        assert!(parse_psbt(&PSBT[..40]).is_none());
+        // multi byte chars around the hex prefix length
+        assert!(parse_psbt("ab₿₿₿").is_none());
+    }
+}
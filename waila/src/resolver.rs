@@ -0,0 +1,1856 @@
+use core::convert::TryFrom;
+use core::fmt;
+use core::str::FromStr;
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use bitcoin::secp256k1::PublicKey;
+use bitcoin::Network;
+use dnssec_prover::query::{ProofBuilder, QueryBuf};
+use dnssec_prover::rr::{Name, RR, TXT_TYPE};
+use dnssec_prover::ser::parse_rr_stream;
+use dnssec_prover::validation::verify_rr_stream;
+use futures::future::{try_join_all, AbortHandle, Abortable};
+use itertools::Itertools;
+use lnurl::pay::PayResponse;
+use lnurl::{LnUrlResponse, Response};
+use nostr::nips::nip47::Method;
+use nostr::{JsonUtil, Url};
+use serde::Deserialize;
+
+pub use crate::http::{HttpClient, HttpResponse, ReqwestClient};
+use crate::{
+    Bip353Name, FedimintInviteCode, LnUrlAuthRequest, LnUrlAuthSigner, NIP49Budget,
+    Nip05Identifier, PayNym, PayjoinEndpoint, PayjoinVersion, PaymentCode, PaymentParams,
+};
+
+/// Default timeout for a single network request made while resolving
+pub const DEFAULT_RESOLVER_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// SOCKS5 proxy of a local Tor daemon, DNS resolution happens through Tor as well
+pub const DEFAULT_TOR_PROXY: &str = "socks5h://127.0.0.1:9050";
+
+/// Default DNS-over-HTTPS endpoint used for BIP-353 lookups, must support RFC 8484 wire format queries
+pub const DEFAULT_DOH_ENDPOINT: &str = "https://cloudflare-dns.com/dns-query";
+
+/// Default paynym.rs API endpoint used to look up PayNym handles
+pub const DEFAULT_PAYNYM_ENDPOINT: &str = "https://paynym.rs/api/v1/nym";
+
+/// Default time LNURL-pay parameters are cached for
+pub const DEFAULT_LNURL_CACHE_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// Default time BIP-353 payment instructions are cached for
+pub const DEFAULT_BIP353_CACHE_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Default time Cashu mint info and federation configs are cached for
+pub const DEFAULT_MINT_INFO_CACHE_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// How long each kind of resolved result is cached for, `None` disables caching for that kind.
+///
+/// The cache is compiled out when targeting `wasm32` as there is no monotonic clock
+/// available, every lookup makes its requests again there whatever is configured here.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheTtl {
+    /// LNURL-pay parameters, withdraw and channel requests are single use and never cached
+    pub lnurl_pay: Option<Duration>,
+    /// BIP-353 payment instructions, never cached for longer than their DNS records' TTL
+    pub bip353: Option<Duration>,
+    /// Cashu mint info and Fedimint federation configs
+    pub mint_info: Option<Duration>,
+}
+
+impl Default for CacheTtl {
+    fn default() -> Self {
+        Self {
+            lnurl_pay: Some(DEFAULT_LNURL_CACHE_TTL),
+            bip353: Some(DEFAULT_BIP353_CACHE_TTL),
+            mint_info: Some(DEFAULT_MINT_INFO_CACHE_TTL),
+        }
+    }
+}
+
+impl CacheTtl {
+    /// Disables caching for every kind of result
+    pub fn disabled() -> Self {
+        Self {
+            lnurl_pay: None,
+            bip353: None,
+            mint_info: None,
+        }
+    }
+}
+
+/// A simple thread safe cache where every entry expires after the given ttl
+#[derive(Debug)]
+struct TtlCache<K, V> {
+    #[cfg(not(target_arch = "wasm32"))]
+    entries: Mutex<HashMap<K, (std::time::Instant, V)>>,
+    #[cfg(target_arch = "wasm32")]
+    _entries: core::marker::PhantomData<(K, V)>,
+}
+
+impl<K, V> Default for TtlCache<K, V> {
+    fn default() -> Self {
+        Self {
+            #[cfg(not(target_arch = "wasm32"))]
+            entries: Mutex::new(HashMap::new()),
+            #[cfg(target_arch = "wasm32")]
+            _entries: core::marker::PhantomData,
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl<K: Eq + Hash, V: Clone> TtlCache<K, V> {
+    fn get(&self, key: &K) -> Option<V> {
+        let mut entries = self.entries.lock().ok()?;
+        match entries.get(key) {
+            Some((expires_at, value)) if *expires_at > std::time::Instant::now() => {
+                Some(value.clone())
+            }
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn insert(&self, key: K, value: V, ttl: Option<Duration>) {
+        let Some(ttl) = ttl else { return };
+        if let Ok(mut entries) = self.entries.lock() {
+            let now = std::time::Instant::now();
+            entries.retain(|_, (expires_at, _)| *expires_at > now);
+            entries.insert(key, (now + ttl, value));
+        }
+    }
+
+    fn clear(&self) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.clear();
+        }
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+impl<K, V> TtlCache<K, V> {
+    fn get(&self, _key: &K) -> Option<V> {
+        None
+    }
+
+    fn insert(&self, _key: K, _value: V, _ttl: Option<Duration>) {}
+
+    fn clear(&self) {}
+}
+
+/// The cached results of a [`Resolver`], one cache per kind of result
+#[derive(Debug, Default)]
+struct ResolverCache {
+    lnurl_pay: TtlCache<String, PayResponse>,
+    bip353: TtlCache<String, PaymentParams<'static>>,
+    federation: TtlCache<String, FederationInfo>,
+    cashu_mint_info: TtlCache<String, CashuMintInfo>,
+}
+
+impl ResolverCache {
+    fn clear(&self) {
+        self.lnurl_pay.clear();
+        self.bip353.clear();
+        self.federation.clear();
+        self.cashu_mint_info.clear();
+    }
+}
+
+/// Configuration shared by all resolvers
+#[derive(Debug, Clone)]
+pub struct ResolverConfig {
+    /// Timeout for each network request, `None` disables the timeout.
+    ///
+    /// The timeout is ignored when targeting `wasm32`, use [`abortable`] there instead.
+    /// Custom [`HttpClient`]s have to apply their own timeout.
+    pub timeout: Option<Duration>,
+    /// Optional proxy all requests are routed through, e.g. `socks5h://127.0.0.1:9050`
+    /// for a local Tor daemon. This is required to reach onion LNURL endpoints.
+    ///
+    /// Use the `socks5h` scheme so DNS resolution also happens through the proxy.
+    /// The proxy is ignored when targeting `wasm32`. Custom [`HttpClient`]s have to
+    /// apply their own proxy, this is then only used for nostr relays.
+    ///
+    /// Nostr relays can only be reached through a SOCKS5 proxy, with any other proxy
+    /// the nostr lookups fail with [`ResolveError::UnsupportedProxy`].
+    pub proxy: Option<String>,
+    /// How long resolved results are cached for, nothing is cached on `wasm32`
+    pub cache_ttl: CacheTtl,
+    /// DNS-over-HTTPS endpoint used for BIP-353 lookups, must support RFC 8484 wire format queries.
+    /// The DNSSEC chain is verified locally, the endpoint only has to be trusted for privacy.
+    pub doh_endpoint: String,
+    /// paynym.rs compatible API endpoint used to look up PayNym handles
+    pub paynym_endpoint: String,
+}
+
+impl Default for ResolverConfig {
+    fn default() -> Self {
+        Self {
+            timeout: Some(DEFAULT_RESOLVER_TIMEOUT),
+            proxy: None,
+            cache_ttl: CacheTtl::default(),
+            doh_endpoint: DEFAULT_DOH_ENDPOINT.to_string(),
+            paynym_endpoint: DEFAULT_PAYNYM_ENDPOINT.to_string(),
+        }
+    }
+}
+
+impl ResolverConfig {
+    /// Set the timeout for each network request
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Set the proxy all requests are routed through
+    pub fn proxy(mut self, proxy: &str) -> Self {
+        self.proxy = Some(proxy.to_string());
+        self
+    }
+
+    /// Route all requests over a local Tor daemon through [`DEFAULT_TOR_PROXY`]
+    pub fn tor(self) -> Self {
+        self.proxy(DEFAULT_TOR_PROXY)
+    }
+
+    /// Set how long resolved results are cached for
+    pub fn cache_ttl(mut self, cache_ttl: CacheTtl) -> Self {
+        self.cache_ttl = cache_ttl;
+        self
+    }
+
+    /// Set the DNS-over-HTTPS endpoint used for BIP-353 lookups
+    pub fn doh_endpoint(mut self, doh_endpoint: &str) -> Self {
+        self.doh_endpoint = doh_endpoint.to_string();
+        self
+    }
+
+    /// Set the API endpoint used to look up PayNym handles
+    pub fn paynym_endpoint(mut self, paynym_endpoint: &str) -> Self {
+        self.paynym_endpoint = paynym_endpoint.to_string();
+        self
+    }
+}
+
+/// Errors that can happen while resolving payment params
+#[derive(Debug)]
+pub enum ResolveError {
+    /// The payment params can't be resolved by this resolver
+    NotResolvable,
+    /// The request took longer than the configured timeout
+    Timeout,
+    /// The resolution was cancelled through its [`AbortHandle`]
+    Cancelled,
+    /// Error talking to the LNURL service
+    LnUrl(lnurl::Error),
+    /// Error making an http request
+    Http(reqwest::Error),
+    /// Error making an http request with a custom [`HttpClient`]
+    Client(String),
+    /// The server responded with a non 2xx status code
+    Status(u16),
+    /// The response body wasn't the expected JSON
+    Json(serde_json::Error),
+    /// The DNSSEC proof for a DNS record could not be built or verified
+    DnssecValidation,
+    /// There was no single valid BIP-353 payment instruction record
+    InvalidRecord,
+    /// The PayNym directory had no usable payment code for the handle
+    PayNymNotFound,
+    /// The [`LnUrlAuthSigner`] failed to provide a linking key or signature
+    Signer(String),
+    /// The LNURL-auth service rejected the login, with its reason
+    AuthRejected(String),
+    /// The response was missing something we asked for
+    InvalidResponse,
+    /// The guardian's config doesn't belong to the federation of the invite code
+    FederationIdMismatch,
+    /// Error talking to the nostr relays
+    Nostr(nostr_sdk::client::Error),
+    /// The NIP-05 identifier isn't registered on its domain
+    Nip05NotFound,
+    /// The nostr profile has no lightning address or LNURL to pay
+    NoPaymentTarget,
+    /// The configured proxy can't be used for nostr relays, which need a SOCKS5 proxy
+    UnsupportedProxy,
+    /// None of the relays could be connected to
+    RelayUnreachable,
+    /// The NIP-47 wallet service hasn't published its info event on the relays
+    WalletInfoNotFound,
+}
+
+impl fmt::Display for ResolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+impl std::error::Error for ResolveError {}
+
+impl From<reqwest::Error> for ResolveError {
+    fn from(e: reqwest::Error) -> Self {
+        if e.is_timeout() {
+            ResolveError::Timeout
+        } else {
+            ResolveError::Http(e)
+        }
+    }
+}
+
+impl From<nostr_sdk::client::Error> for ResolveError {
+    fn from(e: nostr_sdk::client::Error) -> Self {
+        ResolveError::Nostr(e)
+    }
+}
+
+impl From<lnurl::Error> for ResolveError {
+    fn from(e: lnurl::Error) -> Self {
+        ResolveError::LnUrl(e)
+    }
+}
+
+/// Makes a resolution future cancellable, returning the wrapped future
+/// and a handle that can be used to abort it, e.g. when the user navigates away.
+pub fn abortable<F>(
+    future: F,
+) -> (
+    impl Future<Output = Result<F::Output, ResolveError>>,
+    AbortHandle,
+)
+where
+    F: Future,
+{
+    let (handle, registration) = AbortHandle::new_pair();
+    let future = Abortable::new(future, registration);
+    let future = async move { future.await.map_err(|_| ResolveError::Cancelled) };
+    (future, handle)
+}
+
+/// The address of the SOCKS5 proxy relays are connected through. Relays can only be
+/// proxied over SOCKS5, other proxies are an error so we never silently connect directly.
+fn relay_proxy(proxy: Option<&str>) -> Result<Option<std::net::SocketAddr>, ResolveError> {
+    let proxy = match proxy {
+        Some(proxy) => proxy,
+        None => return Ok(None),
+    };
+    // browsers give us no way to proxy websockets
+    if cfg!(target_arch = "wasm32") {
+        return Err(ResolveError::UnsupportedProxy);
+    }
+
+    let url = Url::parse(proxy).map_err(|_| ResolveError::UnsupportedProxy)?;
+    if !url.scheme().starts_with("socks5") {
+        return Err(ResolveError::UnsupportedProxy);
+    }
+    url.socket_addrs(|| None)
+        .ok()
+        .and_then(|addrs| addrs.into_iter().next())
+        .map(Some)
+        .ok_or(ResolveError::UnsupportedProxy)
+}
+
+/// Headers of requests with a JSON body
+const JSON_HEADERS: &[(&str, &str)] = &[("content-type", "application/json")];
+
+/// Content type of RFC 8484 DNS-over-HTTPS wire format messages
+const DNS_MESSAGE_CONTENT_TYPE: &str = "application/dns-message";
+
+/// Verifies an RFC 9102 DNSSEC proof for the TXT records of `name` and extracts the
+/// payment instruction record from it, there must be exactly one `bitcoin:` record.
+/// Returned along with the number of seconds the record may be cached for.
+///
+/// `now` is the current unix time, the proof's signatures have to be valid at that time.
+fn bip353_record(name: &Name, proof: &[u8], now: u64) -> Result<(String, u32), ResolveError> {
+    let rrs = parse_rr_stream(proof).map_err(|_| ResolveError::DnssecValidation)?;
+    let verified = verify_rr_stream(&rrs).map_err(|_| ResolveError::DnssecValidation)?;
+    if now < verified.valid_from || now > verified.expires {
+        return Err(ResolveError::DnssecValidation);
+    }
+
+    let mut records = verified
+        .resolve_name(name)
+        .into_iter()
+        .filter_map(|rr| match rr {
+            RR::Txt(txt) => String::from_utf8(txt.data.as_vec()).ok(),
+            _ => None,
+        })
+        .filter(|txt| txt.to_lowercase().starts_with("bitcoin:"));
+
+    match (records.next(), records.next()) {
+        (Some(uri), None) => Ok((uri, verified.max_cache_ttl)),
+        _ => Err(ResolveError::InvalidRecord),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct PayNymCode {
+    claimed: bool,
+    code: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct PayNymResponse {
+    #[serde(default)]
+    codes: Vec<PayNymCode>,
+}
+
+/// Picks the payment code to use from a PayNym lookup, preferring claimed codes.
+/// Codes of versions we don't support are skipped.
+fn paynym_payment_code(response: PayNymResponse) -> Result<PaymentCode, ResolveError> {
+    let mut codes: Vec<(bool, PaymentCode)> = response
+        .codes
+        .into_iter()
+        .filter_map(|c| Some((c.claimed, PaymentCode::from_str(&c.code).ok()?)))
+        .collect();
+    // stable sort keeps the directory's order within claimed and unclaimed codes
+    codes.sort_by_key(|(claimed, _)| !claimed);
+
+    codes
+        .into_iter()
+        .next()
+        .map(|(_, code)| code)
+        .ok_or(ResolveError::PayNymNotFound)
+}
+
+/// Guardian API method returning the federation's client config
+const CLIENT_CONFIG_METHOD: &str = "client_config";
+
+/// Guardian API method returning the federation's module configs as JSON
+const MODULES_CONFIG_JSON_METHOD: &str = "modules_config_json";
+
+/// A JSON-RPC response from a guardian, without a result if the call failed
+#[derive(Debug, Deserialize)]
+struct GuardianResponse<T> {
+    result: Option<T>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FederationApiEndpoint {
+    url: String,
+    name: String,
+}
+
+/// The federation wide part of a guardian's client config,
+/// the module configs in it are consensus encoded
+#[derive(Debug, Deserialize)]
+struct FederationGlobalConfig {
+    api_endpoints: BTreeMap<u16, FederationApiEndpoint>,
+    #[serde(default)]
+    meta: BTreeMap<String, String>,
+}
+
+impl FederationGlobalConfig {
+    fn federation_id(&self) -> String {
+        crate::fedimint::federation_id(
+            self.api_endpoints
+                .iter()
+                .map(|(peer, e)| (*peer, e.url.as_str(), e.name.as_str())),
+        )
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct FederationModuleConfig {
+    kind: String,
+    /// Only set for the wallet module
+    network: Option<String>,
+}
+
+/// The JSON-RPC request guardians expect, `params` holds the method's
+/// parameters and `auth` is only needed for admin methods
+fn guardian_request(method: &str) -> Result<Vec<u8>, ResolveError> {
+    let request = serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 0,
+        "method": method,
+        "params": [{ "auth": null, "params": null }],
+    });
+    serde_json::to_vec(&request).map_err(ResolveError::Json)
+}
+
+/// Guardians serve their JSON-RPC API over http on the same url as the websocket
+fn guardian_http_url(url: &Url) -> String {
+    let url = url.as_str();
+    if let Some(rest) = url.strip_prefix("wss://") {
+        format!("https://{rest}")
+    } else if let Some(rest) = url.strip_prefix("ws://") {
+        format!("http://{rest}")
+    } else {
+        url.to_string()
+    }
+}
+
+/// What a federation says about itself, e.g. to confirm joining it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FederationInfo {
+    /// The id of the federation in hex
+    pub federation_id: String,
+    /// The `federation_name` from the federation's meta, if it set one
+    pub name: Option<String>,
+    /// The guardians' names by their peer id
+    pub guardians: Vec<(u16, String)>,
+    /// The kinds of the modules the federation runs, e.g. `ln`, `mint` and `wallet`
+    pub modules: Vec<String>,
+    /// The network of the federation's wallet module
+    pub network: Option<Network>,
+}
+
+impl FederationInfo {
+    pub fn guardian_count(&self) -> usize {
+        self.guardians.len()
+    }
+}
+
+fn federation_info(
+    federation_id: String,
+    global: FederationGlobalConfig,
+    modules: BTreeMap<u16, FederationModuleConfig>,
+) -> FederationInfo {
+    let network = modules
+        .values()
+        .filter(|m| m.kind == "wallet")
+        .find_map(|m| Network::from_str(m.network.as_deref()?).ok());
+
+    FederationInfo {
+        federation_id,
+        name: global.meta.get("federation_name").cloned(),
+        guardians: global
+            .api_endpoints
+            .into_iter()
+            .map(|(peer, endpoint)| (peer, endpoint.name))
+            .collect(),
+        modules: modules.into_values().map(|m| m.kind).collect(),
+        network,
+    }
+}
+
+/// A Cashu mint's description of itself, see NUT-06
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CashuMintInfo {
+    pub name: Option<String>,
+    /// The mint's public key in hex
+    pub pubkey: Option<String>,
+    /// The mint's implementation and version, e.g. `Nutshell/0.15.0`
+    pub version: Option<String>,
+    pub description: Option<String>,
+    pub description_long: Option<String>,
+    /// Message of the day, e.g. to announce downtime
+    pub motd: Option<String>,
+    /// The NUTs the mint supports
+    pub nuts: Vec<u16>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CashuMintInfoResponse {
+    name: Option<String>,
+    pubkey: Option<String>,
+    version: Option<String>,
+    description: Option<String>,
+    description_long: Option<String>,
+    motd: Option<String>,
+    #[serde(default)]
+    nuts: BTreeMap<String, serde_json::Value>,
+}
+
+impl From<CashuMintInfoResponse> for CashuMintInfo {
+    fn from(info: CashuMintInfoResponse) -> Self {
+        // optional NUTs say whether they are supported, minting and melting can be disabled
+        let supported = |settings: &serde_json::Value| {
+            settings.get("supported").and_then(|s| s.as_bool()) != Some(false)
+                && settings.get("disabled").and_then(|d| d.as_bool()) != Some(true)
+        };
+        let mut nuts: Vec<u16> = info
+            .nuts
+            .iter()
+            .filter(|(_, settings)| supported(settings))
+            .filter_map(|(nut, _)| nut.parse().ok())
+            .collect();
+        nuts.sort_unstable();
+
+        Self {
+            name: info.name,
+            pubkey: info.pubkey,
+            version: info.version,
+            description: info.description,
+            description_long: info.description_long,
+            motd: info.motd,
+            nuts,
+        }
+    }
+}
+
+/// Whether a Cashu proof can still be spent, see NUT-07
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum CashuProofState {
+    Unspent,
+    /// Being spent in an ongoing payment, it might still fail
+    Pending,
+    Spent,
+}
+
+#[derive(Debug, Deserialize)]
+struct CashuProofStateResponse {
+    #[serde(rename = "Y")]
+    y: PublicKey,
+    state: CashuProofState,
+}
+
+#[derive(Debug, Deserialize)]
+struct CashuCheckStateResponse {
+    states: Vec<CashuProofStateResponse>,
+}
+
+/// The states of a Cashu token's proofs, e.g. to tell the user it was already claimed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CashuTokenState {
+    /// The amount and state of each proof, in the order of the token
+    pub proofs: Vec<(u64, CashuProofState)>,
+}
+
+impl CashuTokenState {
+    /// Whether every proof can still be claimed
+    pub fn is_claimable(&self) -> bool {
+        !self.proofs.is_empty()
+            && self
+                .proofs
+                .iter()
+                .all(|(_, state)| *state == CashuProofState::Unspent)
+    }
+
+    /// Whether every proof was already spent
+    pub fn is_spent(&self) -> bool {
+        self.proofs
+            .iter()
+            .all(|(_, state)| *state == CashuProofState::Spent)
+    }
+
+    /// The sum of the proofs that can still be claimed
+    pub fn unspent_amount(&self) -> u64 {
+        self.proofs
+            .iter()
+            .filter(|(_, state)| *state == CashuProofState::Unspent)
+            .map(|(amount, _)| amount)
+            .sum()
+    }
+}
+
+/// Matches the mint's states to the proofs, mints don't have to keep the order we asked in
+fn cashu_proof_states(
+    proofs: &[(u64, PublicKey)],
+    response: CashuCheckStateResponse,
+) -> Result<Vec<(u64, CashuProofState)>, ResolveError> {
+    let states: HashMap<PublicKey, CashuProofState> = response
+        .states
+        .into_iter()
+        .map(|s| (s.y, s.state))
+        .collect();
+    proofs
+        .iter()
+        .map(|(amount, y)| {
+            Ok((
+                *amount,
+                *states.get(y).ok_or(ResolveError::InvalidResponse)?,
+            ))
+        })
+        .collect()
+}
+
+/// A NIP-05 `nostr.json` response
+#[derive(Debug, Deserialize)]
+struct Nip05Response {
+    names: HashMap<String, String>,
+    #[serde(default)]
+    relays: HashMap<String, Vec<String>>,
+}
+
+/// The pubkey the identifier's domain lists for it, along with its suggested relays
+fn nip05_profile(
+    id: &Nip05Identifier,
+    response: Nip05Response,
+) -> Result<(nostr::PublicKey, Vec<String>), ResolveError> {
+    let hex = response
+        .names
+        .get(id.name())
+        .ok_or(ResolveError::Nip05NotFound)?;
+    let pubkey = nostr::PublicKey::from_hex(hex).map_err(|_| ResolveError::InvalidResponse)?;
+    let relays = response.relays.get(hex).cloned().unwrap_or_default();
+    Ok((pubkey, relays))
+}
+
+/// The lightning payment target of a kind-0 profile, preferring the lightning address
+fn nostr_payment_target(
+    metadata: &nostr::Metadata,
+) -> Result<PaymentParams<'static>, ResolveError> {
+    metadata
+        .lud16
+        .iter()
+        .chain(metadata.lud06.iter())
+        .filter_map(|target| PaymentParams::from_str(target.trim()).ok())
+        .find(|params| params.lnurl().is_some())
+        .ok_or(ResolveError::NoPaymentTarget)
+}
+
+/// What a NIP-47 connection can be used for, e.g. to validate a connection string before saving it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NwcCapabilities {
+    /// The supported methods, for NWA the ones the app requires and optionally uses
+    pub methods: Vec<Method>,
+    /// Methods the wallet service advertises that waila doesn't know about
+    pub unknown_methods: Vec<String>,
+    /// The spending budget, only NWA URIs specify one
+    pub budget: Option<NIP49Budget>,
+}
+
+impl NwcCapabilities {
+    pub fn supports(&self, method: Method) -> bool {
+        self.methods.contains(&method)
+    }
+}
+
+/// Parses a NIP-47 info event's content, the supported methods separated by spaces
+fn nwc_capabilities(content: &str) -> NwcCapabilities {
+    let (methods, unknown_methods): (Vec<_>, Vec<_>) = content
+        .split_whitespace()
+        .unique()
+        .map(|method| Method::from_str(method).map_err(|_| method.to_string()))
+        .partition(Result::is_ok);
+
+    NwcCapabilities {
+        methods: methods.into_iter().filter_map(Result::ok).collect(),
+        unknown_methods: unknown_methods
+            .into_iter()
+            .filter_map(Result::err)
+            .collect(),
+        budget: None,
+    }
+}
+
+/// Why a payjoin endpoint can't be used, the sender should fall back to a plain spend
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PayjoinUnusable {
+    /// The endpoint is neither https nor an onion service
+    InsecureEndpoint,
+    /// The endpoint is an onion service but no proxy is configured to reach it
+    OnionWithoutProxy,
+    /// The BIP-77 session has expired
+    Expired,
+    /// The endpoint didn't respond
+    Unreachable,
+    /// The OHTTP keys of the BIP-77 directory couldn't be fetched
+    OhttpKeysUnavailable,
+}
+
+/// The result of checking a payjoin endpoint before trying to payjoin with it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PayjoinPreflight {
+    pub endpoint: PayjoinEndpoint,
+    /// The directory's OHTTP key configuration for BIP-77 endpoints,
+    /// either from the URI or fetched from the directory
+    pub ohttp_keys: Option<Vec<u8>>,
+    /// Why the endpoint can't be used, `None` if it can
+    pub unusable: Option<PayjoinUnusable>,
+}
+
+impl PayjoinPreflight {
+    pub fn version(&self) -> PayjoinVersion {
+        self.endpoint.version()
+    }
+
+    pub fn is_usable(&self) -> bool {
+        self.unusable.is_none()
+    }
+}
+
+/// Resolves payment params that require a network lookup before they can be paid.
+///
+/// All http requests are made through `C`, see [`Resolver::with_http_client`].
+#[derive(Debug, Clone)]
+pub struct Resolver<C = ReqwestClient> {
+    config: ResolverConfig,
+    http_client: C,
+    cache: Arc<ResolverCache>,
+}
+
+impl Resolver {
+    pub fn new(config: ResolverConfig) -> Result<Self, ResolveError> {
+        let http_client = ReqwestClient::new(&config)?;
+        Ok(Self::with_http_client(config, http_client))
+    }
+
+    /// A resolver routing its requests through `proxy` instead of the configured one,
+    /// e.g. to send a single lookup over Tor. The cache is shared with this resolver.
+    pub fn with_proxy(&self, proxy: &str) -> Result<Self, ResolveError> {
+        let config = self.config.clone().proxy(proxy);
+        Ok(Self {
+            http_client: ReqwestClient::new(&config)?,
+            config,
+            cache: self.cache.clone(),
+        })
+    }
+}
+
+impl<C: HttpClient> Resolver<C> {
+    /// Creates a resolver making its requests through the given client, the configured
+    /// timeout and proxy are only used for nostr relays then.
+    pub fn with_http_client(config: ResolverConfig, http_client: C) -> Self {
+        Self {
+            config,
+            http_client,
+            cache: Arc::new(ResolverCache::default()),
+        }
+    }
+
+    pub fn config(&self) -> &ResolverConfig {
+        &self.config
+    }
+
+    /// Removes all cached results, e.g. after the user changed networks
+    pub fn clear_cache(&self) {
+        self.cache.clear();
+    }
+
+    /// Fetches the LNURL response for an LNURL or lightning address.
+    /// LNURL-pay responses are cached for [`CacheTtl::lnurl_pay`].
+    pub async fn resolve_lnurl(
+        &self,
+        params: &PaymentParams<'_>,
+    ) -> Result<LnUrlResponse, ResolveError> {
+        let lnurl = params.lnurl().ok_or(ResolveError::NotResolvable)?;
+        if let Some(pay) = self.cache.lnurl_pay.get(&lnurl.url) {
+            return Ok(LnUrlResponse::LnUrlPayResponse(pay));
+        }
+
+        let request = async {
+            let response = self
+                .http_client
+                .get(&lnurl.url, &[])
+                .await?
+                .error_for_status()?;
+            let body = String::from_utf8_lossy(&response.body);
+            Ok::<_, ResolveError>(lnurl::decode_ln_url_response(&body)?)
+        };
+        #[cfg(feature = "metrics")]
+        let request = crate::metrics::record_resolution("lnurl", request);
+        let response = request.await?;
+        if let LnUrlResponse::LnUrlPayResponse(pay) = &response {
+            self.cache
+                .lnurl_pay
+                .insert(lnurl.url, pay.clone(), self.config.cache_ttl.lnurl_pay);
+        }
+
+        Ok(response)
+    }
+
+    /// Looks up the BIP-353 payment instructions for the given name, returning them parsed.
+    ///
+    /// The records and their DNSSEC proof (RFC 9102) are fetched through the configured
+    /// [`ResolverConfig::doh_endpoint`], the proof is verified locally before the records are used.
+    /// Results are cached for [`CacheTtl::bip353`], at most for as long as the records' TTL.
+    pub async fn resolve_bip353(
+        &self,
+        name: &Bip353Name,
+    ) -> Result<PaymentParams<'static>, ResolveError> {
+        let key = name.dns_name();
+        if let Some(params) = self.cache.bip353.get(&key) {
+            return Ok(params);
+        }
+
+        let request = async {
+            let name = Name::try_from(name.dns_name()).map_err(|_| ResolveError::InvalidRecord)?;
+            let proof = self.build_txt_proof(&name).await?;
+            let now = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default();
+            let (uri, record_ttl) = bip353_record(&name, &proof, now)?;
+            let params = PaymentParams::from_str(&uri).map_err(|_| ResolveError::InvalidRecord)?;
+            Ok::<_, ResolveError>((params, record_ttl))
+        };
+        #[cfg(feature = "metrics")]
+        let request = crate::metrics::record_resolution("bip353", request);
+        let (params, record_ttl) = request.await?;
+        let ttl = self
+            .config
+            .cache_ttl
+            .bip353
+            .map(|ttl| ttl.min(Duration::from_secs(record_ttl.into())));
+        self.cache.bip353.insert(key, params.clone(), ttl);
+
+        Ok(params)
+    }
+
+    /// Builds a DNSSEC proof for the TXT records of `name` by sending each query
+    /// the proof needs to the DNS-over-HTTPS endpoint in wire format
+    async fn build_txt_proof(&self, name: &Name) -> Result<Vec<u8>, ResolveError> {
+        let (mut builder, query) = ProofBuilder::new(name, TXT_TYPE);
+        let mut queries = vec![query];
+        while builder.awaiting_responses() {
+            let responses = try_join_all(queries.iter().map(|q| self.doh_query(q))).await?;
+            queries = Vec::new();
+            for response in responses {
+                let new_queries = builder
+                    .process_response(&response)
+                    .map_err(|_| ResolveError::DnssecValidation)?;
+                queries.extend(new_queries);
+            }
+        }
+        let (proof, _) = builder
+            .finish_proof()
+            .map_err(|_| ResolveError::DnssecValidation)?;
+        Ok(proof)
+    }
+
+    /// Sends a single RFC 8484 wire format query to the DNS-over-HTTPS endpoint
+    async fn doh_query(&self, query: &QueryBuf) -> Result<QueryBuf, ResolveError> {
+        let url = format!(
+            "{}?dns={}",
+            self.config.doh_endpoint,
+            URL_SAFE_NO_PAD.encode(&query[..])
+        );
+        let response = self
+            .http_client
+            .get(&url, &[("accept", DNS_MESSAGE_CONTENT_TYPE)])
+            .await?
+            .error_for_status()?;
+        let mut buf = QueryBuf::new_zeroed(0);
+        buf.extend_from_slice(&response.body);
+        Ok(buf)
+    }
+
+    /// Looks up the BIP-47 payment code behind a PayNym handle
+    /// through the configured [`ResolverConfig::paynym_endpoint`].
+    pub async fn resolve_paynym(&self, nym: &PayNym) -> Result<PaymentCode, ResolveError> {
+        let request = async {
+            let body = serde_json::to_vec(&HashMap::from([("nym", nym.to_string())]))
+                .map_err(ResolveError::Json)?;
+            let response = self
+                .http_client
+                .post(&self.config.paynym_endpoint, JSON_HEADERS, body)
+                .await?;
+            if response.status == 404 {
+                return Err(ResolveError::PayNymNotFound);
+            }
+            let response = response.error_for_status()?.json::<PayNymResponse>()?;
+            paynym_payment_code(response)
+        };
+        #[cfg(feature = "metrics")]
+        let request = crate::metrics::record_resolution("paynym", request);
+        request.await
+    }
+
+    /// Fetches the config of the federation behind an invite code from the guardians
+    /// in the invite, the first whose config hashes to the invite's federation id is used.
+    /// Configs are cached for [`CacheTtl::mint_info`].
+    pub async fn resolve_federation(
+        &self,
+        invite: &FedimintInviteCode,
+    ) -> Result<FederationInfo, ResolveError> {
+        let key = invite.federation_id();
+        if let Some(info) = self.cache.federation.get(&key) {
+            return Ok(info);
+        }
+
+        let request = async {
+            let mut error = ResolveError::InvalidResponse;
+            for (_, url) in invite.guardians() {
+                match self.fetch_federation_info(invite, url).await {
+                    Ok(info) => return Ok(info),
+                    Err(e) => error = e,
+                }
+            }
+            Err(error)
+        };
+        #[cfg(feature = "metrics")]
+        let request = crate::metrics::record_resolution("fedimint", request);
+        let info = request.await?;
+        self.cache
+            .federation
+            .insert(key, info.clone(), self.config.cache_ttl.mint_info);
+
+        Ok(info)
+    }
+
+    /// Fetches the config of a federation from one of its guardians
+    async fn fetch_federation_info(
+        &self,
+        invite: &FedimintInviteCode,
+        guardian: &Url,
+    ) -> Result<FederationInfo, ResolveError> {
+        let url = guardian_http_url(guardian);
+        let global = self
+            .guardian_call::<FederationGlobalConfig>(&url, CLIENT_CONFIG_METHOD)
+            .await?;
+        if global.federation_id() != invite.federation_id() {
+            return Err(ResolveError::FederationIdMismatch);
+        }
+        let modules = self
+            .guardian_call::<BTreeMap<u16, FederationModuleConfig>>(
+                &url,
+                MODULES_CONFIG_JSON_METHOD,
+            )
+            .await?;
+
+        Ok(federation_info(invite.federation_id(), global, modules))
+    }
+
+    async fn guardian_call<T: serde::de::DeserializeOwned>(
+        &self,
+        url: &str,
+        method: &str,
+    ) -> Result<T, ResolveError> {
+        self.http_client
+            .post(url, JSON_HEADERS, guardian_request(method)?)
+            .await?
+            .error_for_status()?
+            .json::<GuardianResponse<T>>()?
+            .result
+            .ok_or(ResolveError::InvalidResponse)
+    }
+
+    /// Looks up the pubkey and suggested relays of a NIP-05 identifier on its domain
+    pub async fn resolve_nip05(
+        &self,
+        id: &Nip05Identifier,
+    ) -> Result<(nostr::PublicKey, Vec<String>), ResolveError> {
+        let request = async {
+            let response = self
+                .http_client
+                .get(id.url().as_str(), &[])
+                .await?
+                .error_for_status()?
+                .json::<Nip05Response>()?;
+            nip05_profile(id, response)
+        };
+        #[cfg(feature = "metrics")]
+        let request = crate::metrics::record_resolution("nip05", request);
+        request.await
+    }
+
+    /// Fetches the kind-0 profile of a nostr pubkey, profile or NIP-05 identifier and returns
+    /// its `lud16` lightning address or `lud06` LNURL as payment params, e.g. to zap it.
+    ///
+    /// The profile is looked up on `relays` and any relays the profile or NIP-05 suggest.
+    /// Relays are connected to through the configured proxy, which has to be a SOCKS5 proxy.
+    pub async fn resolve_nostr_payment(
+        &self,
+        params: &PaymentParams<'_>,
+        relays: &[Url],
+    ) -> Result<PaymentParams<'static>, ResolveError> {
+        let mut relays: Vec<String> = relays
+            .iter()
+            .chain(params.nostr_relays().iter())
+            .map(|relay| relay.to_string())
+            .collect();
+        let pubkey = match (params.nostr_pubkey(), params.nip05()) {
+            (Some(pubkey), _) => pubkey,
+            (None, Some(id)) => {
+                let (pubkey, nip05_relays) = self.resolve_nip05(&id).await?;
+                relays.extend(nip05_relays);
+                pubkey
+            }
+            (None, None) => return Err(ResolveError::NotResolvable),
+        };
+
+        let request = async {
+            let client = self.nostr_client(relays).await?;
+
+            let filter = nostr::Filter::new()
+                .author(pubkey)
+                .kind(nostr::Kind::Metadata);
+            let events = client
+                .get_events_of(vec![filter], self.config.timeout)
+                .await;
+            let _ = client.disconnect().await;
+
+            let event = events?
+                .into_iter()
+                .filter(|event| event.verify().is_ok())
+                .max_by_key(|event| event.created_at)
+                .ok_or(ResolveError::NoPaymentTarget)?;
+            let metadata = nostr::Metadata::from_json(&event.content)
+                .map_err(|_| ResolveError::InvalidResponse)?;
+            nostr_payment_target(&metadata)
+        };
+        #[cfg(feature = "metrics")]
+        let request = crate::metrics::record_resolution("nostr_profile", request);
+        request.await
+    }
+
+    /// Connects to a NIP-47 connection's relay and returns what it can be used for.
+    ///
+    /// For NWC the wallet service's info event lists the supported methods. NWA URIs
+    /// already say which methods and budget the app asks for, so only the relay is checked.
+    pub async fn get_nwc_info(
+        &self,
+        params: &PaymentParams<'_>,
+    ) -> Result<NwcCapabilities, ResolveError> {
+        let request = async {
+            if let Some(nwc) = params.nostr_wallet_connect() {
+                let relays = nwc.relays().iter().map(|relay| relay.to_string());
+                let client = self.nostr_client(relays).await?;
+                let filter = nostr::Filter::new()
+                    .author(nwc.public_key())
+                    .kind(nostr::Kind::WalletConnectInfo);
+                let events = client
+                    .get_events_of(vec![filter], self.config.timeout)
+                    .await;
+                let _ = client.disconnect().await;
+
+                let event = events?
+                    .into_iter()
+                    .filter(|event| event.verify().is_ok())
+                    .max_by_key(|event| event.created_at)
+                    .ok_or(ResolveError::WalletInfoNotFound)?;
+                Ok(nwc_capabilities(&event.content))
+            } else if let Some(nwa) = params.nostr_wallet_auth() {
+                let client = self.nostr_client([nwa.relay_url.to_string()]).await?;
+                let mut connected = false;
+                for relay in client.relays().await.values() {
+                    connected |= relay.is_connected().await;
+                }
+                let _ = client.disconnect().await;
+                if !connected {
+                    return Err(ResolveError::RelayUnreachable);
+                }
+
+                Ok(NwcCapabilities {
+                    methods: nwa
+                        .required_commands()
+                        .iter()
+                        .chain(nwa.optional_commands())
+                        .cloned()
+                        .unique()
+                        .collect(),
+                    unknown_methods: vec![],
+                    budget: nwa.budget().cloned(),
+                })
+            } else {
+                Err(ResolveError::NotResolvable)
+            }
+        };
+        #[cfg(feature = "metrics")]
+        let request = crate::metrics::record_resolution("nwc_info", request);
+        request.await
+    }
+
+    /// A nostr client connected to the given relays through the configured proxy,
+    /// bad relay urls are skipped.
+    async fn nostr_client<I>(&self, relays: I) -> Result<nostr_sdk::Client, ResolveError>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let proxy = relay_proxy(self.config.proxy.as_deref())?;
+        let opts = nostr_sdk::Options::new().connection_timeout(self.config.timeout);
+        let client = nostr_sdk::ClientBuilder::new().opts(opts).build();
+
+        #[allow(unused_mut)]
+        let mut opts = nostr_sdk::RelayOptions::new();
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            opts = opts.proxy(proxy);
+        }
+        #[cfg(target_arch = "wasm32")]
+        let _ = proxy;
+        for relay in relays.into_iter().unique() {
+            let _ = client.add_relay_with_opts(relay, opts.clone()).await;
+        }
+        client.connect().await;
+        Ok(client)
+    }
+
+    /// Fetches a Cashu mint's info, e.g. the mint of [`PaymentParams::cashu_mints`].
+    /// The info is cached for [`CacheTtl::mint_info`].
+    pub async fn resolve_cashu_mint_info(&self, mint: &Url) -> Result<CashuMintInfo, ResolveError> {
+        let key = mint.to_string();
+        if let Some(info) = self.cache.cashu_mint_info.get(&key) {
+            return Ok(info);
+        }
+
+        let request = async {
+            let url = format!("{}/v1/info", mint.as_str().trim_end_matches('/'));
+            let info = self
+                .http_client
+                .get(&url, &[])
+                .await?
+                .error_for_status()?
+                .json::<CashuMintInfoResponse>()?;
+            Ok::<_, ResolveError>(CashuMintInfo::from(info))
+        };
+        #[cfg(feature = "metrics")]
+        let request = crate::metrics::record_resolution("cashu_info", request);
+        let info = request.await?;
+        self.cache
+            .cashu_mint_info
+            .insert(key, info.clone(), self.config.cache_ttl.mint_info);
+
+        Ok(info)
+    }
+
+    /// Asks the mints of a Cashu token whether its proofs are already spent,
+    /// so wallets only offer to claim tokens that still can be.
+    pub async fn check_cashu_token(
+        &self,
+        params: &PaymentParams<'_>,
+    ) -> Result<CashuTokenState, ResolveError> {
+        let proofs = params.cashu_proofs();
+        if proofs.is_empty() {
+            return Err(ResolveError::NotResolvable);
+        }
+
+        let request = async {
+            let mut states = Vec::with_capacity(proofs.len());
+            for (mint, group) in &proofs.into_iter().group_by(|(mint, _, _)| mint.clone()) {
+                let group: Vec<(u64, PublicKey)> =
+                    group.map(|(_, amount, y)| (amount, y)).collect();
+                let ys: Vec<String> = group.iter().map(|(_, y)| y.to_string()).collect();
+                let url = format!("{}/v1/checkstate", mint.as_str().trim_end_matches('/'));
+                let body =
+                    serde_json::to_vec(&HashMap::from([("Ys", ys)])).map_err(ResolveError::Json)?;
+                let response = self
+                    .http_client
+                    .post(&url, JSON_HEADERS, body)
+                    .await?
+                    .error_for_status()?
+                    .json::<CashuCheckStateResponse>()?;
+                states.extend(cashu_proof_states(&group, response)?);
+            }
+            Ok(CashuTokenState { proofs: states })
+        };
+        #[cfg(feature = "metrics")]
+        let request = crate::metrics::record_resolution("cashu_checkstate", request);
+        request.await
+    }
+
+    /// Checks that the payjoin endpoint of the params can be used: it must be https or an
+    /// onion service reachable through the proxy, not expired, respond, and for BIP-77
+    /// the directory's OHTTP keys must be known.
+    ///
+    /// Problems with the endpoint are reported in [`PayjoinPreflight::unusable`] rather than
+    /// as an error so the sender can fall back to a plain spend.
+    pub async fn preflight_payjoin(
+        &self,
+        params: &PaymentParams<'_>,
+    ) -> Result<PayjoinPreflight, ResolveError> {
+        let endpoint = params.payjoin().ok_or(ResolveError::NotResolvable)?;
+        let request = async { Ok(self.payjoin_preflight(endpoint).await) };
+        #[cfg(feature = "metrics")]
+        let request = crate::metrics::record_resolution("payjoin_preflight", request);
+        request.await
+    }
+
+    async fn payjoin_preflight(&self, endpoint: PayjoinEndpoint) -> PayjoinPreflight {
+        let mut preflight = PayjoinPreflight {
+            ohttp_keys: endpoint.ohttp_keys().map(|keys| keys.to_vec()),
+            endpoint,
+            unusable: None,
+        };
+
+        let endpoint = &preflight.endpoint;
+        if !endpoint.is_secure() {
+            preflight.unusable = Some(PayjoinUnusable::InsecureEndpoint);
+        } else if endpoint.is_onion() && self.config.proxy.is_none() {
+            preflight.unusable = Some(PayjoinUnusable::OnionWithoutProxy);
+        } else if endpoint.is_expired() {
+            preflight.unusable = Some(PayjoinUnusable::Expired);
+        } else {
+            match endpoint.version() {
+                // v1 receivers only accept POSTs of a PSBT, any response means the server is up
+                PayjoinVersion::V1 => {
+                    let url = endpoint.url().as_str();
+                    if self.http_client.get(url, &[]).await.is_err() {
+                        preflight.unusable = Some(PayjoinUnusable::Unreachable);
+                    }
+                }
+                PayjoinVersion::V2 if preflight.ohttp_keys.is_none() => {
+                    match self.fetch_ohttp_keys(endpoint).await {
+                        Ok(keys) if !keys.is_empty() => preflight.ohttp_keys = Some(keys),
+                        _ => preflight.unusable = Some(PayjoinUnusable::OhttpKeysUnavailable),
+                    }
+                }
+                PayjoinVersion::V2 => {}
+            }
+        }
+
+        preflight
+    }
+
+    /// The OHTTP key configuration of a BIP-77 directory, from its well known gateway path
+    async fn fetch_ohttp_keys(&self, endpoint: &PayjoinEndpoint) -> Result<Vec<u8>, ResolveError> {
+        let mut url = endpoint.url().clone();
+        url.set_path("/.well-known/ohttp-gateway");
+        url.set_query(None);
+        url.set_fragment(None);
+
+        let response = self
+            .http_client
+            .get(url.as_str(), &[("accept", "application/ohttp-keys")])
+            .await?
+            .error_for_status()?;
+        Ok(response.body)
+    }
+
+    /// Logs in to an LNURL-auth service, the `signer` provides the linking key and signature
+    /// so the key material never has to leave the app.
+    pub async fn authenticate<S: LnUrlAuthSigner>(
+        &self,
+        request: &LnUrlAuthRequest,
+        signer: &S,
+    ) -> Result<(), ResolveError> {
+        let (key, sig) = request
+            .sign(signer)
+            .map_err(|e| ResolveError::Signer(e.to_string()))?;
+        let url = request.callback_url(&key, &sig);
+
+        let request = async {
+            self.http_client
+                .get(&url, &[])
+                .await?
+                .error_for_status()?
+                .json::<Response>()
+        };
+        #[cfg(feature = "metrics")]
+        let request = crate::metrics::record_resolution("lnurl_auth", request);
+        match request.await? {
+            Response::Ok { .. } => Ok(()),
+            Response::Error { reason } => Err(ResolveError::AuthRejected(reason)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::str::FromStr;
+
+    use bitcoin::hashes::hex::FromHex;
+    use futures::executor::block_on;
+    use lnurl::Tag;
+
+    use super::*;
+
+    #[test]
+    fn test_not_resolvable() {
+        let resolver = Resolver::new(ResolverConfig::default()).unwrap();
+        let params = PaymentParams::from_str("1andreas3batLhQa2FawWjeyjCqyBzypd").unwrap();
+
+        let res = block_on(resolver.resolve_lnurl(&params));
+        assert!(matches!(res, Err(ResolveError::NotResolvable)));
+    }
+
+    #[test]
+    fn test_abort_resolution() {
+        let resolver = Resolver::new(ResolverConfig::default()).unwrap();
+        let params = PaymentParams::from_str("ben@opreturnbot.com").unwrap();
+
+        let (future, handle) = abortable(resolver.resolve_lnurl(&params));
+        handle.abort();
+
+        let res = block_on(future);
+        assert!(matches!(res, Err(ResolveError::Cancelled)));
+    }
+
+    fn sample_pay_response() -> PayResponse {
+        PayResponse {
+            callback: "https://opreturnbot.com/lnurlp/ben/callback".to_string(),
+            max_sendable: 100_000_000,
+            min_sendable: 1_000,
+            tag: Tag::PayRequest,
+            metadata: "[[\"text/plain\",\"ben\"]]".to_string(),
+            comment_allowed: None,
+            allows_nostr: None,
+            nostr_pubkey: None,
+        }
+    }
+
+    #[test]
+    fn test_ttl_cache() {
+        let cache = TtlCache::default();
+        let pay = sample_pay_response();
+
+        cache.insert("a".to_string(), pay.clone(), Some(Duration::from_secs(60)));
+        assert_eq!(cache.get(&"a".to_string()), Some(pay.clone()));
+
+        // zero ttl expires immediately
+        cache.insert("b".to_string(), pay.clone(), Some(Duration::ZERO));
+        assert_eq!(cache.get(&"b".to_string()), None);
+
+        // disabled cache never stores anything
+        cache.insert("c".to_string(), pay, None);
+        assert_eq!(cache.get(&"c".to_string()), None);
+
+        cache.clear();
+        assert_eq!(cache.get(&"a".to_string()), None);
+    }
+
+    #[test]
+    fn test_cached_lnurl_pay() {
+        let resolver = Resolver::new(ResolverConfig::default()).unwrap();
+        let params = PaymentParams::from_str("ben@opreturnbot.com").unwrap();
+        let pay = sample_pay_response();
+
+        // pre-populate the cache so no network request is made
+        resolver.cache.lnurl_pay.insert(
+            params.lnurl().unwrap().url,
+            pay.clone(),
+            Some(Duration::from_secs(60)),
+        );
+
+        let res = block_on(resolver.resolve_lnurl(&params)).unwrap();
+        assert_eq!(res, LnUrlResponse::LnUrlPayResponse(pay));
+    }
+
+    #[test]
+    fn test_cached_mint_info() {
+        let resolver = Resolver::new(ResolverConfig::default()).unwrap();
+        let mint = Url::parse("https://mint.example.com").unwrap();
+        let response: CashuMintInfoResponse =
+            serde_json::from_str(r#"{"name":"Bob's Cashu mint","nuts":{}}"#).unwrap();
+        let info = CashuMintInfo::from(response);
+
+        // pre-populate the cache so no network request is made
+        resolver.cache.cashu_mint_info.insert(
+            mint.to_string(),
+            info.clone(),
+            Some(Duration::from_secs(60)),
+        );
+
+        let res = block_on(resolver.resolve_cashu_mint_info(&mint)).unwrap();
+        assert_eq!(res, info);
+    }
+
+    #[test]
+    fn test_config_proxy() {
+        let config = ResolverConfig::default().proxy("socks5h://127.0.0.1:9050");
+        let resolver = Resolver::new(config).unwrap();
+        assert_eq!(
+            resolver.config().proxy.as_deref(),
+            Some("socks5h://127.0.0.1:9050")
+        );
+
+        let config = ResolverConfig::default().proxy("not a proxy");
+        assert!(Resolver::new(config).is_err());
+    }
+
+    #[test]
+    fn test_with_proxy() {
+        let resolver = Resolver::new(ResolverConfig::default()).unwrap();
+        let tor = resolver.with_proxy(DEFAULT_TOR_PROXY).unwrap();
+        assert_eq!(tor.config().proxy.as_deref(), Some(DEFAULT_TOR_PROXY));
+        assert_eq!(resolver.config().proxy, None);
+        assert!(Arc::ptr_eq(&tor.cache, &resolver.cache));
+
+        assert!(resolver.with_proxy("not a proxy").is_err());
+
+        let config = ResolverConfig::default().tor();
+        assert_eq!(config.proxy.as_deref(), Some(DEFAULT_TOR_PROXY));
+    }
+
+    #[test]
+    fn test_relay_proxy() {
+        assert!(matches!(relay_proxy(None), Ok(None)));
+        assert_eq!(
+            relay_proxy(Some(DEFAULT_TOR_PROXY)).unwrap(),
+            Some("127.0.0.1:9050".parse().unwrap())
+        );
+        assert!(matches!(
+            relay_proxy(Some("http://127.0.0.1:8080")),
+            Err(ResolveError::UnsupportedProxy)
+        ));
+        assert!(matches!(
+            relay_proxy(Some("not a proxy")),
+            Err(ResolveError::UnsupportedProxy)
+        ));
+
+        // nostr lookups fail rather than connecting without the proxy
+        let config = ResolverConfig::default().proxy("http://127.0.0.1:8080");
+        let resolver = Resolver::new(config).unwrap();
+        let params = PaymentParams::from_str(
+            "npub1l2vyh47mk2p0qlsku7hg0vn29faehy9hy34ygaclpn66ukqp3afqutajft",
+        )
+        .unwrap();
+        assert!(matches!(
+            block_on(resolver.resolve_nostr_payment(&params, &[])),
+            Err(ResolveError::UnsupportedProxy)
+        ));
+    }
+
+    /// RFC 9102 proof for the TXT records of `matt.user._bitcoin-payment.mattcorallo.com.`,
+    /// valid from 1785988800 until 1786415920
+    const BIP353_PROOF: &str = "00003000010000000101080100030803010001e0980fa67b5962952deb96828c0a3fede0f86b357272caabb6b709a431429bfc6dfb85548d169c6df7a9a487fccc3d2018227eb7737f85d8fc340b9f2049f4c7da3b2016b8468499827e1903e2c1555fb2d1b0480d4c71f14952db5382ad87baeef8280461b40f303e8fcddd7732610b4d873faa08ce4d05bdde731fe76b0eac61a6fd2f14ba7f6714d2ad37fbe04fe4ab3451e7fc58909aff58b309813ebcc930a25b55fad10d6b78695e267b8e57bfc5d81a66b3e2e591a6c8b548df88355d562b365b0209398dbc54087f35b949315016c4298b3733c859fdaf72f34b1c4f08dc1d9421bce1b111d0199dc2a6c5e936a7bfe17130e6afada8648f8c08cb9900003000010000000101080101030803010001acffb409bcc939f831f7a1e5ec88f7a59255ec53040be432027390a4ce896d6f9086f3c5e177fbfe118163aaec7af1462c47945944c4e2c026be5e98bbcded25978272e1e3e079c5094d573f0e83c92f02b32d3513b1550b826929c80dd0f92cac966d17769fd5867b647c3f38029abdc48152eb8f207159ecc5d232c7c1537c79f4b7ac28ff11682f21681bf6d6aba555032bf6f9f036beb2aaa5b3778d6eebfba6bf9ea191be4ab0caea759e2f773a1f9029c73ecb8d5735b9321db085f1b8e2d8038fe2941992548cee0d67dd4547e11dd63af9c9fc1c5466fb684cf009d7197c2cf79e792ab501e6a8a1ca519af2cb9b5f6367e94c0d47502451357be1b500003000010000000101080101030803010001af7a8deba49d995a792aefc80263e991efdbc86138a931deb2c65d5682eab5d3b03738e3dfdc89d96da64c86c0224d9ce02514d285da3068b19054e5e787b2969058e98e12566c8c808c40c0b769e1db1a24a1bd9b31e303184a31fc7bb56b85bbba8abc02cd5040a444a36d47695969849e16ad856bb58e8fac8855224400319bdab224d83fc0e66aab32ff74bfeaf0f91c454e6850a1295207bbd4cdde8f6ffb08faa9755c2e3284efa01f99393e18786cb132f1e66ebc6517318e1ce8a3b7337ebb54d035ab57d9706ecd9350d4afacd825e43c8668eece89819caf6817af62dc4fbd82f0e33f6647b2b6bda175f14607f59f4635451e6b27df282ef73d8700002e0001000000010113003008000002a3006a8795006a6be5804f660090aa5a3c4c8a0e134db3dae2ce9c8e11c2b6370834e2539011e5202f5ef97d1d56ef34f30a8f7a2e97f9c6e31e37dbd9287ab7eb6622ea95f5d417822f925e8d1274ab839f94dbb68e48d9a6a5b825635e8a6d20689c5dd9f83c30c87baa0f6066731f14bccaf32d87b9dbdef891772c3fcc8c83e974f77f184212ab29e638b631366a25d9e28c7620c9dd13715537f3984a3c42a5c6183128cd67f9a4cf57bd36b6d8b2c9dfb3476e5c13d1e7879e1c4ac2de6ae111fb61a6e60b63655207b947f1a80900f45897668b5dfd3bb0f2c910dad5810748f8a074b0d18d7b809b0d5a701acb6e6bd2ec5f63d596dcd8c9b530ef76e33eb3f4bdc65cf95a495d4d2703636f6d00002b00010000000100244d060d028acbb0cd28f41250a80a491389424d341522d946b0da0c0291f2d3d771d7805a03636f6d00002e0001000000010113002b0801000151806a8538506a7406c0e1b4009fcfce833941e0537edd099dbfe53bb55ac75c846e99fd9fa915ee9f54d05b8194c10afb44c14b898bb29b3a20f1c84803eed1b0891331336657615ecc5a865ad976fcaa753d24ed7442a0ce22c3b57cf67a6d0a666be839d9bfb87fb7ef046d80af751dd557a4a2a1112a78cc661928140fd6831943886c0879dd66b221eec1e09a9edbee5ca96eaacf1529439bbd5566c629117285fb79c3dc2503dd2d2ca421e44b94ad71735fc59183cabfd4057dff81858fc97f55537cdc1df4a139c917f902c82aab5b6d7cccf3f2e33306479d975e86fd79e89433389468186ae10e5299e8be42e4f3d75518ba26d84ff9bc0644559e27dff2c966c7ea8fd1b5425d2e03636f6d00003000010000000100440100030da3aa27a7eb7aea8960dcf161b885da69ab68813dceaea1ebaade3c224606bb1c16f2d4a7415306c8ef934a6a7cb1346d903f649be37f550befb6a54cf2b6bdfd03636f6d00003000010000000100440101030db71f0465101ddbe2bf0c9455d12fa16c1cda44f4bf1ba2553418ad1f3aa9b06973f21b84eb532cf4035ee8d4832ca26d89306a7d32560c0cb0129d450ac1083503636f6d00002e000100000001005700300d01000151806a8708fb6a73414f4d0603636f6d00fa687995d6b32faf2850b99ab9de9542b1d76297236d467cb092c195cf0759fe23681ed35c24ac9d4090871a59f70b5e995c63ed3a6d486b008475c4beb829bf0b6d617474636f72616c6c6f03636f6d00002b000100000001002423490d02282511c1378832188575a172f29a89c09ac28c826fc4fe78534d4c6df5eed2f00b6d617474636f72616c6c6f03636f6d00002b0001000000010024e2f50d02f0e161567d468087ff27b051abc94476178a7cb635da1aa705e05c77ca81de520b6d617474636f72616c6c6f03636f6d00002e0001000000010057002b0d02000151806a7a8b306a714048a1e603636f6d007b48f6f4d233b8702fb5229fd382ca4a19b6bd53a2c242a596ea17c93b2af7207ea6bab227f45353835afd9047b87d32827414113e82e5211a42e33981477dfb0b6d617474636f72616c6c6f03636f6d00003000010000000100440100030d78402053f892f1547bb9b839940aea4c009d047c4af04471e53a53582c3dc1cdb9a5ed8988dd3f88ddd081f98e06ce8052956efa2177e9a6dd51d72dfd344b8d0b6d617474636f72616c6c6f03636f6d00003000010000000100440100030dc8f01e60f6a7940c401d9f6b6fb2c0a8fd8bad3658561102c9b7f05ea9fce1bd64be1b41092db623efa64c871877af3504ac2fe966b33a2d3787cb1ee662bf280b6d617474636f72616c6c6f03636f6d00003000010000000100440101030dc8ddab89616f09312505c8732f2b7453546309c5d6f9ebd172aec0a79114ea039a72578e5dfb78f47da8403711a8ae82fdd2433db679d840790b55a52180d8e10b6d617474636f72616c6c6f03636f6d00002e000100000001006300300d0200093a806a848d6a6a72035223490b6d617474636f72616c6c6f03636f6d009e3bee39bcc687f99d5235f99a624a23e870ec1c8f94a7ba38a2399bfce1425a0b5c483d0d4c604b64a157eb70599baeba7e71ed15ecd45cc4207c5ff16ea291046d6174740475736572105f626974636f696e2d7061796d656e740b6d617474636f72616c6c6f03636f6d000010000100000001004b4a6173206c6f6e6720617320697420646f65736e2774207374617274207769746820626974636f696e3a2c206f74686572207265636f7264732073686f756c642062652069676e6f726564046d6174740475736572105f626974636f696e2d7061796d656e740b6d617474636f72616c6c6f03636f6d00001000010000000101ecff626974636f696e3a626331717a7477793678656e337a647474377a3076726761706d6a74667a3861636a6b6670356670376c3f6c6e6f3d6c6e6f317a7235717975677167736b726b37306b716d7571377633646e7232666e6d68756b7073396e386875743438766b7170716e736b743273767371776a616b70376b36707968746b7578773779326b716d73786c777275687a7176307a736e686839713374397868783339737563367173723037656b6d3565736479756d307736366d6e783876647175777670376470356a70376a337635637036616a3077333239666e6b7171763630713936737a356e6b726335723935716666783030327135337471646beb3878396d32746d7438356a74706d63796376666e727078336c723435683267376e6133736563377867756374667a7a636d386a6a71746a3579613237746536306a303376707430767139746d326e3979786c32686e67666e6d79676573613235733475347a6c78657771707670393478743772757234726878756e776b74686b39766c79336c6d356868307071763461796d6371656a6c6773736e6c707a776c6767796b6b616a7037796a73356a76723261676b79797063646c6a323830637934366a70796e73657a72636a326b7761326c797238787664366c666b706834787278746b327863336c7071046d6174740475736572105f626974636f696e2d7061796d656e740b6d617474636f72616c6c6f03636f6d00002e000100000001006300100d0500000e106a844e8d6a71c475a0b50b6d617474636f72616c6c6f03636f6d00bb54a5eae349bd265cc6812c7b0ebf30cec623cb2674f92e0d24b6eadf2d12215e71ee9df1ecc8eb071a9526e42ca39b5de52675b5b99476639c12a451c93730";
+
+    #[test]
+    fn test_bip353_record() {
+        let name = Name::try_from("matt.user._bitcoin-payment.mattcorallo.com.").unwrap();
+        let proof = Vec::<u8>::from_hex(BIP353_PROOF).unwrap();
+
+        // the proof also holds a record that isn't a payment instruction
+        let (record, ttl) = bip353_record(&name, &proof, 1786000000).unwrap();
+        assert_eq!(ttl, 3600);
+        assert!(record.starts_with("bitcoin:bc1qztwy6xen3zdtt7z0vrgapmjtfz8acjkfp5fp7l?lno=lno1"));
+
+        // signatures that are expired or not yet valid
+        assert!(matches!(
+            bip353_record(&name, &proof, 1786415921),
+            Err(ResolveError::DnssecValidation)
+        ));
+        assert!(matches!(
+            bip353_record(&name, &proof, 1785988799),
+            Err(ResolveError::DnssecValidation)
+        ));
+
+        // a tampered record
+        let mut tampered = proof.clone();
+        let pos = tampered
+            .windows(10)
+            .position(|w| w == b"bitcoin:bc")
+            .unwrap();
+        tampered[pos + 10] ^= 1;
+        assert!(matches!(
+            bip353_record(&name, &tampered, 1786000000),
+            Err(ResolveError::DnssecValidation)
+        ));
+
+        // the proof doesn't cover other names
+        let other = Name::try_from("satoshi.user._bitcoin-payment.mattcorallo.com.").unwrap();
+        assert!(matches!(
+            bip353_record(&other, &proof, 1786000000),
+            Err(ResolveError::InvalidRecord)
+        ));
+
+        assert!(matches!(
+            bip353_record(&name, b"not a proof", 1786000000),
+            Err(ResolveError::DnssecValidation)
+        ));
+    }
+
+    #[test]
+    fn test_paynym_payment_code() {
+        let json = r#"{"codes":[{"claimed":false,"segwit":true,"code":"PM8TJS2JxQ5ztXUpBBRnpTbcUXbUHy2T1abfrb3KkAAtMEGNbey4oumH7Hc578WgQJhPjBxteQ5GHHToTYHE3A1w6p7tU6KSoFmWBVbFGjKPisZDbP97"},{"claimed":true,"segwit":true,"code":"PM8TJTLJbPRGxSbc8EJi42Wrr6QbNSaSSVJ5Y3E4pbCYiTHUskHg13935Ubb7q8tx9GVbh2UuRnBc3WSyJHhUrw8KhprKnn9eDznYGieTzFcwQRya4GA"}],"followers":[],"nymID":"nym","nymName":"stillmountain5a8"}"#;
+        let response: PayNymResponse = serde_json::from_str(json).unwrap();
+        let code = paynym_payment_code(response).unwrap();
+        assert_eq!(code.to_string(), "PM8TJTLJbPRGxSbc8EJi42Wrr6QbNSaSSVJ5Y3E4pbCYiTHUskHg13935Ubb7q8tx9GVbh2UuRnBc3WSyJHhUrw8KhprKnn9eDznYGieTzFcwQRya4GA");
+
+        let response: PayNymResponse =
+            serde_json::from_str(r#"{"codes":[{"claimed":true,"code":"not a code"}]}"#).unwrap();
+        assert!(matches!(
+            paynym_payment_code(response),
+            Err(ResolveError::PayNymNotFound)
+        ));
+    }
+
+    #[test]
+    fn test_federation_info() {
+        let json = r#"{"jsonrpc":"2.0","result":{"api_endpoints":{"0":{"url":"wss://fedimintd.mplsfed.foo/","name":"Hal"},"1":{"url":"wss://fedimintd.mplsfed.xyz/","name":"Satoshi"}},"consensus_version":{"major":0,"minor":0},"meta":{"federation_name":"MplsFed"},
+            "modules":{"0":{"kind":"ln","version":{"major":0,"minor":0},"config":"00"}}},"id":0}"#;
+        let global = serde_json::from_str::<GuardianResponse<FederationGlobalConfig>>(json)
+            .unwrap()
+            .result
+            .unwrap();
+        assert_eq!(
+            global.federation_id(),
+            "eaddc06945eecaacb75e13f4c38e9bbe3ef12def9fc152633939bb9ab6881992"
+        );
+        let json = r#"{"jsonrpc":"2.0","result":{"0":{"kind":"ln","network":"bitcoin","fee_consensus":{}},"1":{"kind":"mint","tbs_pks":{}},"2":{"kind":"wallet","network":"bitcoin","finality_delay":10}},"id":0}"#;
+        let modules = serde_json::from_str::<GuardianResponse<_>>(json)
+            .unwrap()
+            .result
+            .unwrap();
+        let info = federation_info("abcd".to_string(), global, modules);
+        assert_eq!(info.name.as_deref(), Some("MplsFed"));
+        assert_eq!(info.guardian_count(), 2);
+        assert_eq!(info.guardians[1], (1, "Satoshi".to_string()));
+        assert_eq!(info.modules, vec!["ln", "mint", "wallet"]);
+        assert_eq!(info.network, Some(Network::Bitcoin));
+
+        // no meta and no wallet module
+        let global: FederationGlobalConfig = serde_json::from_str(
+            r#"{"api_endpoints":{"0":{"url":"ws://127.0.0.1:18174/","name":"a"}}}"#,
+        )
+        .unwrap();
+        let modules = serde_json::from_str(r#"{"0":{"kind":"mint"}}"#).unwrap();
+        let info = federation_info("abcd".to_string(), global, modules);
+        assert_eq!(info.name, None);
+        assert_eq!(info.network, None);
+
+        assert_eq!(
+            guardian_http_url(&Url::parse("wss://fedimintd.mplsfed.foo/").unwrap()),
+            "https://fedimintd.mplsfed.foo/"
+        );
+    }
+
+    #[test]
+    fn test_resolve_federation_checks_id() {
+        let invite = FedimintInviteCode::from_str("fed11qgqzc2nhwden5te0vejkg6tdd9h8gepwvejkg6tdd9h8garhduhx6at5d9h8jmn9wshxxmmd9uqqzgxg6s3evnr6m9zdxr6hxkdkukexpcs3mn7mj3g5pc5dfh63l4tj6g9zk4er").unwrap();
+        // a config of some other federation
+        let json = r#"{"jsonrpc":"2.0","result":{"api_endpoints":{"0":{"url":"wss://fedimintd.mplsfed.foo/","name":"Hal"}},"meta":{}},"id":0}"#;
+        let resolver =
+            Resolver::with_http_client(ResolverConfig::default(), TestHttpClient::new(200, json));
+        assert!(matches!(
+            block_on(resolver.resolve_federation(&invite)),
+            Err(ResolveError::FederationIdMismatch)
+        ));
+
+        let requests = resolver.http_client.requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(
+            requests[0].0,
+            "https://fedimintd.fediminttwo.mutinynet.com/"
+        );
+    }
+
+    #[test]
+    fn test_cashu_mint_info() {
+        let json = r#"{"name":"Bob's Cashu mint","pubkey":"0283bf290884eed3a7ca2663fc0260de2e2064d6b355ea13f98dec004b7a7ead99","version":"Nutshell/0.15.0","description":"The short mint description","description_long":"A description that can be a long piece of text.","contact":[["email","contact@me.com"]],"motd":"Message to display to users.",
+            "nuts":{"4":{"methods":[{"method":"bolt11","unit":"sat"}],"disabled":false},"5":{"methods":[],"disabled":true},"7":{"supported":true},"8":{"supported":false},"12":{"supported":true}}}"#;
+        let response: CashuMintInfoResponse = serde_json::from_str(json).unwrap();
+        let info = CashuMintInfo::from(response);
+        assert_eq!(info.name.as_deref(), Some("Bob's Cashu mint"));
+        assert_eq!(info.version.as_deref(), Some("Nutshell/0.15.0"));
+        assert_eq!(info.nuts, vec![4, 7, 12]);
+    }
+
+    #[test]
+    fn test_cashu_proof_states() {
+        let y1 = cdk::dhke::hash_to_curve(b"secret 1").unwrap();
+        let y1 = PublicKey::from_slice(&y1.to_bytes()).unwrap();
+        let y2 = cdk::dhke::hash_to_curve(b"secret 2").unwrap();
+        let y2 = PublicKey::from_slice(&y2.to_bytes()).unwrap();
+        let json = format!(
+            r#"{{"states":[{{"Y":"{y2}","state":"SPENT","witness":null}},{{"Y":"{y1}","state":"UNSPENT","witness":null}}]}}"#
+        );
+        let response: CashuCheckStateResponse = serde_json::from_str(&json).unwrap();
+        let state = CashuTokenState {
+            proofs: cashu_proof_states(&[(8, y1), (2, y2)], response).unwrap(),
+        };
+        assert_eq!(
+            state.proofs,
+            vec![(8, CashuProofState::Unspent), (2, CashuProofState::Spent)]
+        );
+        assert!(!state.is_claimable());
+        assert!(!state.is_spent());
+        assert_eq!(state.unspent_amount(), 8);
+
+        // a proof the mint didn't answer for
+        let json = format!(r#"{{"states":[{{"Y":"{y1}","state":"PENDING"}}]}}"#);
+        let response: CashuCheckStateResponse = serde_json::from_str(&json).unwrap();
+        assert!(matches!(
+            cashu_proof_states(&[(8, y1), (2, y2)], response),
+            Err(ResolveError::InvalidResponse)
+        ));
+
+        let resolver = Resolver::new(ResolverConfig::default()).unwrap();
+        let params = PaymentParams::from_str("1andreas3batLhQa2FawWjeyjCqyBzypd").unwrap();
+        assert!(matches!(
+            block_on(resolver.check_cashu_token(&params)),
+            Err(ResolveError::NotResolvable)
+        ));
+    }
+
+    #[test]
+    fn test_nip05_profile() {
+        let id = Nip05Identifier::from_str("bob@example.com").unwrap();
+        let json = r#"{"names":{"bob":"b0635d6a9851d3aed0cd6c495b282167acf761729078d975fc341b22650b07b9"},"relays":{"b0635d6a9851d3aed0cd6c495b282167acf761729078d975fc341b22650b07b9":["wss://relay.example.com"]}}"#;
+        let response: Nip05Response = serde_json::from_str(json).unwrap();
+        let (pubkey, relays) = nip05_profile(&id, response).unwrap();
+        assert_eq!(
+            pubkey.to_hex(),
+            "b0635d6a9851d3aed0cd6c495b282167acf761729078d975fc341b22650b07b9"
+        );
+        assert_eq!(relays, vec!["wss://relay.example.com"]);
+
+        let response: Nip05Response = serde_json::from_str(r#"{"names":{}}"#).unwrap();
+        assert!(matches!(
+            nip05_profile(&id, response),
+            Err(ResolveError::Nip05NotFound)
+        ));
+    }
+
+    #[test]
+    fn test_nostr_payment_target() {
+        let metadata = nostr::Metadata::from_json(
+            r#"{"name":"ben","lud06":"lnurl1dp68gurn8ghj7mmswfjhgatjde3x7apwvdhk6tewwajkcmpdddhx7amw9akxuatjd3cz7cn9dc94s6d4","lud16":"ben@opreturnbot.com"}"#,
+        )
+        .unwrap();
+        let params = nostr_payment_target(&metadata).unwrap();
+        assert_eq!(
+            params.lightning_address().unwrap().to_string(),
+            "ben@opreturnbot.com"
+        );
+
+        // falls back to the LNURL when the lightning address is invalid
+        let metadata = nostr::Metadata::from_json(
+            r#"{"lud06":"LNURL1DP68GURN8GHJ7UM9WFMXJCM99E3K7MF0V9CXJ0M385EKVCENXC6R2C35XVUKXEFCV5MKVV34X5EKZD3EV56NYD3HXQURZEPEXEJXXEPNXSCRVWFNV9NXZCN9XQ6XYEFHVGCXXCMYXYMNSERXFQ5FNS","lud16":"not an address"}"#,
+        )
+        .unwrap();
+        let params = nostr_payment_target(&metadata).unwrap();
+        assert!(params.lnurl().is_some());
+
+        let metadata = nostr::Metadata::from_json(r#"{"name":"ben"}"#).unwrap();
+        assert!(matches!(
+            nostr_payment_target(&metadata),
+            Err(ResolveError::NoPaymentTarget)
+        ));
+
+        let resolver = Resolver::new(ResolverConfig::default()).unwrap();
+        let params = PaymentParams::from_str("1andreas3batLhQa2FawWjeyjCqyBzypd").unwrap();
+        assert!(matches!(
+            block_on(resolver.resolve_nostr_payment(&params, &[])),
+            Err(ResolveError::NotResolvable)
+        ));
+    }
+
+    #[test]
+    fn test_nwc_capabilities() {
+        let capabilities =
+            nwc_capabilities("pay_invoice get_balance make_invoice get_balance sign_message");
+        assert_eq!(
+            capabilities.methods,
+            vec![Method::PayInvoice, Method::GetBalance, Method::MakeInvoice]
+        );
+        assert_eq!(capabilities.unknown_methods, vec!["sign_message"]);
+        assert!(capabilities.supports(Method::PayInvoice));
+        assert!(!capabilities.supports(Method::PayKeysend));
+
+        let resolver = Resolver::new(ResolverConfig::default()).unwrap();
+        let params = PaymentParams::from_str("1andreas3batLhQa2FawWjeyjCqyBzypd").unwrap();
+        assert!(matches!(
+            block_on(resolver.get_nwc_info(&params)),
+            Err(ResolveError::NotResolvable)
+        ));
+    }
+
+    #[test]
+    fn test_payjoin_preflight() {
+        let resolver = Resolver::new(ResolverConfig::default()).unwrap();
+        let params = PaymentParams::from_str("1andreas3batLhQa2FawWjeyjCqyBzypd").unwrap();
+        assert!(matches!(
+            block_on(resolver.preflight_payjoin(&params)),
+            Err(ResolveError::NotResolvable)
+        ));
+
+        // rejected before making any request, parsing a BIP-21 URI already rejects these
+        let endpoint = PayjoinEndpoint::new(nostr::Url::parse("http://example.com/pj").unwrap());
+        let preflight = block_on(resolver.payjoin_preflight(endpoint));
+        assert_eq!(preflight.version(), PayjoinVersion::V1);
+        assert_eq!(preflight.unusable, Some(PayjoinUnusable::InsecureEndpoint));
+        assert!(!preflight.is_usable());
+
+        let params = PaymentParams::from_str("bitcoin:BC1QYLH3U67J673H6Y6ALV70M0PL2YZ53TZHVXGG7U?pj=http://abcdefghijklmnopqrstuvwxyzabcdefghijklmnopqrstuvwxyz2345.onion/pj").unwrap();
+        let preflight = block_on(resolver.preflight_payjoin(&params)).unwrap();
+        assert_eq!(preflight.unusable, Some(PayjoinUnusable::OnionWithoutProxy));
+
+        // a v2 session that expired in 1970
+        let params = PaymentParams::from_str(
+            "bitcoin:BC1QYLH3U67J673H6Y6ALV70M0PL2YZ53TZHVXGG7U?pj=https://payjo.in/TXJCGKTKXLUUZ%23RK1QQQQQQQQ%2BEX1QQQQQQG",
+        )
+        .unwrap();
+        let preflight = block_on(resolver.preflight_payjoin(&params)).unwrap();
+        assert_eq!(preflight.version(), PayjoinVersion::V2);
+        assert_eq!(preflight.unusable, Some(PayjoinUnusable::Expired));
+    }
+
+    /// The url and headers of a request
+    type TestRequest = (String, Vec<(String, String)>);
+
+    /// Replies to every request with the same response and records the requests made
+    struct TestHttpClient {
+        response: HttpResponse,
+        requests: Mutex<Vec<TestRequest>>,
+    }
+
+    impl TestHttpClient {
+        fn new(status: u16, body: &str) -> Self {
+            Self {
+                response: HttpResponse {
+                    status,
+                    body: body.as_bytes().to_vec(),
+                },
+                requests: Mutex::new(vec![]),
+            }
+        }
+
+        fn record(&self, url: &str, headers: &[(&str, &str)]) -> HttpResponse {
+            let headers = headers
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect();
+            self.requests
+                .lock()
+                .unwrap()
+                .push((url.to_string(), headers));
+            self.response.clone()
+        }
+    }
+
+    impl HttpClient for TestHttpClient {
+        async fn get(
+            &self,
+            url: &str,
+            headers: &[(&str, &str)],
+        ) -> Result<HttpResponse, ResolveError> {
+            Ok(self.record(url, headers))
+        }
+
+        async fn post(
+            &self,
+            url: &str,
+            headers: &[(&str, &str)],
+            _body: Vec<u8>,
+        ) -> Result<HttpResponse, ResolveError> {
+            Ok(self.record(url, headers))
+        }
+    }
+
+    #[test]
+    fn test_custom_http_client() {
+        let client = TestHttpClient::new(404, "");
+        let resolver = Resolver::with_http_client(ResolverConfig::default(), client);
+        let nym = PayNym::from_str("+stillmountain5a8").unwrap();
+        assert!(matches!(
+            block_on(resolver.resolve_paynym(&nym)),
+            Err(ResolveError::PayNymNotFound)
+        ));
+
+        let client = TestHttpClient::new(200, "not a dns message");
+        let resolver = Resolver::with_http_client(ResolverConfig::default(), client);
+        let name = Bip353Name::from_str("satoshi@example.com").unwrap();
+        assert!(matches!(
+            block_on(resolver.resolve_bip353(&name)),
+            Err(ResolveError::DnssecValidation)
+        ));
+        let requests = resolver.http_client.requests.lock().unwrap();
+        let dns_name = Name::try_from("satoshi.user._bitcoin-payment.example.com.").unwrap();
+        let (_, query) = ProofBuilder::new(&dns_name, TXT_TYPE);
+        assert_eq!(
+            requests[0].0,
+            format!(
+                "https://cloudflare-dns.com/dns-query?dns={}",
+                URL_SAFE_NO_PAD.encode(&query[..])
+            )
+        );
+        assert_eq!(
+            requests[0].1,
+            vec![("accept".to_string(), "application/dns-message".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_config_timeout() {
+        let config = ResolverConfig::default().timeout(Duration::from_millis(1_500));
+        let resolver = Resolver::new(config).unwrap();
+        assert_eq!(
+            resolver.config().timeout,
+            Some(Duration::from_millis(1_500))
+        );
+    }
+}
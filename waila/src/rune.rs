@@ -0,0 +1,269 @@
+use core::fmt;
+use core::str::FromStr;
+
+use base64::engine::general_purpose::{URL_SAFE, URL_SAFE_NO_PAD};
+use base64::Engine;
+
+use crate::prelude::*;
+
+/// The conditions a rune restriction can check a field with
+const CONDITIONS: [char; 11] = ['!', '=', '/', '^', '$', '~', '<', '>', '{', '}', '#'];
+
+/// A single check of a [`RuneRestriction`], e.g. `method^list` allows any
+/// method starting with `list`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RuneAlternative {
+    pub field: String,
+    pub condition: char,
+    pub value: String,
+}
+
+impl fmt::Display for RuneAlternative {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}{}", self.field, self.condition, self.value)
+    }
+}
+
+/// A restriction of a rune, it passes when any of its alternatives do
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RuneRestriction {
+    pub alternatives: Vec<RuneAlternative>,
+}
+
+impl fmt::Display for RuneRestriction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let alternatives: Vec<String> = self.alternatives.iter().map(|a| a.to_string()).collect();
+        write!(f, "{}", alternatives.join("|"))
+    }
+}
+
+/// A Core Lightning rune, the bearer token used by commando and clnrest to
+/// authenticate requests to the node.
+///
+/// Anyone with the rune can do what its restrictions allow, which may include
+/// spending, so the authentication code is redacted from [`fmt::Debug`] output.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ClnRune {
+    authcode: [u8; 32],
+    unique_id: String,
+    version: Option<String>,
+    restrictions: Vec<RuneRestriction>,
+}
+
+impl ClnRune {
+    /// The id the node created the rune with, used to blacklist it
+    pub fn unique_id(&self) -> &str {
+        &self.unique_id
+    }
+
+    pub fn version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+
+    /// The restrictions the rune was created with, all of them must pass.
+    /// A rune without any is a master rune that can do anything.
+    pub fn restrictions(&self) -> &[RuneRestriction] {
+        &self.restrictions
+    }
+
+    /// Sensitive, the code the node checks the rune with
+    pub fn authcode(&self) -> [u8; 32] {
+        self.authcode
+    }
+}
+
+impl fmt::Debug for ClnRune {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClnRune")
+            .field("authcode", &"<redacted>")
+            .field("unique_id", &self.unique_id)
+            .field("version", &self.version)
+            .field("restrictions", &self.restrictions)
+            .finish()
+    }
+}
+
+/// Sensitive, this is the rune itself and can be used to access the node
+impl fmt::Display for ClnRune {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut restrictions = match &self.version {
+            Some(version) => format!("={}-{version}", self.unique_id),
+            None => format!("={}", self.unique_id),
+        };
+        for restriction in &self.restrictions {
+            let alternatives: Vec<String> = restriction
+                .alternatives
+                .iter()
+                .map(|a| {
+                    let value = a.value.replace('|', "\\|").replace('&', "\\&");
+                    format!("{}{}{value}", a.field, a.condition)
+                })
+                .collect();
+            restrictions.push('&');
+            restrictions.push_str(&alternatives.join("|"));
+        }
+
+        let mut bytes = self.authcode.to_vec();
+        bytes.extend_from_slice(restrictions.as_bytes());
+        write!(f, "{}", URL_SAFE.encode(bytes))
+    }
+}
+
+/// Splits on `sep`, `\` escapes the next character
+fn split_escaped(str: &str, sep: char) -> Vec<String> {
+    let mut parts = vec![String::new()];
+    let mut chars = str.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                if let Some(next) = chars.next() {
+                    let part = parts.last_mut().expect("never empty");
+                    if next != sep {
+                        part.push('\\');
+                    }
+                    part.push(next);
+                }
+            }
+            c if c == sep => parts.push(String::new()),
+            c => parts.last_mut().expect("never empty").push(c),
+        }
+    }
+    parts
+}
+
+fn parse_alternative(str: &str) -> Option<RuneAlternative> {
+    let (index, condition) = str.char_indices().find(|(_, c)| CONDITIONS.contains(c))?;
+    let field = &str[..index];
+    if !field.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return None;
+    }
+
+    Some(RuneAlternative {
+        field: field.to_string(),
+        condition,
+        value: str[index + condition.len_utf8()..].to_string(),
+    })
+}
+
+fn parse_restriction(str: &str) -> Option<RuneRestriction> {
+    let alternatives = split_escaped(str, '|')
+        .iter()
+        .map(|a| parse_alternative(a))
+        .collect::<Option<Vec<_>>>()?;
+    Some(RuneRestriction { alternatives })
+}
+
+impl FromStr for ClnRune {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = URL_SAFE_NO_PAD
+            .decode(s)
+            .or_else(|_| URL_SAFE.decode(s))
+            .map_err(|_| ())?;
+        if bytes.len() <= 32 {
+            return Err(());
+        }
+
+        let mut authcode = [0; 32];
+        authcode.copy_from_slice(&bytes[..32]);
+        let restrictions = core::str::from_utf8(&bytes[32..]).map_err(|_| ())?;
+        let mut restrictions = split_escaped(restrictions, '&')
+            .iter()
+            .map(|r| parse_restriction(r))
+            .collect::<Option<Vec<_>>>()
+            .ok_or(())?;
+
+        // the node always starts a rune with its unique id, this is what tells
+        // a rune apart from any other base64
+        let id = match restrictions.first().map(|r| r.alternatives.as_slice()) {
+            Some([id]) if id.field.is_empty() && id.condition == '=' => id.value.clone(),
+            _ => return Err(()),
+        };
+        restrictions.remove(0);
+        let (unique_id, version) = match id.split_once('-') {
+            Some((id, version)) => (id.to_string(), Some(version.to_string())),
+            None => (id, None),
+        };
+
+        Ok(Self {
+            authcode,
+            unique_id,
+            version,
+            restrictions,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::str::FromStr;
+
+    use super::*;
+
+    fn encode_rune(restrictions: &str) -> String {
+        let mut bytes = vec![3; 32];
+        bytes.extend_from_slice(restrictions.as_bytes());
+        URL_SAFE.encode(bytes)
+    }
+
+    #[test]
+    fn test_parse_rune() {
+        let str = encode_rune("=0&method^list|method^get|method=summary&method/listdatastore");
+        let rune = ClnRune::from_str(&str).unwrap();
+        assert_eq!(rune.unique_id(), "0");
+        assert_eq!(rune.version(), None);
+        assert_eq!(rune.authcode(), [3; 32]);
+        assert_eq!(
+            rune.restrictions()
+                .iter()
+                .map(|r| r.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "method^list|method^get|method=summary",
+                "method/listdatastore"
+            ]
+        );
+        assert_eq!(
+            rune.restrictions()[0].alternatives[0],
+            RuneAlternative {
+                field: "method".to_string(),
+                condition: '^',
+                value: "list".to_string(),
+            }
+        );
+
+        let debug = format!("{rune:?}");
+        assert!(debug.contains("<redacted>"));
+    }
+
+    #[test]
+    fn test_parse_master_rune() {
+        let rune = ClnRune::from_str(&encode_rune("=5-1")).unwrap();
+        assert_eq!(rune.unique_id(), "5");
+        assert_eq!(rune.version(), Some("1"));
+        assert!(rune.restrictions().is_empty());
+        assert_eq!(rune.to_string(), encode_rune("=5-1"));
+    }
+
+    #[test]
+    fn test_escaped_restrictions() {
+        let rune = ClnRune::from_str(&encode_rune(r"=1&pnameamount_msat<10000|id=a\|b")).unwrap();
+        let restriction = &rune.restrictions()[0];
+        assert_eq!(restriction.alternatives.len(), 2);
+        assert_eq!(restriction.alternatives[1].value, "a|b");
+
+        let roundtrip = ClnRune::from_str(&rune.to_string()).unwrap();
+        assert_eq!(roundtrip, rune);
+    }
+
+    #[test]
+    fn test_invalid_rune() {
+        // no unique id
+        assert!(ClnRune::from_str(&encode_rune("method=getinfo")).is_err());
+        // just an authcode
+        assert!(ClnRune::from_str(&URL_SAFE_NO_PAD.encode([3; 32])).is_err());
+        assert!(ClnRune::from_str(&encode_rune("=0&not a restriction")).is_err());
+        assert!(ClnRune::from_str("not a rune").is_err());
+    }
+}
@@ -0,0 +1,216 @@
+use core::str::FromStr;
+
+use crate::bbqr::{self, BbqrDecoder};
+use crate::fountain::UrDecoder;
+use crate::PaymentParams;
+
+/// The kind of QR code being scanned
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ScanKind {
+    /// A single frame with a plain string
+    Static,
+    /// A BC-UR, single or multi-part
+    Ur,
+    /// A Coinkite BBQr
+    Bbqr,
+}
+
+#[derive(Debug, Clone)]
+enum ScanState {
+    Waiting,
+    Static(PaymentParams<'static>),
+    Ur(UrDecoder),
+    Bbqr(BbqrDecoder),
+}
+
+/// Takes every frame from a QR scanner and figures out whether it's a plain
+/// string, an animated UR or a BBQr, returning the [`PaymentParams`] once
+/// enough frames have been seen.
+///
+/// Frames that don't belong to the QR code currently being scanned are ignored,
+/// call [`ScanSession::reset`] to start scanning a different one.
+#[derive(Debug, Clone)]
+pub struct ScanSession {
+    state: ScanState,
+}
+
+impl Default for ScanSession {
+    fn default() -> Self {
+        Self {
+            state: ScanState::Waiting,
+        }
+    }
+}
+
+impl ScanSession {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feeds a scanned frame, returns false if it was ignored
+    pub fn receive(&mut self, frame: &str) -> bool {
+        match &mut self.state {
+            ScanState::Waiting => {
+                if frame.trim_start().to_lowercase().starts_with("ur:") {
+                    let mut decoder = UrDecoder::new();
+                    let accepted = decoder.receive(frame);
+                    if accepted {
+                        self.state = ScanState::Ur(decoder);
+                    }
+                    accepted
+                } else if bbqr::is_bbqr(frame) {
+                    let mut decoder = BbqrDecoder::new();
+                    let accepted = decoder.receive(frame);
+                    if accepted {
+                        self.state = ScanState::Bbqr(decoder);
+                    }
+                    accepted
+                } else {
+                    match PaymentParams::from_str(frame.trim()) {
+                        Ok(params) => {
+                            self.state = ScanState::Static(params);
+                            true
+                        }
+                        Err(_) => false,
+                    }
+                }
+            }
+            ScanState::Static(_) => false,
+            ScanState::Ur(decoder) => decoder.receive(frame),
+            ScanState::Bbqr(decoder) => decoder.receive(frame),
+        }
+    }
+
+    /// The kind of QR code being scanned, once the first frame is accepted
+    pub fn kind(&self) -> Option<ScanKind> {
+        match self.state {
+            ScanState::Waiting => None,
+            ScanState::Static(_) => Some(ScanKind::Static),
+            ScanState::Ur(_) => Some(ScanKind::Ur),
+            ScanState::Bbqr(_) => Some(ScanKind::Bbqr),
+        }
+    }
+
+    /// The fraction of the QR code scanned so far, between 0 and 1
+    pub fn progress(&self) -> f64 {
+        match &self.state {
+            ScanState::Waiting => 0.0,
+            ScanState::Static(_) => 1.0,
+            ScanState::Ur(decoder) => decoder.progress(),
+            ScanState::Bbqr(decoder) => decoder.progress(),
+        }
+    }
+
+    /// Whether every frame has been scanned, even if the payload
+    /// turned out to be a type we don't support
+    pub fn is_complete(&self) -> bool {
+        match &self.state {
+            ScanState::Waiting => false,
+            ScanState::Static(_) => true,
+            ScanState::Ur(decoder) => decoder.is_complete(),
+            ScanState::Bbqr(decoder) => decoder.is_complete(),
+        }
+    }
+
+    /// The decoded payload, once complete
+    pub fn result(&self) -> Option<PaymentParams<'static>> {
+        match &self.state {
+            ScanState::Waiting => None,
+            ScanState::Static(params) => Some(params.clone()),
+            ScanState::Ur(decoder) => decoder.result(),
+            ScanState::Bbqr(decoder) => decoder.result(),
+        }
+    }
+
+    /// Forgets every frame so a new QR code can be scanned
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bitcoin::{Amount, Network};
+
+    use super::*;
+
+    const BBQR_PSBT: [&str; 2] = [
+        "B$ZP0200FMUE4KXZZ7EFBSGEYDAMBKGWLBUC4577KPVUX45V56LGZLVOMDQGCTJ3XV4W2SUKXDYCSJ3R5FXQYIHQ6777772MAGD4C3AGGEDJCUVZWILZEE5HLUEZSIXDPJY7GYXFR7HNNV2WETRJKBSJ7EZ3FGFW",
+        "B$ZP0201T5T55GPM2TNQBZCW76L335X62BAYWWP3DC3XV747DNSKW63WLHF6KA434IHK4TYOTLSTNANJNYF56DNL5UON73SHLRZ2W7XRPPJGXR4DP7JIEZWZZPTOQT32S4ZODN6AUO3OU3JBBQGA3DCAZQAMCDAA",
+    ];
+
+    #[test]
+    fn test_scan_static() {
+        let mut session = ScanSession::new();
+        assert!(!session.receive("not anything"));
+        assert_eq!(session.kind(), None);
+
+        assert!(session.receive("bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq"));
+        assert_eq!(session.kind(), Some(ScanKind::Static));
+        assert!(session.is_complete());
+        assert_eq!(session.progress(), 1.0);
+        assert!(session.result().unwrap().address().is_some());
+
+        // the scanner keeps seeing the same code
+        assert!(!session.receive("bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq"));
+
+        session.reset();
+        assert_eq!(session.kind(), None);
+        assert!(session.result().is_none());
+    }
+
+    #[test]
+    fn test_scan_ur() {
+        let mut session = ScanSession::new();
+        assert!(session.receive("UR:CRYPTO-PSBT/2-3/LPAOAXCSVTCYDKHHUEASHDGRTYGHMWCEFETTQDOTCNWNFXFRTBPDHSAEAEAEAEAEAECMAEBBKPCKKOVSCFMEMTTYGHMWCEFETTQDOTCNWNFXFRTBAEAEAEAEGWADAAECLTTKAXRHIAFWLGLAAEAEAEKGZMCATBWSSASEETNYRNADRECLTDMWET"));
+        assert_eq!(session.kind(), Some(ScanKind::Ur));
+        assert_eq!(session.progress(), 1.0 / 3.0);
+
+        // a stray frame from something else
+        assert!(!session.receive(BBQR_PSBT[0]));
+        assert!(!session.receive("bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq"));
+
+        assert!(session.receive("UR:CRYPTO-PSBT/1-3/LPADAXCSVTCYDKHHUEASHDGRHDUEJOJKIDJYZMADAEJSAOAEAEAEADDSLYJSEMCKURWZLPWLEMPMWYOXQDKGKSAEBNAHIYSBQDPMIEIECHBWSGFWCHCWYNAEAEAEAEAEZCZMZMZMAOGDSRAEAEAEAEAEAECMAEBBKPCKKOVSCFMEMTMEFELNEN"));
+        assert!(session.receive("UR:CRYPTO-PSBT/3-3/LPAXAXCSVTCYDKHHUEASHDGRGRZMNTGMFSDPSNOLLRTERTPRHYSEPEIAGMNYFGMHAOKBQZBATPFTRLAYYNURHDTBFGKGVSZOMOWMFPSEZTIYGMNYFHCAJZDLMOWYIEMHZOBEVOLNKGRPGHAEAELAADAEAELAAEAEAELAAEAEAEAEAEAXSBFNVD"));
+        assert!(session.is_complete());
+
+        let result = session.result().unwrap();
+        assert_eq!(result.network(), Some(Network::Testnet));
+    }
+
+    #[test]
+    fn test_scan_bbqr_zlib() {
+        let mut session = ScanSession::new();
+        assert!(session.receive(BBQR_PSBT[1]));
+        assert_eq!(session.kind(), Some(ScanKind::Bbqr));
+        assert_eq!(session.progress(), 0.5);
+        assert!(session.result().is_none());
+
+        assert!(session.receive(BBQR_PSBT[0]));
+        assert!(session.is_complete());
+        let result = session.result().unwrap();
+        assert_eq!(result.psbt_output_amount(), Some(Amount::from_sat(75_000)));
+    }
+
+    #[test]
+    fn test_scan_bbqr_base32() {
+        let mut session = ScanSession::new();
+        for part in [
+            "B$2P0300OBZWE5H7AEAHCAQAAAAACJUBOE3R5X7SQXUTPLPOUSZXW6AABQCWNS5TVVSGIFYTZJBBOG7WAAAAAAAA7X7777YCKDBQAAAAAAAAAFQACR2R45XIDGIZNVCU",
+            "B$2P0302TVJD2LONU2CNHQFSL3A26Y2STJDJAAT6WQHNQOVXBD3N6WGWIZ56R64S5NA4D7DGKKND6HLMF6JO4ZEQ7MIOFBT3WZKAAAEAAEAABAAAAAAIAAAAAAAA",
+            "B$2P0301SQOELUNTUMR7CQZ322UGCAAAAAAAAAAWAAKHKHTW5AMZDFWUKSKBYRORWORSH4KDHPLAAAAAABHQCBBVQ7HQHOLDIKGYAAAAAB576HOW57BMCOE2XYA3KS77",
+        ] {
+            assert!(!session.is_complete());
+            assert!(session.receive(part));
+        }
+        assert!(session.result().unwrap().psbt().is_some());
+    }
+
+    #[test]
+    fn test_scan_bbqr_text() {
+        let mut session = ScanSession::new();
+        assert!(session.receive("B$HU010062633171617230737272723778666B7679356C3634336C79646E77397265353967747A7A7766356D6471"));
+        assert!(session.is_complete());
+        assert!(session.result().unwrap().address().is_some());
+    }
+}
@@ -0,0 +1,194 @@
+use core::convert::TryFrom;
+use core::fmt::{self, Write};
+use core::str::FromStr;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use bitcoin::hashes::hex::FromHex;
+use bitcoin::hashes::Hash;
+use bitcoin::{OutPoint, Txid};
+use serde_json::{json, Value};
+
+use crate::prelude::*;
+
+/// The nonce and MAC of the encrypted backup, anything shorter can't be a backup
+const MIN_BACKUP_LEN: usize = 24 + 16;
+
+/// An LND static channel backup, the JSON `lncli exportchanbackup --all` and the
+/// REST `/v1/channels/backup` endpoint output.
+///
+/// The backup itself is encrypted with a key derived from the node's seed, so
+/// only the channels it covers can be read. It can only be restored with the seed
+/// of the node that created it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LndChannelBackup {
+    chan_points: Vec<OutPoint>,
+    packed_backup: Vec<u8>,
+}
+
+impl LndChannelBackup {
+    /// The funding outpoints of the channels in the backup
+    pub fn chan_points(&self) -> &[OutPoint] {
+        &self.chan_points
+    }
+
+    /// The encrypted multi channel backup, what LND writes to `channel.backup`
+    pub fn packed_backup(&self) -> &[u8] {
+        &self.packed_backup
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::new(), |mut hex, b| {
+        let _ = write!(hex, "{b:02x}");
+        hex
+    })
+}
+
+/// lncli uses hex, the REST API base64
+fn decode_bytes(str: &str) -> Option<Vec<u8>> {
+    Vec::<u8>::from_hex(str)
+        .ok()
+        .or_else(|| STANDARD.decode(str).ok())
+}
+
+fn parse_chan_point(value: &Value) -> Option<OutPoint> {
+    let txid = match value.get("funding_txid_str").and_then(Value::as_str) {
+        Some(txid) => Txid::from_str(txid).ok()?,
+        // the REST API gives the txid in its internal byte order
+        None => {
+            let bytes = value.get("funding_txid_bytes")?.as_str()?;
+            Txid::from_slice(&STANDARD.decode(bytes).ok()?).ok()?
+        }
+    };
+    // zero values are omitted from the JSON
+    let vout = match value.get("output_index") {
+        Some(index) => u32::try_from(index.as_u64()?).ok()?,
+        None => 0,
+    };
+    Some(OutPoint::new(txid, vout))
+}
+
+/// Re-emits the backup as the JSON lncli outputs, without the single channel backups
+impl fmt::Display for LndChannelBackup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let chan_points: Vec<Value> = self
+            .chan_points
+            .iter()
+            .map(|chan_point| {
+                json!({
+                    "funding_txid_str": chan_point.txid.to_string(),
+                    "output_index": chan_point.vout,
+                })
+            })
+            .collect();
+        let json = json!({
+            "multi_chan_backup": {
+                "chan_points": chan_points,
+                "multi_chan_backup": to_hex(&self.packed_backup),
+            }
+        });
+        write!(f, "{json}")
+    }
+}
+
+impl FromStr for LndChannelBackup {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let json: Value = serde_json::from_str(s).map_err(|_| ())?;
+        let multi = json.get("multi_chan_backup").ok_or(())?;
+
+        let packed_backup = multi
+            .get("multi_chan_backup")
+            .and_then(Value::as_str)
+            .and_then(decode_bytes)
+            .ok_or(())?;
+        if packed_backup.len() < MIN_BACKUP_LEN {
+            return Err(());
+        }
+
+        let chan_points = multi
+            .get("chan_points")
+            .and_then(Value::as_array)
+            .ok_or(())?
+            .iter()
+            .map(parse_chan_point)
+            .collect::<Option<Vec<_>>>()
+            .ok_or(())?;
+
+        Ok(Self {
+            chan_points,
+            packed_backup,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::str::FromStr;
+
+    use super::*;
+
+    const TXID: &str = "a4b9a1f2b3e26dd0b4ac94d35cb4b9a1c56d4b2b6e8d14a7f8ac9d3e5f1c2b3a";
+
+    #[test]
+    fn test_parse_lncli_backup() {
+        let backup = "ab".repeat(120);
+        let json = format!(
+            r#"{{
+    "single_chan_backups": {{
+        "chan_backups": [
+            {{
+                "chan_point": {{ "funding_txid_str": "{TXID}", "output_index": 1 }},
+                "chan_backup": "{backup}"
+            }}
+        ]
+    }},
+    "multi_chan_backup": {{
+        "chan_points": [
+            {{ "funding_txid_str": "{TXID}", "output_index": 1 }},
+            {{ "funding_txid_str": "{TXID}" }}
+        ],
+        "multi_chan_backup": "{backup}"
+    }}
+}}"#
+        );
+
+        let parsed = LndChannelBackup::from_str(&json).unwrap();
+        let txid = Txid::from_str(TXID).unwrap();
+        assert_eq!(
+            parsed.chan_points(),
+            [OutPoint::new(txid, 1), OutPoint::new(txid, 0)]
+        );
+        assert_eq!(parsed.packed_backup(), vec![0xab; 120].as_slice());
+
+        let roundtrip = LndChannelBackup::from_str(&parsed.to_string()).unwrap();
+        assert_eq!(roundtrip, parsed);
+    }
+
+    #[test]
+    fn test_parse_rest_backup() {
+        let txid = Txid::from_str(TXID).unwrap();
+        let txid_bytes = STANDARD.encode(txid.as_byte_array());
+        let backup = STANDARD.encode([7; 64]);
+        let json = format!(
+            r#"{{"multi_chan_backup":{{"chan_points":[{{"funding_txid_bytes":"{txid_bytes}","output_index":2}}],"multi_chan_backup":"{backup}"}}}}"#
+        );
+
+        let parsed = LndChannelBackup::from_str(&json).unwrap();
+        assert_eq!(parsed.chan_points(), [OutPoint::new(txid, 2)]);
+        assert_eq!(parsed.packed_backup(), [7; 64].as_slice());
+    }
+
+    #[test]
+    fn test_invalid_backup() {
+        assert!(LndChannelBackup::from_str("{}").is_err());
+        // too short to be encrypted
+        assert!(LndChannelBackup::from_str(
+            r#"{"multi_chan_backup":{"chan_points":[],"multi_chan_backup":"abcd"}}"#
+        )
+        .is_err());
+        assert!(LndChannelBackup::from_str("not json").is_err());
+    }
+}
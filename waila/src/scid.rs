@@ -0,0 +1,119 @@
+use core::fmt;
+use core::str::FromStr;
+
+use crate::prelude::*;
+
+/// Lightning channels were first opened on mainnet in 2018, around this block height.
+/// Anything lower is almost certainly just a random number, e.g. a 13 digit phone number.
+const MIN_BLOCK_HEIGHT: u64 = 500_000;
+const MAX_BLOCK_HEIGHT: u64 = 0x00ff_ffff;
+const MAX_TX_INDEX: u64 = 0x00ff_ffff;
+const MAX_OUTPUT_INDEX: u64 = 0xffff;
+
+/// A lightning short channel id, identifies a channel by the location of its funding output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ShortChannelId(u64);
+
+impl ShortChannelId {
+    pub fn from_parts(block_height: u32, tx_index: u32, output_index: u16) -> Option<Self> {
+        let block_height = u64::from(block_height);
+        let tx_index = u64::from(tx_index);
+        if !(MIN_BLOCK_HEIGHT..=MAX_BLOCK_HEIGHT).contains(&block_height) || tx_index > MAX_TX_INDEX
+        {
+            return None;
+        }
+
+        Some(Self(
+            (block_height << 40) | (tx_index << 16) | u64::from(output_index),
+        ))
+    }
+
+    /// The height of the block the funding transaction was confirmed in
+    pub fn block_height(&self) -> u32 {
+        (self.0 >> 40) as u32
+    }
+
+    /// The index of the funding transaction in its block
+    pub fn tx_index(&self) -> u32 {
+        ((self.0 >> 16) & MAX_TX_INDEX) as u32
+    }
+
+    /// The index of the funding output in the funding transaction
+    pub fn output_index(&self) -> u16 {
+        (self.0 & MAX_OUTPUT_INDEX) as u16
+    }
+
+    /// The integer encoding used by LND and the lightning gossip protocol
+    pub fn as_u64(&self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for ShortChannelId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}x{}x{}",
+            self.block_height(),
+            self.tx_index(),
+            self.output_index()
+        )
+    }
+}
+
+impl FromStr for ShortChannelId {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts = s.split(['x', 'X']).collect::<Vec<_>>();
+        match parts.as_slice() {
+            [block_height, tx_index, output_index] => {
+                let block_height = block_height.parse().map_err(|_| ())?;
+                let tx_index = tx_index.parse().map_err(|_| ())?;
+                let output_index = output_index.parse().map_err(|_| ())?;
+                ShortChannelId::from_parts(block_height, tx_index, output_index).ok_or(())
+            }
+            [scid] => {
+                let scid: u64 = scid.parse().map_err(|_| ())?;
+                if scid >> 40 < MIN_BLOCK_HEIGHT {
+                    return Err(());
+                }
+                Ok(ShortChannelId(scid))
+            }
+            _ => Err(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::str::FromStr;
+
+    use super::ShortChannelId;
+
+    #[test]
+    fn test_parse_scid() {
+        let scid = ShortChannelId::from_str("812312x1303x1").unwrap();
+        assert_eq!(scid.block_height(), 812312);
+        assert_eq!(scid.tx_index(), 1303);
+        assert_eq!(scid.output_index(), 1);
+        assert_eq!(scid.to_string(), "812312x1303x1");
+
+        let from_u64 = ShortChannelId::from_str(&scid.as_u64().to_string()).unwrap();
+        assert_eq!(scid, from_u64);
+        assert_eq!(scid.as_u64(), 893146489467371521);
+    }
+
+    #[test]
+    fn test_invalid_scid() {
+        assert!(ShortChannelId::from_str("812312x1303").is_err());
+        assert!(ShortChannelId::from_str("812312x1303x70000").is_err());
+        assert!(ShortChannelId::from_str("16777216x1x1").is_err());
+        assert!(ShortChannelId::from_str("0x1x1").is_err());
+        assert!(ShortChannelId::from_str("12345").is_err());
+        assert!(ShortChannelId::from_str("499999x1x1").is_err());
+        assert!(ShortChannelId::from_str("1234567890123").is_err());
+        assert!(ShortChannelId::from_str("549754714376372224").is_err());
+        assert!(ShortChannelId::from_str("").is_err());
+    }
+}
@@ -0,0 +1,221 @@
+use core::fmt;
+use core::str::FromStr;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use bitcoin::address::AddressType;
+use bitcoin::secp256k1::Secp256k1;
+use bitcoin::sign_message::{signed_msg_hash, MessageSignature};
+use bitcoin::{Address, PublicKey};
+
+use crate::prelude::*;
+
+const BEGIN_MESSAGE: &str = "-----BEGIN BITCOIN SIGNED MESSAGE-----";
+const BEGIN_SIGNATURE: [&str; 2] = [
+    "-----BEGIN SIGNATURE-----",
+    "-----BEGIN BITCOIN SIGNATURE-----",
+];
+const END_MESSAGE: [&str; 2] = [
+    "-----END BITCOIN SIGNED MESSAGE-----",
+    "-----END BITCOIN SIGNATURE-----",
+];
+
+/// A message signed with a bitcoin address, the classic `signmessage` format.
+///
+/// Either the armored `-----BEGIN BITCOIN SIGNED MESSAGE-----` blob, which contains
+/// the message, address and signature, or just a base64 signature. A bare signature
+/// can't be verified without the message and address it was made for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignedMessage {
+    message: Option<String>,
+    address: Option<Address>,
+    signature: MessageSignature,
+}
+
+impl SignedMessage {
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+
+    /// The address the message claims to be signed by, see [`SignedMessage::verify`]
+    pub fn address(&self) -> Option<&Address> {
+        self.address.as_ref()
+    }
+
+    pub fn signature(&self) -> MessageSignature {
+        self.signature
+    }
+
+    /// The public key that made the signature, recovered from the message
+    pub fn recovered_pubkey(&self) -> Option<PublicKey> {
+        let message = self.message.as_ref()?;
+        let secp = Secp256k1::verification_only();
+        let mut pubkey = self
+            .signature
+            .recover_pubkey(&secp, signed_msg_hash(message))
+            .ok()?;
+
+        // BIP137 segwit signatures set the compressed flag differently, the
+        // keys are always compressed
+        if self.address.as_ref().and_then(|a| a.address_type()) != Some(AddressType::P2pkh) {
+            pubkey.compressed = true;
+        }
+        Some(pubkey)
+    }
+
+    /// Whether the signature was made by the key of the address, `None` for a
+    /// bare signature. Only single key P2PKH, P2WPKH and P2SH-P2WPKH addresses
+    /// can be verified, signatures for other addresses are never valid.
+    pub fn verify(&self) -> Option<bool> {
+        let address = self.address.as_ref()?;
+        Some(
+            self.recovered_pubkey()
+                .map_or(false, |pubkey| address.is_related_to_pubkey(&pubkey)),
+        )
+    }
+}
+
+impl fmt::Display for SignedMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let signature = STANDARD.encode(self.signature.serialize());
+        match (&self.message, &self.address) {
+            (Some(message), Some(address)) => write!(
+                f,
+                "{BEGIN_MESSAGE}\n{message}\n{}\n{address}\n{signature}\n{}",
+                BEGIN_SIGNATURE[0], END_MESSAGE[0]
+            ),
+            _ => write!(f, "{signature}"),
+        }
+    }
+}
+
+fn parse_signature(str: &str) -> Result<MessageSignature, ()> {
+    let bytes = STANDARD.decode(str.trim()).map_err(|_| ())?;
+    MessageSignature::from_slice(&bytes).map_err(|_| ())
+}
+
+impl FromStr for SignedMessage {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let armored = match s.trim().strip_prefix(BEGIN_MESSAGE) {
+            Some(armored) => armored,
+            None => {
+                return Ok(Self {
+                    message: None,
+                    address: None,
+                    signature: parse_signature(s)?,
+                })
+            }
+        };
+
+        let (message, rest) = BEGIN_SIGNATURE
+            .iter()
+            .find_map(|begin| armored.split_once(begin))
+            .ok_or(())?;
+        let rest = END_MESSAGE
+            .iter()
+            .find_map(|end| rest.trim_end().strip_suffix(end))
+            .ok_or(())?;
+
+        let mut lines = rest.lines().map(str::trim).filter(|l| !l.is_empty());
+        let address = Address::from_str(lines.next().ok_or(())?)
+            .map_err(|_| ())?
+            .assume_checked();
+        let signature = parse_signature(lines.next().ok_or(())?)?;
+        if lines.next().is_some() {
+            return Err(());
+        }
+
+        // the message starts on the line after the header
+        let message = message
+            .strip_prefix("\r\n")
+            .or_else(|| message.strip_prefix('\n'))
+            .unwrap_or(message);
+        let message = message
+            .strip_suffix("\r\n")
+            .or_else(|| message.strip_suffix('\n'))
+            .unwrap_or(message);
+
+        Ok(Self {
+            message: Some(message.replace("\r\n", "\n")),
+            address: Some(address),
+            signature,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::str::FromStr;
+
+    use bitcoin::secp256k1::SecretKey;
+    use bitcoin::{Network, PrivateKey};
+
+    use super::*;
+
+    fn sign(message: &str, key: &PrivateKey) -> MessageSignature {
+        let secp = Secp256k1::new();
+        let msg =
+            bitcoin::secp256k1::Message::from_slice(signed_msg_hash(message).as_ref()).unwrap();
+        let signature = secp.sign_ecdsa_recoverable(&msg, &key.inner);
+        MessageSignature::new(signature, key.compressed)
+    }
+
+    fn test_key() -> PrivateKey {
+        PrivateKey::new(SecretKey::from_slice(&[1; 32]).unwrap(), Network::Bitcoin)
+    }
+
+    #[test]
+    fn test_parse_signed_message() {
+        let key = test_key();
+        let secp = Secp256k1::new();
+        let address = Address::p2pkh(&key.public_key(&secp), Network::Bitcoin);
+        let signature = STANDARD.encode(sign("hello world", &key).serialize());
+        let str = format!(
+            "-----BEGIN BITCOIN SIGNED MESSAGE-----\nhello world\n-----BEGIN SIGNATURE-----\n{address}\n{signature}\n-----END BITCOIN SIGNED MESSAGE-----"
+        );
+
+        let parsed = SignedMessage::from_str(&str).unwrap();
+        assert_eq!(parsed.message(), Some("hello world"));
+        assert_eq!(parsed.address(), Some(&address));
+        assert_eq!(parsed.recovered_pubkey(), Some(key.public_key(&secp)));
+        assert_eq!(parsed.verify(), Some(true));
+        assert_eq!(parsed.to_string(), str);
+
+        // the signature doesn't match a different message
+        let tampered = SignedMessage::from_str(&str.replace("hello world", "hello moon")).unwrap();
+        assert_eq!(tampered.verify(), Some(false));
+    }
+
+    #[test]
+    fn test_parse_segwit_signed_message() {
+        let key = test_key();
+        let secp = Secp256k1::new();
+        let address = Address::p2wpkh(&key.public_key(&secp), Network::Bitcoin).unwrap();
+        let signature = STANDARD.encode(sign("multi\nline", &key).serialize());
+        let str = format!(
+            "-----BEGIN BITCOIN SIGNED MESSAGE-----\r\nmulti\r\nline\r\n-----BEGIN BITCOIN SIGNATURE-----\r\n{address}\r\n\r\n{signature}\r\n-----END BITCOIN SIGNATURE-----\r\n"
+        );
+
+        let parsed = SignedMessage::from_str(&str).unwrap();
+        assert_eq!(parsed.message(), Some("multi\nline"));
+        assert_eq!(parsed.verify(), Some(true));
+    }
+
+    #[test]
+    fn test_parse_bare_signature() {
+        let signature = STANDARD.encode(sign("hello world", &test_key()).serialize());
+        let parsed = SignedMessage::from_str(&signature).unwrap();
+        assert_eq!(parsed.message(), None);
+        assert_eq!(parsed.address(), None);
+        assert_eq!(parsed.verify(), None);
+        assert_eq!(parsed.to_string(), signature);
+
+        assert!(SignedMessage::from_str("aGVsbG8gd29ybGQ=").is_err());
+        assert!(SignedMessage::from_str(
+            "-----BEGIN BITCOIN SIGNED MESSAGE-----\nhello\n-----END BITCOIN SIGNED MESSAGE-----"
+        )
+        .is_err());
+    }
+}
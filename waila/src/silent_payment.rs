@@ -0,0 +1,179 @@
+use core::fmt;
+use core::str::FromStr;
+
+use bitcoin::bech32::{self, FromBase32, ToBase32, Variant};
+use bitcoin::secp256k1::PublicKey;
+use bitcoin::Network;
+
+use crate::prelude::*;
+
+/// Version 31 is reserved for a future backwards incompatible upgrade
+const MAX_VERSION: u8 = 30;
+const KEY_LENGTH: usize = 33;
+
+/// A BIP-352 silent payment address
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SilentPaymentAddress {
+    version: u8,
+    network: Network,
+    scan_key: PublicKey,
+    spend_key: PublicKey,
+}
+
+impl SilentPaymentAddress {
+    pub fn new(network: Network, scan_key: PublicKey, spend_key: PublicKey) -> Self {
+        Self {
+            version: 0,
+            network,
+            scan_key,
+            spend_key,
+        }
+    }
+
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    /// The network the address is for, testnet addresses are also valid on signet
+    pub fn network(&self) -> Network {
+        self.network
+    }
+
+    /// The key the receiver scans the chain with
+    pub fn scan_key(&self) -> PublicKey {
+        self.scan_key
+    }
+
+    /// The key the receiver's outputs are tweaked from
+    pub fn spend_key(&self) -> PublicKey {
+        self.spend_key
+    }
+
+    pub fn is_valid_for_network(&self, network: Network) -> bool {
+        match (self.network, network) {
+            (a, b) if a == b => true,
+            (Network::Testnet, Network::Signet) => true,
+            _ => false,
+        }
+    }
+
+    fn hrp(&self) -> &'static str {
+        match self.network {
+            Network::Bitcoin => "sp",
+            Network::Regtest => "sprt",
+            _ => "tsp",
+        }
+    }
+}
+
+impl fmt::Display for SilentPaymentAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut data = vec![bech32::u5::try_from_u8(self.version).map_err(|_| fmt::Error)?];
+        let mut keys = self.scan_key.serialize().to_vec();
+        keys.extend_from_slice(&self.spend_key.serialize());
+        data.extend(keys.to_base32());
+
+        bech32::encode_to_fmt(f, self.hrp(), data, Variant::Bech32m).map_err(|_| fmt::Error)?
+    }
+}
+
+impl FromStr for SilentPaymentAddress {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (hrp, data, variant) = bech32::decode(s).map_err(|_| ())?;
+        let network = match hrp.as_str() {
+            "sp" => Network::Bitcoin,
+            "tsp" => Network::Testnet,
+            "sprt" => Network::Regtest,
+            _ => return Err(()),
+        };
+        if variant != Variant::Bech32m {
+            return Err(());
+        }
+
+        let (version, data) = data.split_first().ok_or(())?;
+        let version = version.to_u8();
+        if version > MAX_VERSION {
+            return Err(());
+        }
+
+        let bytes = Vec::<u8>::from_base32(data).map_err(|_| ())?;
+        // future versions may append data, which we must ignore
+        if bytes.len() < KEY_LENGTH * 2 || (version == 0 && bytes.len() != KEY_LENGTH * 2) {
+            return Err(());
+        }
+
+        let scan_key = PublicKey::from_slice(&bytes[..KEY_LENGTH]).map_err(|_| ())?;
+        let spend_key =
+            PublicKey::from_slice(&bytes[KEY_LENGTH..KEY_LENGTH * 2]).map_err(|_| ())?;
+
+        Ok(Self {
+            version,
+            network,
+            scan_key,
+            spend_key,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::str::FromStr;
+
+    use bitcoin::secp256k1::PublicKey;
+    use bitcoin::Network;
+
+    use super::SilentPaymentAddress;
+
+    const ADDRESS: &str = "sp1qqgste7k9hx0qftg6qmwlkqtwuy6cycyavzmzj85c6qdfhjdpdjtdgqjuexzk6murw56suy3e0rd2cgqvycxttddwsvgxe2usfpxumr70xc9pkqwv";
+
+    #[test]
+    fn test_parse_silent_payment_address() {
+        let address = SilentPaymentAddress::from_str(ADDRESS).unwrap();
+        assert_eq!(address.version(), 0);
+        assert_eq!(address.network(), Network::Bitcoin);
+        assert_eq!(
+            address.scan_key(),
+            PublicKey::from_str(
+                "0220bcfac5b99e04ad1a06ddfb016ee13582609d60b6291e98d01a9bc9a16c96d4"
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            address.spend_key(),
+            PublicKey::from_str(
+                "025cc9856d6f8375350e123978daac200c260cb5b5ae83106cab90484dcd8fcf36"
+            )
+            .unwrap()
+        );
+        assert_eq!(address.to_string(), ADDRESS);
+
+        let upper = SilentPaymentAddress::from_str(&ADDRESS.to_uppercase()).unwrap();
+        assert_eq!(upper, address);
+    }
+
+    #[test]
+    fn test_testnet_silent_payment_address() {
+        let address = SilentPaymentAddress::from_str("tsp1qqgste7k9hx0qftg6qmwlkqtwuy6cycyavzmzj85c6qdfhjdpdjtdgqjuexzk6murw56suy3e0rd2cgqvycxttddwsvgxe2usfpxumr70xc3wk4yh").unwrap();
+        assert_eq!(address.network(), Network::Testnet);
+        assert!(address.is_valid_for_network(Network::Testnet));
+        assert!(address.is_valid_for_network(Network::Signet));
+        assert!(!address.is_valid_for_network(Network::Bitcoin));
+
+        let roundtrip =
+            SilentPaymentAddress::new(Network::Testnet, address.scan_key(), address.spend_key());
+        assert_eq!(roundtrip, address);
+    }
+
+    #[test]
+    fn test_invalid_silent_payment_address() {
+        // bad checksum
+        assert!(SilentPaymentAddress::from_str(&ADDRESS.replace("pkqwv", "pkqww")).is_err());
+        // wrong hrp
+        assert!(
+            SilentPaymentAddress::from_str("bc1qar0srrr7xfkvy5l643lydnw9re59gtzzwf5mdq").is_err()
+        );
+        assert!(SilentPaymentAddress::from_str("").is_err());
+    }
+}
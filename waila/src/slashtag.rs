@@ -0,0 +1,165 @@
+use core::fmt;
+use core::str::FromStr;
+
+use crate::prelude::*;
+
+const SLASH_SCHEME: &str = "slash:";
+
+/// The z-base32 alphabet slashtags encode keys with
+const ZBASE32_ALPHABET: &[u8; 32] = b"ybndrfg8ejkmcpqxot1uwisza345h769";
+
+/// A 32 byte key is 52 z-base32 characters
+const ENCODED_KEY_LEN: usize = 52;
+
+/// A Slashtags `slash:` URL, used by Bitkit to share contacts and the payment
+/// details published under them, e.g. `slash:<key>/public/slashpay.json`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Slashtag {
+    public_key: [u8; 32],
+    path: Option<String>,
+}
+
+impl Slashtag {
+    /// The ed25519 public key of the slashtag's drive
+    pub fn public_key(&self) -> [u8; 32] {
+        self.public_key
+    }
+
+    /// The z-base32 encoded public key, how slashtags display keys
+    pub fn encoded_public_key(&self) -> String {
+        encode_zbase32(&self.public_key)
+    }
+
+    /// The path of a file in the slashtag's drive, including any query or fragment
+    pub fn path(&self) -> Option<&str> {
+        self.path.as_deref()
+    }
+}
+
+fn encode_zbase32(bytes: &[u8]) -> String {
+    let mut encoded = String::new();
+    let mut buffer = 0u16;
+    let mut bits = 0;
+    for byte in bytes {
+        buffer = (buffer << 8) | u16::from(*byte);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            encoded.push(ZBASE32_ALPHABET[usize::from((buffer >> bits) & 0x1f)] as char);
+        }
+    }
+    if bits > 0 {
+        encoded.push(ZBASE32_ALPHABET[usize::from((buffer << (5 - bits)) & 0x1f)] as char);
+    }
+    encoded
+}
+
+fn decode_zbase32_key(str: &str) -> Option<[u8; 32]> {
+    if str.len() != ENCODED_KEY_LEN {
+        return None;
+    }
+
+    let mut key = [0; 32];
+    let mut buffer = 0u16;
+    let mut bits = 0;
+    let mut index = 0;
+    for c in str.bytes() {
+        let value = ZBASE32_ALPHABET.iter().position(|a| *a == c)? as u16;
+        buffer = (buffer << 5) | value;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            // the trailing padding bits are dropped
+            if index < key.len() {
+                key[index] = (buffer >> bits) as u8;
+                index += 1;
+            }
+        }
+    }
+
+    // the padding must be zero so each key has a single encoding
+    if buffer & ((1 << bits) - 1) != 0 {
+        return None;
+    }
+    Some(key)
+}
+
+impl fmt::Display for Slashtag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{SLASH_SCHEME}{}", self.encoded_public_key())?;
+        if let Some(path) = &self.path {
+            write!(f, "{path}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Slashtag {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rest = s
+            .get(..SLASH_SCHEME.len())
+            .filter(|scheme| scheme.eq_ignore_ascii_case(SLASH_SCHEME))
+            .map(|_| &s[SLASH_SCHEME.len()..])
+            .ok_or(())?;
+        let rest = rest.strip_prefix("//").unwrap_or(rest);
+
+        let end = rest.find(['/', '?', '#']).unwrap_or(rest.len());
+        let public_key = decode_zbase32_key(&rest[..end]).ok_or(())?;
+        let path = Some(&rest[end..])
+            .filter(|path| !path.is_empty() && *path != "/")
+            .map(|path| path.to_string());
+
+        Ok(Self { public_key, path })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::str::FromStr;
+
+    use super::*;
+
+    const KEY: &str = "3cdufxt5xbtrnhxjqpe86rnakcdyr86hy1p5dgodohwk11ea6uzo";
+
+    #[test]
+    fn test_zbase32_roundtrip() {
+        let key = decode_zbase32_key(KEY).unwrap();
+        assert_eq!(encode_zbase32(&key), KEY);
+
+        assert_eq!(encode_zbase32(&[0; 32]), "y".repeat(52));
+        assert_eq!(decode_zbase32_key(&"y".repeat(52)), Some([0; 32]));
+    }
+
+    #[test]
+    fn test_parse_slashtag() {
+        let slashtag = Slashtag::from_str(&format!("slash:{KEY}")).unwrap();
+        assert_eq!(slashtag.encoded_public_key(), KEY);
+        assert_eq!(slashtag.path(), None);
+        assert_eq!(slashtag.to_string(), format!("slash:{KEY}"));
+
+        let str = format!("slash://{KEY}/public/slashpay.json?relay=http://localhost:3000");
+        let slashtag = Slashtag::from_str(&str).unwrap();
+        assert_eq!(
+            slashtag.path(),
+            Some("/public/slashpay.json?relay=http://localhost:3000")
+        );
+        assert_eq!(
+            slashtag.to_string(),
+            format!("slash:{KEY}/public/slashpay.json?relay=http://localhost:3000")
+        );
+    }
+
+    #[test]
+    fn test_invalid_slashtag() {
+        assert!(Slashtag::from_str(KEY).is_err());
+        assert!(Slashtag::from_str("slash:").is_err());
+        // l isn't in the alphabet
+        assert!(Slashtag::from_str(&format!("slash:{}", KEY.replace('3', "l"))).is_err());
+        // too short
+        assert!(Slashtag::from_str(&format!("slash:{}", &KEY[1..])).is_err());
+        // the padding bits are set
+        assert!(Slashtag::from_str(&format!("slash:{}", KEY.replace('o', "a"))).is_err());
+    }
+}
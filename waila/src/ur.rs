@@ -0,0 +1,513 @@
+use core::convert::TryInto;
+use core::fmt::Write;
+use core::str::FromStr;
+
+use bitcoin::bip32::{ChainCode, ChildNumber, ExtendedPubKey, Fingerprint};
+use bitcoin::psbt::PartiallySignedTransaction;
+use bitcoin::secp256k1::PublicKey;
+use bitcoin::Network;
+use miniscript::descriptor::{Descriptor, DescriptorPublicKey};
+
+use crate::prelude::*;
+use crate::{PaymentParams, Xpub};
+
+/// The bytewords wordlist, minimal bytewords only use the first and last letter
+const BYTEWORDS: [&str; 256] = [
+    "able", "acid", "also", "apex", "aqua", "arch", "atom", "aunt", "away", "axis", "back", "bald",
+    "barn", "belt", "beta", "bias", "blue", "body", "brag", "brew", "bulb", "buzz", "calm", "cash",
+    "cats", "chef", "city", "claw", "code", "cola", "cook", "cost", "crux", "curl", "cusp", "cyan",
+    "dark", "data", "days", "deli", "dice", "diet", "door", "down", "draw", "drop", "drum", "dull",
+    "duty", "each", "easy", "echo", "edge", "epic", "even", "exam", "exit", "eyes", "fact", "fair",
+    "fern", "figs", "film", "fish", "fizz", "flap", "flew", "flux", "foxy", "free", "frog", "fuel",
+    "fund", "gala", "game", "gear", "gems", "gift", "girl", "glow", "good", "gray", "grim", "guru",
+    "gush", "gyro", "half", "hang", "hard", "hawk", "heat", "help", "high", "hill", "holy", "hope",
+    "horn", "huts", "iced", "idea", "idle", "inch", "inky", "into", "iris", "iron", "item", "jade",
+    "jazz", "join", "jolt", "jowl", "judo", "jugs", "jump", "junk", "jury", "keep", "keno", "kept",
+    "keys", "kick", "kiln", "king", "kite", "kiwi", "knob", "lamb", "lava", "lazy", "leaf", "legs",
+    "liar", "limp", "lion", "list", "logo", "loud", "love", "luau", "luck", "lung", "main", "many",
+    "math", "maze", "memo", "menu", "meow", "mild", "mint", "miss", "monk", "nail", "navy", "need",
+    "news", "next", "noon", "note", "numb", "obey", "oboe", "omit", "onyx", "open", "oval", "owls",
+    "paid", "part", "peck", "play", "plus", "poem", "pool", "pose", "puff", "puma", "purr", "quad",
+    "quiz", "race", "ramp", "real", "redo", "rich", "road", "rock", "roof", "ruby", "ruin", "runs",
+    "rust", "safe", "saga", "scar", "sets", "silk", "skew", "slot", "soap", "solo", "song", "stub",
+    "surf", "swan", "taco", "task", "taxi", "tent", "tied", "time", "tiny", "toil", "tomb", "toys",
+    "trip", "tuna", "twin", "ugly", "undo", "unit", "urge", "user", "vast", "very", "veto", "vial",
+    "vibe", "view", "visa", "void", "vows", "wall", "wand", "warm", "wasp", "wave", "waxy", "webs",
+    "what", "when", "whiz", "wolf", "work", "yank", "yawn", "yell", "yoga", "yurt", "zaps", "zero",
+    "zest", "zinc", "zone", "zoom",
+];
+
+// CBOR tags from the BCR-2020-006 registry
+const TAG_HDKEY: u64 = 303;
+const TAG_KEYPATH: u64 = 304;
+const TAG_COININFO: u64 = 305;
+const TAG_ECKEY: u64 = 306;
+const TAG_OUTPUT: u64 = 308;
+const TAG_SH: u64 = 400;
+const TAG_WSH: u64 = 401;
+const TAG_PK: u64 = 402;
+const TAG_PKH: u64 = 403;
+const TAG_WPKH: u64 = 404;
+const TAG_MULTI: u64 = 406;
+const TAG_SORTED_MULTI: u64 = 407;
+const TAG_TR: u64 = 409;
+
+/// Nesting limit so malicious payloads can't blow the stack
+const MAX_CBOR_DEPTH: usize = 16;
+
+pub(crate) fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xffff_ffffu32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            let mask = (!(crc & 1)).wrapping_add(1);
+            crc = (crc >> 1) ^ (0xedb8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+/// Decodes minimal bytewords and verifies the trailing CRC32 checksum
+pub(crate) fn decode_bytewords(str: &str) -> Option<Vec<u8>> {
+    if str.len() % 2 != 0 || !str.is_ascii() {
+        return None;
+    }
+
+    let bytes = str
+        .as_bytes()
+        .chunks(2)
+        .map(|pair| {
+            BYTEWORDS.iter().position(|word| {
+                let word = word.as_bytes();
+                word[0] == pair[0] && word[3] == pair[1]
+            })
+        })
+        .map(|i| i.map(|i| i as u8))
+        .collect::<Option<Vec<u8>>>()?;
+
+    if bytes.len() < 4 {
+        return None;
+    }
+    let (data, checksum) = bytes.split_at(bytes.len() - 4);
+    if crc32(data).to_be_bytes() != checksum {
+        return None;
+    }
+
+    Some(data.to_vec())
+}
+
+/// The subset of CBOR used by the UR registry types
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Cbor {
+    Uint(u64),
+    Bytes(Vec<u8>),
+    Text(String),
+    Array(Vec<Cbor>),
+    Map(Vec<(Cbor, Cbor)>),
+    Tag(u64, Box<Cbor>),
+    Bool(bool),
+}
+
+impl Cbor {
+    pub(crate) fn decode(data: &[u8]) -> Option<Cbor> {
+        let mut reader = CborReader { data, pos: 0 };
+        let value = reader.read(0)?;
+        if reader.pos != data.len() {
+            return None;
+        }
+        Some(value)
+    }
+
+    fn get(&self, key: u64) -> Option<&Cbor> {
+        match self {
+            Cbor::Map(entries) => entries
+                .iter()
+                .find(|(k, _)| *k == Cbor::Uint(key))
+                .map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_uint(&self) -> Option<u64> {
+        match self {
+            Cbor::Uint(n) => Some(*n),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Cbor::Bytes(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+
+    fn as_bool(&self) -> Option<bool> {
+        match self {
+            Cbor::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    /// Strips the given tag, tags are optional when the type is implied
+    fn untag(&self, tag: u64) -> &Cbor {
+        match self {
+            Cbor::Tag(t, inner) if *t == tag => inner,
+            _ => self,
+        }
+    }
+}
+
+struct CborReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl CborReader<'_> {
+    fn take(&mut self, len: usize) -> Option<&[u8]> {
+        let end = self.pos.checked_add(len)?;
+        let bytes = self.data.get(self.pos..end)?;
+        self.pos = end;
+        Some(bytes)
+    }
+
+    fn read_argument(&mut self, info: u8) -> Option<u64> {
+        match info {
+            0..=23 => Some(info as u64),
+            24 => self.take(1).map(|b| b[0] as u64),
+            25 => self
+                .take(2)
+                .map(|b| u16::from_be_bytes(b.try_into().unwrap()) as u64),
+            26 => self
+                .take(4)
+                .map(|b| u32::from_be_bytes(b.try_into().unwrap()) as u64),
+            27 => self
+                .take(8)
+                .map(|b| u64::from_be_bytes(b.try_into().unwrap())),
+            // indefinite lengths aren't used by any of the types we support
+            _ => None,
+        }
+    }
+
+    fn read(&mut self, depth: usize) -> Option<Cbor> {
+        if depth > MAX_CBOR_DEPTH {
+            return None;
+        }
+
+        let initial = *self.take(1)?.first()?;
+        let major = initial >> 5;
+        let argument = self.read_argument(initial & 0x1f)?;
+        // every item is at least a byte, so this bounds allocations by the input size
+        let remaining = (self.data.len() - self.pos) as u64;
+
+        match major {
+            0 => Some(Cbor::Uint(argument)),
+            2 => self
+                .take(argument.try_into().ok()?)
+                .map(|b| Cbor::Bytes(b.to_vec())),
+            3 => {
+                let bytes = self.take(argument.try_into().ok()?)?;
+                String::from_utf8(bytes.to_vec()).ok().map(Cbor::Text)
+            }
+            4 if argument <= remaining => (0..argument)
+                .map(|_| self.read(depth + 1))
+                .collect::<Option<Vec<_>>>()
+                .map(Cbor::Array),
+            5 if argument <= remaining => (0..argument)
+                .map(|_| Some((self.read(depth + 1)?, self.read(depth + 1)?)))
+                .collect::<Option<Vec<_>>>()
+                .map(Cbor::Map),
+            6 => self
+                .read(depth + 1)
+                .map(|inner| Cbor::Tag(argument, Box::new(inner))),
+            7 => match argument {
+                20 => Some(Cbor::Bool(false)),
+                21 => Some(Cbor::Bool(true)),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+/// Returns the `[fingerprint/path]` origin of a crypto-keypath along with its depth
+fn key_path(cbor: &Cbor, default_fingerprint: Option<u32>) -> Option<(String, Vec<ChildNumber>)> {
+    let cbor = cbor.untag(TAG_KEYPATH);
+    let components = match cbor.get(1)? {
+        Cbor::Array(components) => components,
+        _ => return None,
+    };
+
+    let mut path = Vec::with_capacity(components.len() / 2);
+    for pair in components.chunks(2) {
+        let index = pair.first()?.as_uint()?.try_into().ok()?;
+        let child = if pair.get(1)?.as_bool()? {
+            ChildNumber::from_hardened_idx(index).ok()?
+        } else {
+            ChildNumber::from_normal_idx(index).ok()?
+        };
+        path.push(child);
+    }
+
+    let fingerprint = match cbor.get(2) {
+        Some(fingerprint) => Some(fingerprint.as_uint()?.try_into().ok()?),
+        None => default_fingerprint,
+    };
+    let origin = fingerprint
+        .map(|fingerprint: u32| {
+            let mut origin = format!("[{}", Fingerprint::from(fingerprint.to_be_bytes()));
+            for child in &path {
+                let _ = write!(origin, "/{child}");
+            }
+            origin.push(']');
+            origin
+        })
+        .unwrap_or_default();
+
+    Some((origin, path))
+}
+
+/// Decodes a crypto-hdkey into an xpub, private keys are rejected
+fn hdkey(cbor: &Cbor) -> Option<ExtendedPubKey> {
+    let cbor = cbor.untag(TAG_HDKEY);
+    if cbor.get(2).and_then(Cbor::as_bool) == Some(true) {
+        return None;
+    }
+
+    let public_key = PublicKey::from_slice(cbor.get(3)?.as_bytes()?).ok()?;
+    let chain_code: [u8; 32] = cbor.get(4)?.as_bytes()?.try_into().ok()?;
+    let network = match cbor.get(5).map(|info| info.untag(TAG_COININFO).get(2)) {
+        Some(Some(network)) if network.as_uint()? == 1 => Network::Testnet,
+        _ => Network::Bitcoin,
+    };
+
+    let (depth, child_number) = match cbor.get(6) {
+        Some(origin) => {
+            let (_, path) = key_path(origin, None)?;
+            let depth = match origin.untag(TAG_KEYPATH).get(3) {
+                Some(depth) => depth.as_uint()?.try_into().ok()?,
+                None => path.len().try_into().ok()?,
+            };
+            let child_number = path.last().copied().unwrap_or(ChildNumber::from(0));
+            (depth, child_number)
+        }
+        None => (0, ChildNumber::from(0)),
+    };
+    let parent_fingerprint = match cbor.get(8) {
+        Some(fingerprint) => {
+            let fingerprint: u32 = fingerprint.as_uint()?.try_into().ok()?;
+            Fingerprint::from(fingerprint.to_be_bytes())
+        }
+        None => Fingerprint::default(),
+    };
+
+    Some(ExtendedPubKey {
+        network,
+        depth,
+        parent_fingerprint,
+        child_number,
+        public_key,
+        chain_code: ChainCode::from(chain_code),
+    })
+}
+
+/// Converts a crypto-hdkey or crypto-eckey into a descriptor key expression
+fn key_expression(cbor: &Cbor, master_fingerprint: Option<u32>) -> Option<String> {
+    match cbor {
+        Cbor::Tag(TAG_HDKEY, inner) => {
+            let xpub = hdkey(inner)?;
+            let origin = match inner.get(6) {
+                Some(origin) => key_path(origin, master_fingerprint)?.0,
+                None => String::new(),
+            };
+            // accounts usually leave out the children, assume the receive chain
+            let children = match inner.get(7) {
+                Some(children) => {
+                    let (_, path) = key_path(children, None)?;
+                    path.iter().fold(String::new(), |mut s, c| {
+                        let _ = write!(s, "/{c}");
+                        s
+                    })
+                }
+                None => "/0".to_string(),
+            };
+            Some(format!("{origin}{xpub}{children}/*"))
+        }
+        Cbor::Tag(TAG_ECKEY, inner) => {
+            if inner.get(2).and_then(Cbor::as_bool) == Some(true) {
+                return None;
+            }
+            let key = PublicKey::from_slice(inner.get(3)?.as_bytes()?).ok()?;
+            Some(key.to_string())
+        }
+        _ => None,
+    }
+}
+
+/// Converts a crypto-output script expression into a descriptor string
+fn script_expression(cbor: &Cbor, master_fingerprint: Option<u32>, depth: usize) -> Option<String> {
+    if depth > MAX_CBOR_DEPTH {
+        return None;
+    }
+
+    let (tag, inner) = match cbor {
+        Cbor::Tag(tag, inner) => (*tag, inner.as_ref()),
+        _ => return None,
+    };
+
+    let name = match tag {
+        TAG_OUTPUT => return script_expression(inner, master_fingerprint, depth + 1),
+        TAG_SH => "sh",
+        TAG_WSH => "wsh",
+        TAG_PK => "pk",
+        TAG_PKH => "pkh",
+        TAG_WPKH => "wpkh",
+        TAG_TR => "tr",
+        TAG_MULTI => "multi",
+        TAG_SORTED_MULTI => "sortedmulti",
+        _ => return None,
+    };
+
+    let body = match tag {
+        TAG_MULTI | TAG_SORTED_MULTI => {
+            let threshold = inner.get(1)?.as_uint()?;
+            let keys = match inner.get(2)? {
+                Cbor::Array(keys) => keys
+                    .iter()
+                    .map(|key| key_expression(key, master_fingerprint))
+                    .collect::<Option<Vec<_>>>()?,
+                _ => return None,
+            };
+            format!("{threshold},{}", keys.join(","))
+        }
+        _ => key_expression(inner, master_fingerprint)
+            .or_else(|| script_expression(inner, master_fingerprint, depth + 1))?,
+    };
+
+    Some(format!("{name}({body})"))
+}
+
+fn parse_descriptor(str: &str) -> Option<PaymentParams<'static>> {
+    Descriptor::<DescriptorPublicKey>::from_str(str)
+        .ok()
+        .map(|desc| PaymentParams::Descriptor(Box::new(desc)))
+}
+
+/// Maps a decoded UR payload to the matching [`PaymentParams`]
+pub(crate) fn decode_payload(ur_type: &str, payload: &[u8]) -> Option<PaymentParams<'static>> {
+    let cbor = Cbor::decode(payload)?;
+
+    match ur_type {
+        "crypto-psbt" => {
+            let psbt = PartiallySignedTransaction::deserialize(cbor.as_bytes()?).ok()?;
+            Some(PaymentParams::Psbt(Box::new(psbt)))
+        }
+        "bytes" => {
+            let bytes = cbor.as_bytes()?;
+            PartiallySignedTransaction::deserialize(bytes)
+                .map(|psbt| PaymentParams::Psbt(Box::new(psbt)))
+                .ok()
+                .or_else(|| PaymentParams::from_bytes(bytes).ok())
+        }
+        "crypto-hdkey" => hdkey(&cbor).map(|key| {
+            PaymentParams::Xpub(Xpub {
+                key,
+                script_type: None,
+            })
+        }),
+        "crypto-output" => parse_descriptor(&script_expression(
+            &Cbor::Tag(TAG_OUTPUT, Box::new(cbor)),
+            None,
+            0,
+        )?),
+        "crypto-account" => {
+            let master_fingerprint: u32 = cbor.get(1)?.as_uint()?.try_into().ok()?;
+            // an account lists an output per script type, we can only return one
+            let output = match cbor.get(2)? {
+                Cbor::Array(outputs) => outputs.first()?,
+                _ => return None,
+            };
+            parse_descriptor(&script_expression(output, Some(master_fingerprint), 0)?)
+        }
+        _ => None,
+    }
+}
+
+/// Parses a single-part `ur:type/bytewords` string
+pub(crate) fn parse_ur(str: &str) -> Option<PaymentParams<'static>> {
+    // URs are usually uppercase so they fit alphanumeric QR codes
+    let lower = str.to_lowercase();
+    let (ur_type, body) = lower.strip_prefix("ur:")?.split_once('/')?;
+    // multi-part URs have a sequence number before the body
+    if body.contains('/') {
+        return None;
+    }
+
+    let payload = decode_bytewords(body)?;
+    decode_payload(ur_type, &payload)
+}
+
+#[cfg(test)]
+mod test {
+    use bitcoin::Network;
+
+    use super::*;
+
+    #[test]
+    fn test_decode_bytewords() {
+        // from BCR-2020-005
+        let payload = decode_bytewords("hdeymejtswhhylkepmykhhtsytsnoyoyaxaedsuttydmmhhpktpmsrjtgwdpfnsboxgwlbaawzuefywkdplrsrjynbvygabwjldapfcsdwkbrkch").unwrap();
+        assert_eq!(payload.len(), 52);
+        assert_eq!(payload[..2], [0x58, 0x32]);
+        assert_eq!(
+            Cbor::decode(&payload).unwrap().as_bytes().unwrap().len(),
+            50
+        );
+
+        // bad checksum
+        assert!(decode_bytewords("hdeymejtswhhylkepmykhhtsytsnoyoyaxaedsuttydmmhhpktpmsrjtgwdpfnsboxgwlbaawzuefywkdplrsrjynbvygabwjldapfcsdwkbrkcd").is_none());
+        // not a byteword
+        assert!(decode_bytewords("qqqqqqqqqq").is_none());
+    }
+
+    #[test]
+    fn test_cbor_limits() {
+        // array claiming more items than there are bytes
+        assert!(Cbor::decode(&[0x9b, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]).is_none());
+        // byte string longer than the input
+        assert!(Cbor::decode(&[0x58, 0x20, 0x00]).is_none());
+        // trailing data
+        assert!(Cbor::decode(&[0x01, 0x02]).is_none());
+        // deeply nested arrays
+        assert!(Cbor::decode(&[0x81; 64]).is_none());
+    }
+
+    #[test]
+    fn test_parse_crypto_hdkey() {
+        let params = parse_ur("UR:CRYPTO-HDKEY/ONAXHDCLAOHKHGBNAETLWLKSBDPFBBFDCTENCTIAVDOLTSYLEHKIASQDOXWFNNGDREIDCHJEMNAAHDCXPDYKDSLNNYPARSMWSBYLCMGTVEAEEYOXPTMYOLHFFMADRSMHVTTBHTMWLUSKUOPLAHTAADEHNBAMTAADDYOTADLNCSGHYKAEYKAEYKAOCYVOLNKGRPAXAXAYCYRYMEDIEEWELTLYCN").unwrap();
+        let xpub = params.xpub().unwrap();
+        assert_eq!(xpub.network(), Network::Bitcoin);
+        assert_eq!(xpub.key.to_string(), "xpub6D3anNhuPFsyitz6c7Kgp1sS3Dcz5CReqh3Pf8CBaBxNy9PYSuKKz4869P2hbxQJccVAKAbfU5xz7VYDBQDcubAPNZ73ADBoxvnMB4PXXYE");
+
+        let params = parse_ur("UR:CRYPTO-HDKEY/ONAXHDCLAOKBQZBATPFTRLAYYNURHDTBFGKGVSZOMOWMFPSEZTIYGMNYFHCAJZDLMOWYIEMHZOAAHDCXKGZMCATBWSSASEETNYRNADREGRZMNTGMFSDPSNOLLRTERTPRHYSEPEIAGMNYFGMHAHTAADEHOYAOADAMTAADDYOTADLNCSGHYKADYKAEYKAOCYVOLNKGRPAXAXAYCYRHIAFWLGSSRLJOLB").unwrap();
+        let tpub = params.xpub().unwrap();
+        assert_eq!(tpub.network(), Network::Testnet);
+        assert_eq!(tpub.key.to_string(), "tpubDDPRy5xWxJTuVmsh7YRzK8o2EdMWgn4t41fTLxXRgyRN7EKvN2L8BKCFC1gUfPu8Xp6rr667Yc26zrXsiBZsgBc8dQiYnhPNk2Q7CsBrer5");
+    }
+
+    #[test]
+    fn test_parse_crypto_output() {
+        let params = parse_ur("UR:CRYPTO-OUTPUT/TAADMETAADMSOEADAOAOLFTAADDLONAXHDCLAXKGJKRTMOLNJESSTIWEGEETRYWMMYTADMJLGDTNFGROVTURFTVLLSPRPDGEWYSAJEAAHDCXENGSFYJZDYEOSTNEKGKGDYHSKBLERDYLPKAORPKETTHTDTGTSGUOWTLNJZEMTSOTAHTAADEHNBAMTAADDYOTADLOCSDYYKAEYKAEYKAOYKAOCYVOLNKGRPAXAAAYCYTLCYTSWKTAADDLONAXHDCLAOMNNYUOWPTLNEMOFGNTFWTNWNIDGWJZROTEVABSIYVDMWGLGYWKMSIOSKAAKPMOMTAAHDCXJYBYWNPACECEWZGUCPWDSNWZKPSKIEWNEMVLAXFXVDVDCATSNDISBZCNCYJSIHWMAHTAADEHNBAMTAADDYOTADLOCSDYYKAEYKAEYKAOYKAOCYPMSSAYFRAXAAAYCYEEHHWZWSTTFYFTLY").unwrap();
+        assert_eq!(
+            params.descriptor().unwrap().to_string(),
+            "wsh(sortedmulti(2,[e2867bb6/48'/0'/0'/2']xpub6F6kdziwgeYBty6BhQQxf73MxQDNPcKKApsvENDUEx3mBPumWC4GcBFi7gk8wE7r2V4gKRmC92UXPWPxz7HxP3U4n46xutvWWLsUAWswLWe/0/*,[adc4083b/48'/0'/0'/2']xpub6DvE8qstk42wTk45g4KedXXLkbnkhFdYRimfinAAf5YqWq1oMRjZaYcrmp6PzfgZftzDrzhcmWpWx4YNRrPJFKvKZUAc8NKx9Q2SUyMpEts/0/*))#kkrdcavr"
+        );
+    }
+
+    #[test]
+    fn test_invalid_ur() {
+        assert!(parse_ur("ur:crypto-psbt").is_none());
+        assert!(parse_ur("ur:crypto-psbt/1-3/lpadaxcfaxhl").is_none());
+        assert!(parse_ur("ur:unknown-type/hdeymejtswhhylkepmykhhtsytsnoyoyaxaedsuttydmmhhpktpmsrjtgwdpfnsboxgwlbaawzuefywkdplrsrjynbvygabwjldapfcsdwkbrkch").is_none());
+    }
+}
@@ -0,0 +1,199 @@
+use core::fmt;
+use core::str::FromStr;
+
+use bitcoin::base58;
+use bitcoin::bip32::{self, ExtendedPubKey, Fingerprint};
+use bitcoin::Network;
+
+/// The script type implied by a SLIP-132 extended key prefix
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum XpubScriptType {
+    /// `ypub`/`upub`, BIP-49
+    NestedSegwit,
+    /// `zpub`/`vpub`, BIP-84
+    NativeSegwit,
+    /// `Ypub`/`Upub`
+    NestedSegwitMultisig,
+    /// `Zpub`/`Vpub`
+    NativeSegwitMultisig,
+}
+
+/// Version bytes of every extended public key prefix we recognize.
+/// `xpub` and `tpub` don't imply a script type.
+const VERSIONS: [([u8; 4], Network, Option<XpubScriptType>); 10] = [
+    ([0x04, 0x88, 0xb2, 0x1e], Network::Bitcoin, None),
+    (
+        [0x04, 0x9d, 0x7c, 0xb2],
+        Network::Bitcoin,
+        Some(XpubScriptType::NestedSegwit),
+    ),
+    (
+        [0x04, 0xb2, 0x47, 0x46],
+        Network::Bitcoin,
+        Some(XpubScriptType::NativeSegwit),
+    ),
+    (
+        [0x02, 0x95, 0xb4, 0x3f],
+        Network::Bitcoin,
+        Some(XpubScriptType::NestedSegwitMultisig),
+    ),
+    (
+        [0x02, 0xaa, 0x7e, 0xd3],
+        Network::Bitcoin,
+        Some(XpubScriptType::NativeSegwitMultisig),
+    ),
+    ([0x04, 0x35, 0x87, 0xcf], Network::Testnet, None),
+    (
+        [0x04, 0x4a, 0x52, 0x62],
+        Network::Testnet,
+        Some(XpubScriptType::NestedSegwit),
+    ),
+    (
+        [0x04, 0x5f, 0x1c, 0xf6],
+        Network::Testnet,
+        Some(XpubScriptType::NativeSegwit),
+    ),
+    (
+        [0x02, 0x42, 0x89, 0xef],
+        Network::Testnet,
+        Some(XpubScriptType::NestedSegwitMultisig),
+    ),
+    (
+        [0x02, 0x57, 0x54, 0x83],
+        Network::Testnet,
+        Some(XpubScriptType::NativeSegwitMultisig),
+    ),
+];
+
+/// An extended public key, including SLIP-132 encodings like `zpub`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Xpub {
+    /// The key, always in its `xpub`/`tpub` form
+    pub key: ExtendedPubKey,
+    pub script_type: Option<XpubScriptType>,
+}
+
+impl Xpub {
+    pub fn network(&self) -> Network {
+        self.key.network
+    }
+
+    pub fn depth(&self) -> u8 {
+        self.key.depth
+    }
+
+    pub fn fingerprint(&self) -> Fingerprint {
+        self.key.fingerprint()
+    }
+
+    pub fn parent_fingerprint(&self) -> Fingerprint {
+        self.key.parent_fingerprint
+    }
+
+    fn version(&self) -> [u8; 4] {
+        VERSIONS
+            .iter()
+            .find(|(_, network, script_type)| {
+                *network == self.key.network && *script_type == self.script_type
+            })
+            .map(|(version, _, _)| *version)
+            .expect("all combinations have a version")
+    }
+}
+
+impl fmt::Display for Xpub {
+    /// Encodes the key with the same prefix it was parsed with
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut data = self.key.encode();
+        data[..4].copy_from_slice(&self.version());
+        f.write_str(&base58::encode_check(&data))
+    }
+}
+
+impl FromStr for Xpub {
+    type Err = bip32::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut data = base58::decode_check(s).map_err(bip32::Error::Base58)?;
+        if data.len() != 78 {
+            return Err(bip32::Error::WrongExtendedKeyLength(data.len()));
+        }
+        let mut version = [0; 4];
+        version.copy_from_slice(&data[..4]);
+
+        let (network, script_type) = VERSIONS
+            .iter()
+            .find(|(v, _, _)| *v == version)
+            .map(|(_, network, script_type)| (*network, *script_type))
+            .ok_or(bip32::Error::UnknownVersion(version))?;
+
+        // swap to the standard version bytes so the bitcoin crate can decode it
+        let standard = VERSIONS
+            .iter()
+            .find(|(_, n, script_type)| *n == network && script_type.is_none())
+            .map(|(version, _, _)| version)
+            .expect("every network has a standard version");
+        data[..4].copy_from_slice(standard);
+
+        let key = ExtendedPubKey::decode(&data)?;
+        Ok(Xpub { key, script_type })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::str::FromStr;
+
+    use bitcoin::Network;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_xpub() {
+        let xpub = Xpub::from_str("xpub6D3anNhuPFsyitz6c7Kgp1sS3Dcz5CReqh3Pf8CBaBxNy9PYSuKKz4869P2hbxQJccVAKAbfU5xz7VYDBQDcubAPNZ73ADBoxvnMB4PXXYE").unwrap();
+        assert_eq!(xpub.network(), Network::Bitcoin);
+        assert_eq!(xpub.script_type, None);
+        assert_eq!(xpub.depth(), 3);
+        assert_eq!(xpub.parent_fingerprint().to_string(), "bd912734");
+
+        let tpub = Xpub::from_str("tpubDDPRy5xWxJTuVmsh7YRzK8o2EdMWgn4t41fTLxXRgyRN7EKvN2L8BKCFC1gUfPu8Xp6rr667Yc26zrXsiBZsgBc8dQiYnhPNk2Q7CsBrer5").unwrap();
+        assert_eq!(tpub.network(), Network::Testnet);
+        assert_eq!(tpub.script_type, None);
+    }
+
+    #[test]
+    fn test_parse_slip132() {
+        let zpub = Xpub::from_str("zpub6ri7Pi3jgcxwRVNLGptwEC4SP9usxSQefv5qDuyxLCi95M1zxDeTEBSNBnwsbmi9Rtimp7nnPQg5t4mLco3eW4Xb7EVtL2pnWNudxFHiG9E").unwrap();
+        assert_eq!(zpub.network(), Network::Bitcoin);
+        assert_eq!(zpub.script_type, Some(XpubScriptType::NativeSegwit));
+        assert_eq!(
+            zpub.key.to_string(),
+            "xpub6D3anNhuPFsyitz6c7Kgp1sS3Dcz5CReqh3Pf8CBaBxNy9PYSuKKz4869P2hbxQJccVAKAbfU5xz7VYDBQDcubAPNZ73ADBoxvnMB4PXXYE"
+        );
+        assert_eq!(
+            zpub.to_string(),
+            "zpub6ri7Pi3jgcxwRVNLGptwEC4SP9usxSQefv5qDuyxLCi95M1zxDeTEBSNBnwsbmi9Rtimp7nnPQg5t4mLco3eW4Xb7EVtL2pnWNudxFHiG9E"
+        );
+
+        let ypub = Xpub::from_str("ypub6XRxXpVUGvwP8pqiAVJyEnAQ2AfYCUUwzt7aRdbZfeTX47zCtk7bwmWPwKMNK9guxnRxeS4FjRD5PMH22ZDiR8ar3oDznTMGqk1xCh82LAp").unwrap();
+        assert_eq!(ypub.script_type, Some(XpubScriptType::NestedSegwit));
+
+        let vpub = Xpub::from_str("vpub5ZMGqWnzwfRVXPJbzdseh3GeZZxxp9apgA2iRcgWb3uiFk2knV9unkD8uZgX95e3vdLLjZP86BUxY6p8svtcCGndq8aPwM6AccgQfk1KZb3").unwrap();
+        assert_eq!(vpub.network(), Network::Testnet);
+        assert_eq!(vpub.script_type, Some(XpubScriptType::NativeSegwit));
+
+        let multisig = Xpub::from_str("Zpub75fNNZoDYyBX28doJnTBuMaB28YXVCyuKKa2iRGnNjdvun78mumfwRRuxocnVUeb5ENGgxxRwZa8HFErijGw7zwwMCcDV82UwXGdXcQFgi5").unwrap();
+        assert_eq!(
+            multisig.script_type,
+            Some(XpubScriptType::NativeSegwitMultisig)
+        );
+        assert_eq!(multisig.depth(), 4);
+    }
+
+    #[test]
+    fn test_invalid_xpub() {
+        // private keys are not accepted
+        assert!(Xpub::from_str("xprv9s21ZrQH143K3QTDL4LXw2F7HEK3wJUD2nW2nRk4stbPy6cq3jPPqjiChkVvvNKmPGJxWUtg6LnF5kejMRNNU3TGtRBeJgk33yuGBxrMPHi").is_err());
+        assert!(Xpub::from_str("1andreas3batLhQa2FawWjeyjCqyBzypd").is_err());
+    }
+}